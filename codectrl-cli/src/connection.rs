@@ -0,0 +1,211 @@
+// region: imports
+
+use anyhow::Context;
+use clap::{Arg, ArgMatches};
+use codectrl_common::project::PROJECT_HEADER;
+use std::{env, fs};
+use tonic::{
+    metadata::{Ascii, MetadataValue},
+    service::Interceptor,
+    transport::{Certificate, Channel, ClientTlsConfig, Identity},
+    Request, Status,
+};
+
+#[cfg(unix)]
+use tokio::net::UnixStream;
+#[cfg(unix)]
+use tonic::transport::Uri;
+#[cfg(unix)]
+use tower::service_fn;
+
+// endregion
+
+/// The prefix of hosts that are a Unix socket rather than a network address,
+/// i.e. `unix:/run/codectrl/codectrl.sock`.
+const UNIX_SOCKET_PREFIX: &str = "unix:";
+
+/// The arguments for how to connect to the server, shared by every
+/// subcommand.
+pub fn connection_args() -> Vec<Arg<'static>> {
+    vec![
+        Arg::new("host")
+            .takes_value(true)
+            .short('H')
+            .long("host")
+            .default_value("127.0.0.1")
+            .help(
+                "The host of the CodeCTRL server, or the path of its Unix socket \
+                 prefixed with `unix:` (i.e. unix:/run/codectrl/codectrl.sock)",
+            ),
+        Arg::new("port")
+            .takes_value(true)
+            .short('p')
+            .long("port")
+            .default_value("3002")
+            .help("The port of the CodeCTRL server"),
+        Arg::new("token").takes_value(true).long("token").help(
+            "The token to log in with, if the server requires one. Can also be \
+             specified with the CODECTRL_TOKEN environment variable.",
+        ),
+        Arg::new("project")
+            .takes_value(true)
+            .long("project")
+            .help("Only shows the logs of this project"),
+        Arg::new("tls")
+            .long("tls")
+            .help("Connects to the server over TLS"),
+        Arg::new("ca_cert")
+            .takes_value(true)
+            .long("ca-cert")
+            .requires("tls")
+            .help(
+                "The PEM file of the CA that signed the server's certificate, the \
+                 system's root certificates are trusted if not given",
+            ),
+        Arg::new("client_cert")
+            .takes_value(true)
+            .long("client-cert")
+            .requires_all(&["tls", "client_key"])
+            .help("The PEM file of the certificate to present to the server"),
+        Arg::new("client_key")
+            .takes_value(true)
+            .long("client-key")
+            .requires_all(&["tls", "client_cert"])
+            .help("The PEM file of the private key of the client certificate"),
+    ]
+}
+
+/// Sends the token and project given on the command line with every request.
+#[derive(Debug, Clone)]
+pub struct Credentials {
+    token: Option<MetadataValue<Ascii>>,
+    project: Option<MetadataValue<Ascii>>,
+}
+
+impl Interceptor for Credentials {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        if let Some(token) = &self.token {
+            request
+                .metadata_mut()
+                .insert("authorization", token.clone());
+        }
+
+        if let Some(project) = &self.project {
+            request
+                .metadata_mut()
+                .insert(PROJECT_HEADER, project.clone());
+        }
+
+        Ok(request)
+    }
+}
+
+/// # Errors
+///
+/// Returns an error if the token or project can't be sent in a header.
+pub fn credentials(matches: &ArgMatches) -> anyhow::Result<Credentials> {
+    let token = matches
+        .value_of("token")
+        .map(String::from)
+        .or_else(|| env::var("CODECTRL_TOKEN").ok())
+        .filter(|token| !token.is_empty());
+
+    Ok(Credentials {
+        token: token
+            .map(|token| format!("Bearer {token}").parse())
+            .transpose()
+            .context("The token is not valid")?,
+        project: matches
+            .value_of("project")
+            .map(str::parse)
+            .transpose()
+            .context("The project name is not valid")?,
+    })
+}
+
+/// Reads the PEM files that were given into the TLS config of the connection,
+/// or returns `None` if TLS isn't used.
+fn tls_config(matches: &ArgMatches) -> anyhow::Result<Option<ClientTlsConfig>> {
+    if !matches.is_present("tls") {
+        return Ok(None);
+    }
+
+    let read = |name: &str| {
+        matches
+            .value_of(name)
+            .map(|path| {
+                fs::read(path).with_context(|| format!("Could not read TLS file {path}"))
+            })
+            .transpose()
+    };
+
+    let mut tls_config = ClientTlsConfig::new();
+
+    if let Some(ca_cert) = read("ca_cert")? {
+        tls_config = tls_config.ca_certificate(Certificate::from_pem(ca_cert));
+    }
+
+    if let (Some(cert), Some(key)) = (read("client_cert")?, read("client_key")?) {
+        tls_config = tls_config.identity(Identity::from_pem(cert, key));
+    }
+
+    Ok(Some(tls_config))
+}
+
+/// Connects to the server at the host and port given on the command line, or
+/// to the Unix socket in the host if it has the [`UNIX_SOCKET_PREFIX`].
+///
+/// # Errors
+///
+/// Returns an error if the TLS files can't be read or the server can't be
+/// connected to.
+pub async fn connect(matches: &ArgMatches) -> anyhow::Result<Channel> {
+    let host = matches.value_of("host").unwrap_or("127.0.0.1");
+    let port = matches
+        .value_of("port")
+        .unwrap_or("3002")
+        .parse::<u32>()
+        .context("Port was not a valid value: needs to be an integer value.")?;
+    let tls_config = tls_config(matches)?;
+
+    let scheme = if tls_config.is_some() {
+        "https"
+    } else {
+        "http"
+    };
+    let unix_socket = host.strip_prefix(UNIX_SOCKET_PREFIX);
+
+    // The address isn't used to connect to a Unix socket, but tonic still needs
+    // one for the requests and to check the server's certificate against.
+    let address = if unix_socket.is_some() {
+        format!("{scheme}://localhost")
+    } else {
+        format!("{scheme}://{host}:{port}")
+    };
+
+    let mut endpoint = Channel::from_shared(address)?;
+
+    if let Some(tls_config) = tls_config {
+        endpoint = endpoint.tls_config(tls_config)?;
+    }
+
+    match unix_socket {
+        #[cfg(unix)]
+        Some(path) => {
+            let path = path.to_string();
+
+            endpoint
+                .connect_with_connector(service_fn(move |_: Uri| {
+                    UnixStream::connect(path.clone())
+                }))
+                .await
+                .with_context(|| format!("Could not connect to {host}"))
+        },
+        #[cfg(not(unix))]
+        Some(_) => anyhow::bail!("Unix sockets are not supported on this platform"),
+        None => endpoint
+            .connect()
+            .await
+            .with_context(|| format!("Could not connect to {host}:{port}")),
+    }
+}
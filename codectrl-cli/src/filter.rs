@@ -0,0 +1,201 @@
+// region: imports
+
+use anyhow::{anyhow, Context};
+use chrono::{DateTime, TimeZone, Utc};
+use clap::{Arg, ArgMatches};
+use codectrl_common::{
+    fields::Fields, proto::log_search::LogQuery, query::LogMatcher, severity::Severity,
+};
+use codectrl_protobuf_bindings::data::Log;
+
+// endregion
+
+/// The filters shared by every subcommand, matching the filters available in
+/// the GUI.
+pub fn filter_args() -> Vec<Arg<'static>> {
+    vec![
+        Arg::new("message")
+            .takes_value(true)
+            .short('m')
+            .long("message")
+            .help("Only shows logs whose message contains this"),
+        Arg::new("file_name")
+            .takes_value(true)
+            .short('f')
+            .long("file-name")
+            .help("Only shows logs whose file name contains this"),
+        Arg::new("address")
+            .takes_value(true)
+            .short('a')
+            .long("address")
+            .help(
+                "Only shows logs sent from this address, `*` matches anything (i.e. \
+                 192.168.*.*)",
+            ),
+        Arg::new("line_number")
+            .takes_value(true)
+            .short('l')
+            .long("line-number")
+            .help("Only shows logs sent from this line number"),
+        Arg::new("message_type")
+            .takes_value(true)
+            .short('t')
+            .long("message-type")
+            .help("Only shows logs whose message type contains this"),
+        Arg::new("severity")
+            .takes_value(true)
+            .short('s')
+            .long("severity")
+            .possible_values(["trace", "debug", "info", "warn", "error", "fatal"])
+            .help("Only shows logs that are at least this severe"),
+        Arg::new("field")
+            .takes_value(true)
+            .multiple_occurrences(true)
+            .short('F')
+            .long("field")
+            .help(
+                "Only shows logs with this structured field, or with this value for it \
+                 (i.e. user.id=42). Can be given more than once",
+            ),
+        Arg::new("has_warnings")
+            .long("has-warnings")
+            .conflicts_with("no_warnings")
+            .help("Only shows logs with warnings"),
+        Arg::new("no_warnings")
+            .long("no-warnings")
+            .help("Only shows logs without warnings"),
+        Arg::new("since").takes_value(true).long("since").help(
+            "Only shows logs received at or after this time, as an RFC 3339 timestamp \
+             or milliseconds since the Unix epoch",
+        ),
+        Arg::new("until").takes_value(true).long("until").help(
+            "Only shows logs received before this time, as an RFC 3339 timestamp or \
+             milliseconds since the Unix epoch",
+        ),
+        Arg::new("regex")
+            .short('r')
+            .long("regex")
+            .help("Treats the message, file name, address and type as regexes"),
+        Arg::new("case_sensitive")
+            .short('c')
+            .long("case-sensitive")
+            .help("Matches the message, file name, address and type case sensitively"),
+    ]
+}
+
+fn parse_time(time: &str) -> anyhow::Result<i64> {
+    if let Ok(millis) = time.parse::<i64>() {
+        return Ok(millis);
+    }
+
+    DateTime::parse_from_rfc3339(time)
+        .map(|time| time.timestamp_millis())
+        .with_context(|| format!("\"{time}\" is not a valid time"))
+}
+
+/// Builds the query that the filters in `matches` describe.
+///
+/// # Errors
+///
+/// Returns an error if the line number or times are invalid.
+pub fn query_from_matches(matches: &ArgMatches) -> anyhow::Result<LogQuery> {
+    let string = |name: &str| matches.value_of(name).map(String::from);
+
+    Ok(LogQuery {
+        message: string("message"),
+        file_name: string("file_name"),
+        address: string("address"),
+        line_number: matches
+            .value_of("line_number")
+            .map(str::parse)
+            .transpose()
+            .context("The line number needs to be a positive integer")?,
+        message_type: string("message_type"),
+        min_severity: string("severity"),
+        fields: matches
+            .values_of("field")
+            .map(|fields| fields.map(String::from).collect())
+            .unwrap_or_default(),
+        has_warnings: if matches.is_present("has_warnings") {
+            Some(true)
+        } else if matches.is_present("no_warnings") {
+            Some(false)
+        } else {
+            None
+        },
+        received_after: matches.value_of("since").map(parse_time).transpose()?,
+        received_before: matches.value_of("until").map(parse_time).transpose()?,
+        regex: matches.is_present("regex"),
+        case_sensitive: matches.is_present("case_sensitive"),
+        ..LogQuery::default()
+    })
+}
+
+/// Applies a [`LogQuery`] to logs as they are received, for tailing logs.
+#[derive(Debug, Clone)]
+pub struct LogFilter {
+    matcher: LogMatcher,
+    line_number: Option<u32>,
+    min_severity: Option<Severity>,
+    has_warnings: Option<bool>,
+    received_after: Option<DateTime<Utc>>,
+    received_before: Option<DateTime<Utc>>,
+}
+
+impl LogFilter {
+    /// # Errors
+    ///
+    /// Returns an error if one of the string filters is an invalid regex, the
+    /// severity is invalid, or one of the times is out of range.
+    pub fn new(query: &LogQuery) -> anyhow::Result<Self> {
+        let timestamp = |millis: Option<i64>| {
+            millis
+                .map(|millis| {
+                    Utc.timestamp_millis_opt(millis)
+                        .single()
+                        .ok_or_else(|| anyhow!("{millis} is out of range"))
+                })
+                .transpose()
+        };
+
+        Ok(Self {
+            matcher: LogMatcher::new(query)?,
+            line_number: query.line_number,
+            min_severity: query
+                .min_severity
+                .as_deref()
+                .map(str::parse)
+                .transpose()
+                .map_err(|error: String| anyhow!(error))?,
+            has_warnings: query.has_warnings,
+            received_after: timestamp(query.received_after)?,
+            received_before: timestamp(query.received_before)?,
+        })
+    }
+
+    pub fn matches(
+        &self,
+        log: &Log,
+        severity: Severity,
+        fields: &Fields,
+        received_at: DateTime<Utc>,
+    ) -> bool {
+        self.matcher.matches(log)
+            && self.matcher.matches_fields(fields)
+            && self
+                .line_number
+                .map_or(true, |line_number| log.line_number == line_number)
+            && self
+                .min_severity
+                .map_or(true, |min_severity| severity >= min_severity)
+            && self
+                .has_warnings
+                .map_or(true, |has_warnings| log.warnings.is_empty() != has_warnings)
+            && self
+                .received_after
+                .map_or(true, |received_after| received_at >= received_after)
+            && self
+                .received_before
+                .map_or(true, |received_before| received_at < received_before)
+    }
+}
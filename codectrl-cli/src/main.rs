@@ -0,0 +1,261 @@
+#![warn(clippy::pedantic)]
+
+mod connection;
+mod filter;
+mod output;
+
+// region: imports
+
+use anyhow::Context;
+use chrono::{TimeZone, Utc};
+use clap::{crate_authors, crate_description, crate_name, crate_version, Arg, Command};
+use codectrl_common::{
+    fields::Fields,
+    proto::log_search::{log_search_client::LogSearchClient, LogQuery, QueriedLog},
+};
+use codectrl_protobuf_bindings::logs_service::log_server_client::LogServerClient;
+use connection::{connect, connection_args, credentials, Credentials};
+use filter::{filter_args, query_from_matches, LogFilter};
+use output::{print_log, Format};
+use std::time::Duration;
+use tokio::time::sleep;
+use tonic::{transport::Channel, Code};
+
+// endregion
+
+/// How long `tail` waits before reconnecting the first time, doubling each
+/// time reconnecting fails up to [`MAX_RECONNECT_DELAY`].
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// Prints a log that the server sent along with its severity and fields, if it
+/// matches `filter`. Returns whether it was printed.
+fn print_queried(
+    format: Format,
+    queried: QueriedLog,
+    filter: Option<&LogFilter>,
+) -> anyhow::Result<bool> {
+    let received_at = Utc
+        .timestamp_millis_opt(queried.received_at)
+        .single()
+        .unwrap_or_else(Utc::now);
+    let project = Some(queried.project.as_str()).filter(|p| !p.is_empty());
+    let severity = queried.severity.parse().unwrap_or_default();
+    let fields: Fields = serde_json::from_str(&queried.fields).unwrap_or_default();
+
+    match queried.log {
+        Some(log)
+            if filter.map_or(true, |filter| {
+                filter.matches(&log, severity, &fields, received_at)
+            }) =>
+        {
+            print_log(format, &log, severity, &fields, received_at, project)?;
+            Ok(true)
+        },
+        _ => Ok(false),
+    }
+}
+
+/// Registers a connection and prints every log the server sends down it, first
+/// the stored logs and then new logs as they arrive. If the stream is
+/// interrupted, i.e. by the server restarting, the connection is registered
+/// again and the logs it missed are sent once the server is back.
+async fn tail(
+    channel: Channel,
+    credentials: Credentials,
+    query: &LogQuery,
+    format: Format,
+) -> anyhow::Result<()> {
+    let filter = LogFilter::new(query)?;
+    let mut client =
+        LogServerClient::with_interceptor(channel.clone(), credentials.clone());
+    let mut search_client = LogSearchClient::with_interceptor(channel, credentials);
+
+    let mut connection = client
+        .register_client(())
+        .await
+        .context("Could not register with the server")?
+        .into_inner();
+    let mut is_registered = true;
+    let mut delay = RECONNECT_DELAY;
+
+    loop {
+        if !is_registered {
+            match client.register_existing_client(connection.clone()).await {
+                Ok(_) => is_registered = true,
+                // The server lost its database, so the connection has to be
+                // registered from scratch.
+                Err(status) if status.code() == Code::NotFound =>
+                    match client.register_client(()).await {
+                        Ok(response) => {
+                            connection = response.into_inner();
+                            is_registered = true;
+                        },
+                        Err(status) => eprintln!(
+                            "Could not register with the server: {}",
+                            status.message()
+                        ),
+                    },
+                Err(status) => eprintln!(
+                    "Could not register with the server again: {}",
+                    status.message()
+                ),
+            }
+        }
+
+        if is_registered {
+            match search_client.stream_logs(connection.clone()).await {
+                Ok(response) => {
+                    let mut stream = response.into_inner();
+
+                    loop {
+                        match stream.message().await {
+                            Ok(Some(queried)) => {
+                                delay = RECONNECT_DELAY;
+                                print_queried(format, queried, Some(&filter))?;
+                            },
+                            Ok(None) => break,
+                            Err(status) => {
+                                eprintln!(
+                                    "The log stream was interrupted: {}",
+                                    status.message()
+                                );
+                                break;
+                            },
+                        }
+                    }
+                },
+                Err(status) => eprintln!("Could not get logs: {}", status.message()),
+            }
+
+            // The server forgets about the connection if it restarted, so it
+            // has to be registered again before it can be streamed from.
+            is_registered = false;
+        }
+
+        sleep(delay).await;
+        delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+    }
+}
+
+/// Prints up to `limit` logs matching `query`, fetching as many pages as that
+/// takes.
+async fn query(
+    channel: Channel,
+    credentials: Credentials,
+    mut query: LogQuery,
+    limit: Option<usize>,
+    format: Format,
+) -> anyhow::Result<()> {
+    let mut client = LogSearchClient::with_interceptor(channel, credentials);
+    let mut printed = 0;
+
+    loop {
+        let page = client
+            .query_logs(query.clone())
+            .await
+            .map_err(|status| anyhow::anyhow!("{}", status.message()))?
+            .into_inner();
+
+        for queried in page.logs {
+            if matches!(limit, Some(limit) if printed >= limit) {
+                return Ok(());
+            }
+
+            if print_queried(format, queried, None)? {
+                printed += 1;
+            }
+        }
+
+        if page.next_page_token.is_empty() {
+            return Ok(());
+        }
+
+        query.page_token = page.next_page_token;
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let matches = Command::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!(", "))
+        .about(crate_description!())
+        .subcommand_required(true)
+        .args(connection_args())
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .help("Prints one JSON object per log rather than formatted text"),
+        )
+        .arg(
+            Arg::new("no_colour")
+                .long("no-colour")
+                .alias("no-color")
+                .help("Disables coloured output"),
+        )
+        .subcommand(
+            Command::new("tail")
+                .about("Prints logs as the server receives them")
+                .args(filter_args()),
+        )
+        .subcommand(
+            Command::new("query")
+                .about("Searches the logs stored on the server")
+                .args(filter_args())
+                .arg(
+                    Arg::new("limit")
+                        .takes_value(true)
+                        .short('n')
+                        .long("limit")
+                        .default_value("100")
+                        .help("The most logs to print, 0 prints every matching log"),
+                )
+                .arg(
+                    Arg::new("newest_first")
+                        .long("newest-first")
+                        .help("Prints the newest logs first"),
+                ),
+        )
+        .get_matches();
+
+    let format = if matches.is_present("json") {
+        Format::JsonLines
+    } else {
+        Format::Pretty
+    };
+
+    if matches.is_present("no_colour") || format == Format::JsonLines {
+        colored::control::set_override(false);
+    }
+
+    let channel = connect(&matches).await?;
+    let credentials = credentials(&matches)?;
+
+    match matches.subcommand() {
+        Some(("tail", matches)) =>
+            tail(channel, credentials, &query_from_matches(matches)?, format).await,
+        Some(("query", matches)) => {
+            let limit = matches
+                .value_of("limit")
+                .unwrap_or("100")
+                .parse::<usize>()
+                .context("The limit needs to be a positive integer")?;
+
+            let query = LogQuery {
+                newest_first: matches.is_present("newest_first"),
+                ..query_from_matches(matches)?
+            };
+
+            self::query(
+                channel,
+                credentials,
+                query,
+                Some(limit).filter(|limit| *limit > 0),
+                format,
+            )
+            .await
+        },
+        _ => unreachable!("a subcommand is required"),
+    }
+}
@@ -0,0 +1,112 @@
+// region: imports
+
+use chrono::{DateTime, Local, Utc};
+use codectrl_common::{fields::Fields, severity::Severity};
+use codectrl_protobuf_bindings::data::Log;
+use colored::{ColoredString, Colorize};
+use serde_json::json;
+
+// endregion
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Colourised, human readable output.
+    Pretty,
+    /// One JSON object per line.
+    JsonLines,
+}
+
+/// Prints `log` to stdout in the given `format`.
+///
+/// # Errors
+///
+/// Returns an error if the log could not be serialised to JSON.
+pub fn print_log(
+    format: Format,
+    log: &Log,
+    severity: Severity,
+    fields: &Fields,
+    received_at: DateTime<Utc>,
+    project: Option<&str>,
+) -> anyhow::Result<()> {
+    match format {
+        Format::Pretty => print_pretty(log, severity, received_at, project),
+        Format::JsonLines => println!(
+            "{}",
+            serde_json::to_string(&json!({
+                "received_at": received_at.to_rfc3339(),
+                "project": project,
+                "severity": severity.to_string(),
+                "fields": fields,
+                "log": log,
+            }))?
+        ),
+    }
+
+    Ok(())
+}
+
+fn coloured_severity(severity: Severity) -> ColoredString {
+    let name = format!("{:<5}", severity.to_string().to_uppercase());
+
+    match severity {
+        Severity::Trace => name.dimmed(),
+        Severity::Debug => name.blue(),
+        Severity::Info => name.green(),
+        Severity::Warn => name.yellow(),
+        Severity::Error => name.red(),
+        Severity::Fatal => name.red().bold().reversed(),
+    }
+}
+
+fn print_pretty(
+    log: &Log,
+    severity: Severity,
+    received_at: DateTime<Utc>,
+    project: Option<&str>,
+) {
+    let time = received_at
+        .with_timezone(&Local)
+        .format("%F %X")
+        .to_string();
+    let location = format!("{}:{}", log.file_name, log.line_number);
+    let project = project
+        .map(|project| format!(" [{project}]"))
+        .unwrap_or_default();
+
+    println!(
+        "{} {} {} {}{} from {}",
+        time.dimmed(),
+        coloured_severity(severity),
+        location.cyan().bold(),
+        log.message_type.magenta(),
+        project.green(),
+        log.address.dimmed(),
+    );
+
+    for line in log.message.lines() {
+        println!("    {line}");
+    }
+
+    for warning in &log.warnings {
+        println!("    {} {warning}", "warning:".yellow().bold());
+    }
+
+    // The stack is sent outermost call first, like a backtrace it's more useful
+    // to see where the log was sent from first.
+    for stack in log.stack.iter().rev() {
+        println!(
+            "    {} {}",
+            "at".dimmed(),
+            format!(
+                "{}:{}:{}",
+                stack.file_path, stack.line_number, stack.column_number
+            )
+            .blue(),
+        );
+
+        if !stack.code.trim().is_empty() {
+            println!("        {}", stack.code.trim().dimmed());
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use std::env;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // The web GUI only needs the clients, and the generated servers need
+    // `tonic::transport`, which doesn't build for wasm.
+    let is_wasm =
+        env::var("CARGO_CFG_TARGET_ARCH").map_or(false, |arch| arch == "wasm32");
+
+    tonic_build::configure()
+        .build_server(!is_wasm)
+        .extern_path(
+            ".codectrl.server.external.Log",
+            "::codectrl_protobuf_bindings::data::Log",
+        )
+        .extern_path(
+            ".codectrl.server.external.Connection",
+            "::codectrl_protobuf_bindings::logs_service::Connection",
+        )
+        .compile(&["proto/log_search.proto"], &["proto"])?;
+
+    Ok(())
+}
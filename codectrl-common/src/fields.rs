@@ -0,0 +1,88 @@
+// region: imports
+
+use serde_json::{Map, Value};
+
+// endregion
+
+/// The metadata header that loggers can use to attach structured fields to a
+/// log, as a JSON object.
+pub const FIELDS_HEADER: &str = "x-fields";
+
+/// The structured key/value fields of a log. The server sends them along with
+/// each log as a JSON object.
+pub type Fields = Map<String, Value>;
+
+/// Parses the fields out of a message that is a JSON object. Loggers that
+/// format their messages with `Debug` send it as a quoted JSON string, which is
+/// unquoted first.
+#[must_use]
+pub fn from_message(message: &str) -> Option<Fields> {
+    match serde_json::from_str(message.trim()).ok()? {
+        Value::Object(fields) => Some(fields),
+        Value::String(message) => match serde_json::from_str(&message).ok()? {
+            Value::Object(fields) => Some(fields),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Looks up a field by its dotted `path`, i.e. `user.id` or `items.0.name`.
+#[must_use]
+pub fn lookup<'a>(fields: &'a Fields, path: &str) -> Option<&'a Value> {
+    let mut keys = path.split('.');
+    let mut value = fields.get(keys.next()?)?;
+
+    for key in keys {
+        value = match value {
+            Value::Object(object) => object.get(key)?,
+            Value::Array(array) => array.get(key.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+
+    Some(value)
+}
+
+/// Formats a field value for display and matching, strings without quotes.
+#[must_use]
+pub fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(string) => string.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// A `field=value` filter, which matches logs that have a field at `path`
+/// equal to `value`. A filter with just a path matches logs that have the field
+/// at all.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldFilter {
+    pub path: String,
+    pub value: Option<String>,
+}
+
+impl FieldFilter {
+    #[must_use]
+    pub fn parse(filter: &str) -> Self {
+        match filter.split_once('=') {
+            Some((path, value)) => Self {
+                path: path.trim().to_string(),
+                value: Some(value.trim().to_string()),
+            },
+            None => Self {
+                path: filter.trim().to_string(),
+                value: None,
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn matches(&self, fields: &Fields) -> bool {
+        match (lookup(fields, &self.path), &self.value) {
+            (Some(field), Some(value)) => value_to_string(field) == *value,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+}
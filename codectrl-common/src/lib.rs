@@ -0,0 +1,10 @@
+//! The parts of CodeCTRL that both the server and its clients need, which have
+//! to build for the web GUI as well.
+
+#![warn(clippy::pedantic)]
+
+pub mod fields;
+pub mod project;
+pub mod proto;
+pub mod query;
+pub mod severity;
@@ -0,0 +1,3 @@
+/// The metadata header that loggers and clients use to say which project they
+/// are sending or viewing logs for.
+pub const PROJECT_HEADER: &str = "x-project";
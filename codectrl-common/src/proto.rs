@@ -0,0 +1,8 @@
+//! gRPC services that the server provides on top of
+//! `codectrl-protobuf-bindings`, which its clients use too.
+
+#![allow(clippy::pedantic, clippy::derive_partial_eq_without_eq)]
+
+pub mod log_search {
+    tonic::include_proto!("codectrl.server.log_search");
+}
@@ -0,0 +1,78 @@
+// region: imports
+
+use crate::{
+    fields::{FieldFilter, Fields},
+    proto::log_search::LogQuery,
+};
+use codectrl_protobuf_bindings::data::Log;
+use regex::{escape, Regex, RegexBuilder};
+
+// endregion
+
+/// The string filters of a [`LogQuery`], compiled into regular expressions so
+/// that substring, case insensitive and regex matching all work the same way,
+/// along with its field filters. The other filters are turned into SQL instead.
+#[derive(Debug, Clone, Default)]
+pub struct LogMatcher {
+    message: Option<Regex>,
+    file_name: Option<Regex>,
+    address: Option<Regex>,
+    message_type: Option<Regex>,
+    fields: Vec<FieldFilter>,
+}
+
+impl LogMatcher {
+    /// # Errors
+    ///
+    /// Returns an error if one of the string filters is an invalid regular
+    /// expression.
+    pub fn new(query: &LogQuery) -> Result<Self, regex::Error> {
+        let compile = |filter: Option<&String>, is_address: bool| {
+            filter
+                .map(|filter| {
+                    let pattern = if query.regex {
+                        filter.clone()
+                    } else if is_address {
+                        filter.split('*').map(escape).collect::<Vec<_>>().join(".*")
+                    } else {
+                        escape(filter)
+                    };
+
+                    RegexBuilder::new(&pattern)
+                        .case_insensitive(!query.case_sensitive)
+                        .build()
+                })
+                .transpose()
+        };
+
+        Ok(Self {
+            message: compile(query.message.as_ref(), false)?,
+            file_name: compile(query.file_name.as_ref(), false)?,
+            address: compile(query.address.as_ref(), true)?,
+            message_type: compile(query.message_type.as_ref(), false)?,
+            fields: query
+                .fields
+                .iter()
+                .map(|filter| FieldFilter::parse(filter))
+                .collect(),
+        })
+    }
+
+    #[must_use]
+    pub fn matches(&self, log: &Log) -> bool {
+        let is_match = |regex: &Option<Regex>, value: &str| {
+            regex.as_ref().map_or(true, |regex| regex.is_match(value))
+        };
+
+        is_match(&self.message, &log.message)
+            && is_match(&self.file_name, &log.file_name)
+            && is_match(&self.address, &log.address)
+            && is_match(&self.message_type, &log.message_type)
+    }
+
+    /// Whether the `fields` of a log match every field filter.
+    #[must_use]
+    pub fn matches_fields(&self, fields: &Fields) -> bool {
+        self.fields.iter().all(|filter| filter.matches(fields))
+    }
+}
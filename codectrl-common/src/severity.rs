@@ -0,0 +1,106 @@
+// region: imports
+
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+// endregion
+
+/// The metadata header that loggers can use to give the severity of a log, for
+/// when its message type doesn't say.
+pub const SEVERITY_HEADER: &str = "x-severity";
+
+/// How severe a log is, normalised from whatever the logger sent. Ordered from
+/// least to most severe. The server sends it along with each log as its
+/// lowercase name, see [`Display`].
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+)]
+pub enum Severity {
+    Trace,
+    Debug,
+    #[default]
+    Info,
+    Warn,
+    Error,
+    Fatal,
+}
+
+impl Severity {
+    pub const ALL: [Self; 6] = [
+        Self::Trace,
+        Self::Debug,
+        Self::Info,
+        Self::Warn,
+        Self::Error,
+        Self::Fatal,
+    ];
+
+    /// Works out the severity from a message type, i.e. a level name like
+    /// `WARNING` or `crit`, or the name of an error type like `ValueError` or
+    /// `std::io::Error`. Returns `None` for anything else, like the message
+    /// types of the CodeCTRL loggers (`String`, `i32`, ...).
+    #[must_use]
+    pub fn from_message_type(message_type: &str) -> Option<Self> {
+        let message_type = message_type.trim().to_lowercase();
+
+        // OTLP severity texts can have a number on the end, i.e. `INFO2`.
+        let level = message_type.trim_end_matches(|c: char| c.is_ascii_digit());
+
+        match level {
+            "trace" | "finest" | "finer" | "verbose" => Some(Self::Trace),
+            "debug" | "fine" | "config" => Some(Self::Debug),
+            "info" | "information" | "informational" | "notice" => Some(Self::Info),
+            "warn" | "warning" => Some(Self::Warn),
+            "error" | "err" | "severe" | "exception" => Some(Self::Error),
+            "fatal" | "critical" | "crit" | "alert" | "emergency" | "emerg" | "panic" =>
+                Some(Self::Fatal),
+            _ if level.ends_with("error") || level.ends_with("exception") =>
+                Some(Self::Error),
+            _ => None,
+        }
+    }
+
+    /// This severity and every severity above it.
+    #[must_use]
+    pub fn and_above(self) -> Vec<Self> {
+        Self::ALL
+            .into_iter()
+            .filter(|severity| *severity >= self)
+            .collect()
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Trace => write!(f, "trace"),
+            Self::Debug => write!(f, "debug"),
+            Self::Info => write!(f, "info"),
+            Self::Warn => write!(f, "warn"),
+            Self::Error => write!(f, "error"),
+            Self::Fatal => write!(f, "fatal"),
+        }
+    }
+}
+
+impl FromStr for Severity {
+    type Err = String;
+
+    fn from_str(severity: &str) -> Result<Self, Self::Err> {
+        Self::from_message_type(severity)
+            .ok_or_else(|| format!("\"{severity}\" is not a severity"))
+    }
+}
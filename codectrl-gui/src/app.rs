@@ -2,19 +2,18 @@
 
 use crate::{
     components::{about_view, details_view, main_view, main_view_empty, settings_view},
-    data::{AppState, Filter},
+    data::{
+        merge_saved_filters, AppState, ReceivedLog, SavedFilter, Severity, SeverityStyle,
+        QUERY_HELP,
+    },
     GrpcClient,
 };
 
 use authentura_egui_styling::{application_style, fonts};
-use chrono::{DateTime, Local};
 use ciborium::de as ciborium_de;
-use codectrl_protobuf_bindings::{
-    data::Log,
-    logs_service::{Connection, ServerDetails},
-};
+use codectrl_protobuf_bindings::logs_service::{Connection, ServerDetails};
 use eframe::{Frame, Storage};
-use egui::{Context, Vec2, WidgetText};
+use egui::{Color32, Context, RichText, Vec2, WidgetText};
 use flate2::bufread;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -28,7 +27,9 @@ use std::{
 // region: native-only imports
 
 #[cfg(not(target_arch = "wasm32"))]
-use crate::{wrapper::WrapperMsg, TOASTS};
+use crate::{data::ExportFormat, wrapper::WrapperMsg, TOASTS};
+#[cfg(not(target_arch = "wasm32"))]
+use chrono::Local;
 #[cfg(not(target_arch = "wasm32"))]
 use ciborium::ser as ciborium_ser;
 #[cfg(not(target_arch = "wasm32"))]
@@ -49,7 +50,7 @@ use std::{
 #[cfg(not(target_arch = "wasm32"))]
 use std::{fs::File, io::Write, path::Path};
 #[cfg(not(target_arch = "wasm32"))]
-use tokio::runtime::Handle;
+use tokio::{runtime::Handle, time::sleep};
 #[cfg(not(target_arch = "wasm32"))]
 use tonic::{Response, Status};
 
@@ -80,8 +81,10 @@ type Encoder<T> = write::DeflateEncoder<T>;
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Session {
     pub session_timestamp: String,
-    pub received: VecDeque<(Log, DateTime<Local>)>,
+    pub received: VecDeque<ReceivedLog>,
     pub message_alerts: BTreeSet<String>,
+    #[serde(default)]
+    pub saved_filters: Vec<SavedFilter>,
 }
 
 // region: wasm functions
@@ -104,7 +107,7 @@ fn register_client(mut grpc_client: GrpcClient) -> Receiver<Connection> {
     let task = executor::spawn(async move {
         spawn_local(async move {
             info!("Registering client...");
-            let res = grpc_client.register_client(()).await;
+            let res = grpc_client.logs.register_client(()).await;
             if let Ok(connection) = res {
                 let connection = connection.into_inner();
 
@@ -128,14 +131,18 @@ fn get_server_logs(
     let task = executor::spawn(async move {
         info!("Starting logs loop...");
         loop {
-            if let Ok(res) = grpc_client.get_logs(grpc_client_connection.clone()).await {
+            if let Ok(res) = grpc_client
+                .search
+                .stream_logs(grpc_client_connection.clone())
+                .await
+            {
                 let mut response = res.into_inner();
 
-                while let Ok(Some(message)) = response.message().await {
-                    received
-                        .write()
-                        .unwrap()
-                        .push_front((message.clone(), Local::now()));
+                while let Ok(Some(queried)) = response.message().await {
+                    if let Some(received_log) = ReceivedLog::from_queried(queried) {
+                        received.write().unwrap().push_front(received_log);
+                    }
+
                     context.request_repaint();
                     executor::yield_animation_frame().await;
                 }
@@ -231,22 +238,28 @@ impl App {
                 grpc_client_connection
             };
 
+        // `stream_logs` is a live subscription: the server keeps the stream open
+        // and pushes new logs as they arrive, along with their severity, so this
+        // only loops again if the stream was interrupted.
         handle.spawn(async move {
             loop {
-                if let Ok(res) =
-                    grpc_client.get_logs(grpc_client_connection.clone()).await
+                if let Ok(res) = grpc_client
+                    .search
+                    .stream_logs(grpc_client_connection.clone())
+                    .await
                 {
                     let mut response = res.into_inner();
 
-                    while let Ok(Some(message)) = response.message().await {
-                        received
-                            .write()
-                            .unwrap()
-                            .push_front((message.clone(), Local::now()));
+                    while let Ok(Some(queried)) = response.message().await {
+                        if let Some(received_log) = ReceivedLog::from_queried(queried) {
+                            received.write().unwrap().push_front(received_log);
+                        }
 
                         context_clone.request_repaint();
                     }
                 }
+
+                sleep(Duration::from_secs(1)).await;
             }
         });
 
@@ -284,6 +297,36 @@ impl App {
                 {
                     self.state.is_settings_open = true;
                 },
+                // saved filter bindings
+                Event::Key {
+                    key,
+                    pressed,
+                    modifiers,
+                } if *pressed && (modifiers.ctrl || modifiers.mac_cmd) => {
+                    let shortcut = match key {
+                        Key::Num1 => 1,
+                        Key::Num2 => 2,
+                        Key::Num3 => 3,
+                        Key::Num4 => 4,
+                        Key::Num5 => 5,
+                        Key::Num6 => 6,
+                        Key::Num7 => 7,
+                        Key::Num8 => 8,
+                        Key::Num9 => 9,
+                        _ => continue,
+                    };
+
+                    if let Some(filter) = self
+                        .state
+                        .application_settings
+                        .saved_filters
+                        .iter()
+                        .find(|filter| filter.shortcut == Some(shortcut))
+                        .cloned()
+                    {
+                        filter.apply(&mut self.state);
+                    }
+                },
                 _ => (),
             }
         }
@@ -317,6 +360,7 @@ impl App {
             session_timestamp,
             received: self.state.received.read().unwrap().clone(),
             message_alerts,
+            saved_filters: self.state.application_settings.saved_filters.clone(),
         };
 
         let mut data = vec![];
@@ -371,6 +415,35 @@ impl App {
         }
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
+    fn export_file_dialog(&mut self, format: ExportFormat) {
+        let file_path = if let Some(file_path) = FileDialog::new()
+            .set_file_name(&format!(
+                "{file_name}.{extension}",
+                file_name = Local::now().format(&self.state.filename_format),
+                extension = format.extension(),
+            ))
+            .add_filter(format.name(), &[format.extension()])
+            .save_file()
+        {
+            file_path
+        } else {
+            return;
+        };
+
+        let data = format.export(&self.state.shown_logs());
+
+        if let Err(error) = std::fs::write(&file_path, data) {
+            MessageDialog::new()
+                .set_title("Could not export logs")
+                .set_description(&format!(
+                    "Could not write to file \"{file_path}\": {error}",
+                    file_path = file_path.to_string_lossy(),
+                ))
+                .show();
+        }
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     fn load_file_dialog(&mut self) {
         let file_path = if let Some(file_path) = FileDialog::new()
@@ -419,12 +492,17 @@ impl App {
             received,
             session_timestamp,
             message_alerts,
+            application_settings,
             ..
         } = &mut app.state;
 
         *received.write().unwrap() = session.received;
         *session_timestamp = session.session_timestamp;
         *message_alerts = session.message_alerts;
+        merge_saved_filters(
+            &mut application_settings.saved_filters,
+            session.saved_filters,
+        );
 
         Ok(())
     }
@@ -537,12 +615,17 @@ impl App {
             received,
             session_timestamp,
             message_alerts,
+            application_settings,
             ..
         } = &mut app.as_ref().lock().unwrap().state;
 
         *received.write().unwrap() = session.received;
         *session_timestamp = session.session_timestamp;
         *message_alerts = session.message_alerts;
+        merge_saved_filters(
+            &mut application_settings.saved_filters,
+            session.saved_filters,
+        );
 
         Ok(())
     }
@@ -605,9 +688,9 @@ impl eframe::App for App {
             let mut grpc_client = self.grpc_client.clone().unwrap();
 
             let promise = self.promise.get_or_insert_with(|| {
-                Promise::spawn_async(
-                    async move { grpc_client.get_server_details(()).await },
-                )
+                Promise::spawn_async(async move {
+                    grpc_client.logs.get_server_details(()).await
+                })
             });
 
             if let Some(Ok(details)) = promise.ready() {
@@ -625,7 +708,7 @@ impl eframe::App for App {
 
                 self.state.refresh_server_details = true;
                 self.promise = Some(Promise::spawn_async(async move {
-                    grpc_client.get_server_details(()).await
+                    grpc_client.logs.get_server_details(()).await
                 }));
                 self.state.time_details_last_checked = Instant::now();
             }
@@ -661,6 +744,16 @@ impl eframe::App for App {
                             self.load_file_dialog();
                         }
 
+                        #[cfg(not(target_arch = "wasm32"))]
+                        ui.menu_button("Export shown logs", |ui| {
+                            for format in ExportFormat::ALL {
+                                if ui.button(format.name()).clicked() {
+                                    ui.close_menu();
+                                    self.export_file_dialog(format);
+                                }
+                            }
+                        });
+
                         ui.separator();
 
                         if shortcut_button(ui, "Settings", "Ctrl+P").clicked() {
@@ -708,54 +801,140 @@ impl eframe::App for App {
                     ui.separator();
 
                     ui.label("Filter: ");
-                    ui.text_edit_singleline(&mut self.state.search_filter);
+
+                    let is_query_invalid = self
+                        .state
+                        .parsed_query
+                        .update(
+                            &self.state.query,
+                            self.state.is_case_sensitive,
+                            self.state.is_using_regex,
+                        )
+                        .is_err();
+
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.state.query)
+                            .hint_text("file:main.rs line:>40 msg~/timeout/")
+                            .text_color_opt(
+                                is_query_invalid.then_some(Color32::LIGHT_RED),
+                            ),
+                    )
+                    .on_hover_text(QUERY_HELP);
 
                     // u1f5d9 = 🗙
                     if ui.button("\u{1f5d9}").clicked() {
-                        self.state.search_filter = "".into();
+                        self.state.query = "".into();
+                    }
+
+                    if let Err(error) = self.state.parsed_query.update(
+                        &self.state.query,
+                        self.state.is_case_sensitive,
+                        self.state.is_using_regex,
+                    ) {
+                        // u26a0 = ⚠
+                        ui.label(
+                            RichText::new(format!("\u{26a0} {error}"))
+                                .color(Color32::LIGHT_RED),
+                        );
                     }
 
-                    ui.label("Filter by:");
-                    egui::ComboBox::from_label("")
-                        .selected_text(format!("{}", self.state.filter_by))
+                    ui.label("Severity:");
+                    egui::ComboBox::from_id_source("severity_filter")
+                        .selected_text(match self.state.severity_filter {
+                            Some(severity) => format!("{} and above", severity.label()),
+                            None => "All".into(),
+                        })
                         .show_ui(ui, |ui| {
                             ui.selectable_value(
-                                &mut self.state.filter_by,
-                                Filter::Message,
-                                format!("{}", Filter::Message),
+                                &mut self.state.severity_filter,
+                                None,
+                                "All",
                             );
 
-                            ui.selectable_value(
-                                &mut self.state.filter_by,
-                                Filter::Time,
-                                format!("{}", Filter::Time),
-                            );
+                            for severity in Severity::ALL {
+                                ui.selectable_value(
+                                    &mut self.state.severity_filter,
+                                    Some(severity),
+                                    RichText::new(format!(
+                                        "{} and above",
+                                        severity.label()
+                                    ))
+                                    .color(severity.colour()),
+                                );
+                            }
+                        });
 
-                            ui.selectable_value(
-                                &mut self.state.filter_by,
-                                Filter::FileName,
-                                format!("{}", Filter::FileName),
-                            );
+                    ui.checkbox(&mut self.state.is_case_sensitive, "Case sensitive");
+                    ui.checkbox(&mut self.state.is_using_regex, "Regex");
 
-                            ui.selectable_value(
-                                &mut self.state.filter_by,
-                                Filter::Address,
-                                format!("{}", Filter::Address),
-                            );
+                    ui.menu_button("Saved filters", |ui| {
+                        let mut applied = None;
+
+                        for filter in &self.state.application_settings.saved_filters {
+                            let text = match filter.shortcut {
+                                Some(shortcut) =>
+                                    format!("{} (Ctrl+{shortcut})", filter.name),
+                                None => filter.name.clone(),
+                            };
+
+                            if ui
+                                .selectable_label(filter.is_applied(&self.state), text)
+                                .on_hover_text(&filter.query)
+                                .clicked()
+                            {
+                                applied = Some(filter.clone());
+                            }
+                        }
 
-                            ui.selectable_value(
-                                &mut self.state.filter_by,
-                                Filter::LineNumber,
-                                format!("{}", Filter::LineNumber),
+                        if let Some(filter) = applied {
+                            filter.apply(&mut self.state);
+                            ui.close_menu();
+                        }
+
+                        if !self.state.application_settings.saved_filters.is_empty() {
+                            ui.separator();
+                        }
+
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                egui::TextEdit::singleline(
+                                    &mut self.state.saved_filter_name,
+                                )
+                                .hint_text("Name"),
                             );
-                        });
 
-                    ui.checkbox(&mut self.state.is_case_sensitive, "Case sensitive");
-                    ui.checkbox(&mut self.state.is_using_regex, "Regex");
+                            let name = self.state.saved_filter_name.trim();
+                            let is_name_taken = self
+                                .state
+                                .application_settings
+                                .saved_filters
+                                .iter()
+                                .any(|filter| filter.name == name);
+
+                            if ui
+                                .add_enabled(
+                                    !name.is_empty() && !is_name_taken,
+                                    egui::Button::new("Save current filter"),
+                                )
+                                .clicked()
+                            {
+                                let filter = SavedFilter::from_state(name, &self.state);
+
+                                self.state
+                                    .application_settings
+                                    .saved_filters
+                                    .push(filter);
+                                self.state.saved_filter_name.clear();
+                                ui.close_menu();
+                            }
+                        });
+                    });
                     ui.checkbox(
                         &mut self.state.do_scroll_to_selected_log,
                         "Scroll to selected log",
                     );
+                    ui.checkbox(&mut self.state.is_grouping_logs, "Group by call site");
+                    ui.checkbox(&mut self.state.is_timeline_open, "Timeline");
 
                     if ui
                         .button(
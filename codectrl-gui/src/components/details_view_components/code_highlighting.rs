@@ -5,13 +5,47 @@ use egui::{text::LayoutJob, Color32, Context, FontSelection, TextFormat, TextSty
 use std::path::Path;
 use syntect::{
     easy::HighlightLines,
-    highlighting::{Style, ThemeSet},
-    parsing::SyntaxSet,
+    highlighting::{Style, Theme, ThemeSet},
+    parsing::{SyntaxReference, SyntaxSet},
     util::LinesWithEndings,
 };
 
 // endregion
 
+fn highlighted_lines<'a>(
+    code: &'a str,
+    syntax: &SyntaxReference,
+    syntax_set: &SyntaxSet,
+    theme: &Theme,
+) -> Vec<Vec<(Color32, &'a str)>> {
+    let mut highlight = HighlightLines::new(syntax, theme);
+
+    LinesWithEndings::from(code)
+        .map(|line| {
+            let ranges: Vec<(Style, &str)> =
+                if let Ok(ranges) = highlight.highlight_line(line, syntax_set) {
+                    ranges
+                } else {
+                    vec![(Style::default(), line)]
+                };
+
+            ranges
+                .into_iter()
+                .map(|(style, code)| {
+                    (
+                        Color32::from_rgb(
+                            style.foreground.r,
+                            style.foreground.g,
+                            style.foreground.b,
+                        ),
+                        code,
+                    )
+                })
+                .collect()
+        })
+        .collect()
+}
+
 pub fn code_highlighter(code: &str, log: &Log, ctx: &Context) -> LayoutJob {
     let syntax_set = SyntaxSet::load_defaults_newlines();
     let theme_set = ThemeSet::load_defaults();
@@ -27,33 +61,62 @@ pub fn code_highlighter(code: &str, log: &Log, ctx: &Context) -> LayoutJob {
         syntax_set.find_syntax_plain_text()
     };
 
-    let mut highlight =
-        HighlightLines::new(syntax, &theme_set.themes["Solarized (dark)"]);
     let mut job = LayoutJob::default();
 
-    for line in LinesWithEndings::from(code) {
-        let ranges: Vec<(Style, &str)> =
-            if let Ok(ranges) = highlight.highlight_line(line, &syntax_set) {
-                ranges
+    for ranges in highlighted_lines(
+        code,
+        syntax,
+        &syntax_set,
+        &theme_set.themes["Solarized (dark)"],
+    ) {
+        for (colour, code) in ranges {
+            job.append(code, 0.0, TextFormat::simple(font_id.clone(), colour));
+        }
+    }
+
+    job
+}
+
+/// Highlights `code` with the syntax called `syntax_name`, as a separate job
+/// for each line so that lines can be laid out on their own. The indentation
+/// and line endings are left out.
+pub fn line_highlighter(code: &str, syntax_name: &str, ctx: &Context) -> Vec<LayoutJob> {
+    let syntax_set = SyntaxSet::load_defaults_newlines();
+    let theme_set = ThemeSet::load_defaults();
+    let font_id = FontSelection::Style(TextStyle::Monospace).resolve(&ctx.style());
+
+    let syntax = syntax_set
+        .find_syntax_by_name(syntax_name)
+        .unwrap_or_else(|| syntax_set.find_syntax_plain_text());
+
+    highlighted_lines(
+        code,
+        syntax,
+        &syntax_set,
+        &theme_set.themes["Solarized (dark)"],
+    )
+    .into_iter()
+    .map(|ranges| {
+        let mut job = LayoutJob::default();
+        let mut is_indentation = true;
+
+        for (colour, code) in ranges {
+            let code = code.trim_end_matches(['\r', '\n']);
+            let code = if is_indentation {
+                code.trim_start()
             } else {
-                vec![(Style::default(), line)]
+                code
             };
 
-        for h in ranges {
-            let (style, code) = h;
-            job.append(
-                code,
-                0.0,
-                TextFormat::simple(
-                    font_id.clone(),
-                    Color32::from_rgb(
-                        style.foreground.r,
-                        style.foreground.g,
-                        style.foreground.b,
-                    ),
-                ),
-            );
+            if code.is_empty() {
+                continue;
+            }
+
+            is_indentation = false;
+            job.append(code, 0.0, TextFormat::simple(font_id.clone(), colour));
         }
-    }
-    job
+
+        job
+    })
+    .collect()
 }
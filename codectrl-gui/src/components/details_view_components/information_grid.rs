@@ -2,7 +2,10 @@
 
 use crate::{
     components::{details_view_components::code_highlighter, message_preview_view},
-    data::{AppState, ISO_8601_TIME_FORMAT, LOCALE_TIME_FORMAT},
+    data::{
+        field_value_to_string, AppState, Fields, ReceivedLog, ISO_8601_TIME_FORMAT,
+        LOCALE_TIME_FORMAT,
+    },
     widgets::CopyableLabel,
 };
 
@@ -14,6 +17,7 @@ use egui::{
     WidgetText,
 };
 use egui_extras::{Column, TableBuilder};
+use serde_json::Value;
 use xxhash_rust::xxh3::xxh3_128 as xxhash;
 
 // endregion
@@ -66,8 +70,11 @@ pub fn draw_information_grid(app_state: &mut AppState, ctx: &Context, ui: &mut U
         })
         .body(|mut body| {
             body.row(available_height, |mut row| {
-                if let Some((log, time)) = app_state.clicked_item.clone() {
-                    row.col(|ui| detail_scroll(app_state, &log, &time, ctx, ui));
+                if let Some(ReceivedLog {
+                    log, time, fields, ..
+                }) = app_state.clicked_item.clone()
+                {
+                    row.col(|ui| detail_scroll(app_state, &log, &time, &fields, ctx, ui));
                     row.col(|ui| {
                         code_scroll(
                             (
@@ -91,6 +98,7 @@ fn detail_scroll(
     app_state: &mut AppState,
     log: &Log,
     time: &DateTime<Local>,
+    fields: &Fields,
     ctx: &Context,
     ui: &mut Ui,
 ) {
@@ -122,7 +130,11 @@ fn detail_scroll(
                         }
 
                         message_preview_view(
-                            &mut app_state.is_message_preview_open,
+                            (
+                                &mut app_state.is_message_preview_open,
+                                &mut app_state.is_message_preview_raw,
+                                &mut app_state.message_preview,
+                            ),
                             ctx,
                             &log.message,
                             &log.message_type,
@@ -185,6 +197,12 @@ fn detail_scroll(
                     },
                 );
 
+                if !fields.is_empty() {
+                    ui.collapsing(format!("Fields ({} field(s))", fields.len()), |ui| {
+                        draw_fields(ui, fields);
+                    });
+                }
+
                 if !log.warnings.is_empty() {
                     ui.collapsing(format!("{} Warning(s)", log.warnings.len()), |ui| {
                         for (index, warning) in log.warnings.iter().enumerate() {
@@ -199,6 +217,35 @@ fn detail_scroll(
         });
 }
 
+fn draw_field(ui: &mut Ui, key: &str, value: &Value) {
+    match value {
+        Value::Object(object) if !object.is_empty() => {
+            ui.collapsing(format!("{key} ({} field(s))", object.len()), |ui| {
+                draw_fields(ui, object);
+            });
+        },
+        Value::Array(array) if !array.is_empty() => {
+            ui.collapsing(format!("{key} ({} item(s))", array.len()), |ui| {
+                for (index, value) in array.iter().enumerate() {
+                    draw_field(ui, &index.to_string(), value);
+                }
+            });
+        },
+        value => {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(format!("{key}:")).strong());
+                ui.add(CopyableLabel::new_monospace(field_value_to_string(value)));
+            });
+        },
+    }
+}
+
+fn draw_fields(ui: &mut Ui, fields: &Fields) {
+    for (key, value) in fields {
+        draw_field(ui, key, value);
+    }
+}
+
 fn code_scroll(
     (
         is_copying_line_numbers,
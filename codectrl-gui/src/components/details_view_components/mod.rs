@@ -1,5 +1,5 @@
 mod code_highlighting;
 mod information_grid;
 
-pub use code_highlighting::code_highlighter;
+pub use code_highlighting::{code_highlighter, line_highlighter};
 pub use information_grid::draw_information_grid;
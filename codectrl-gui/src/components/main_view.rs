@@ -1,95 +1,26 @@
 // region: imports
 
-use super::{main_view_components::draw_log_item, regex_filter};
-use crate::data::{AppState, Filter, ISO_8601_TIME_FORMAT};
+use super::main_view_components::{draw_log_group, draw_log_item, draw_timeline};
+use crate::data::{AppState, LogFilter, LogGroups, ReceivedLog};
 use authentura_egui_styling::{CODECTRL_GREEN, DARK_HEADER_FOREGROUND_COLOUR};
-use chrono::{DateTime, Local};
-use codectrl_protobuf_bindings::data::Log;
+use chrono::Local;
 use egui::{Context, Direction, Layout, RichText, TextStyle, Ui};
 use egui_extras::{Column, TableBuilder};
-use regex::RegexBuilder;
 
 // endregion
 
-fn app_state_filter(
-    is_case_sensitive: bool,
-    is_using_regex: bool,
-    search_filter: &str,
-    filter_by: &Filter,
-    log: &Log,
-    time: &DateTime<Local>,
-) -> bool {
-    let string_filter = |search_filter: &str, search_string: &str| -> bool {
-        if is_case_sensitive {
-            log.message.contains(search_filter)
-        } else if is_using_regex {
-            regex_filter(search_filter, search_string, is_case_sensitive)
-        } else {
-            log.message
-                .to_lowercase()
-                .contains(&search_filter.to_lowercase())
+pub fn main_view(app_state: &mut AppState, ctx: &Context) {
+    egui::CentralPanel::default().show(ctx, |ui| {
+        if !app_state.application_settings.saved_filters.is_empty() {
+            saved_filter_tabs(app_state, ui);
+            ui.separator();
         }
-    };
-
-    match filter_by {
-        Filter::Message => string_filter(search_filter, &log.message),
-        Filter::Time => time
-            .format(ISO_8601_TIME_FORMAT)
-            .to_string()
-            .contains(search_filter),
-        Filter::FileName => string_filter(search_filter, &log.file_name),
-        Filter::Address => {
-            let regex = if let Ok(regex) =  RegexBuilder::new(
-                r#"(\b25[0-5]|\b2[0-4][0-9]|\b[01]?[0-9][0-9]?)(\.(25[0-5]|2[0-4][0-9]|[01]?[0-9][0-9]?)){3}"#,
-            ).build() {
-                regex
-            } else {
-                return false;
-            };
-
-            let address = log.address.split('.');
-            let search_address = search_filter.split('.');
-            let mut is_matching = true;
-            let mut contains_glob = false;
-
-            for (address_split, search_split) in address.zip(search_address) {
-                if search_split == "*" {
-                    contains_glob = true;
-                    continue;
-                }
-
-                if !is_matching {
-                    break;
-                }
-
-                match (address_split.parse::<u8>(), search_split.parse::<u8>()) {
-                    (Ok(ap), Ok(sp)) => is_matching = ap == sp,
-                    (Err(_), _) | (_, Err(_)) => return false,
-                }
-            }
-
-            if !contains_glob
-                && (!regex.is_match(&log.address) || !regex.is_match(search_filter))
-            {
-                return false;
-            }
-
-            is_matching
-        },
-        Filter::LineNumber => {
-            let number = search_filter.parse::<u32>().unwrap_or(0);
-
-            if number == 0 {
-                return true;
-            }
 
-            log.line_number == number
-        },
-    }
-}
+        if app_state.is_timeline_open {
+            draw_timeline(app_state, ui);
+            ui.separator();
+        }
 
-pub fn main_view(app_state: &mut AppState, ctx: &Context) {
-    egui::CentralPanel::default().show(ctx, |ui| {
         let max_rect = ui.max_rect();
 
         ui.vertical_centered(|ui| {
@@ -117,6 +48,7 @@ pub fn main_view(app_state: &mut AppState, ctx: &Context) {
                             Direction::LeftToRight,
                         ))
                         .column(Column::exact(20.0))
+                        .column(Column::exact(80.0))
                         .column(Column::remainder())
                         .column(Column::exact(150.0))
                         .column(Column::remainder())
@@ -124,6 +56,7 @@ pub fn main_view(app_state: &mut AppState, ctx: &Context) {
                         .column(Column::auto())
                         .header(30.0, |mut header| {
                             header.col(|ui| heading(ui, ""));
+                            header.col(|ui| heading(ui, "Severity"));
                             header.col(|ui| heading(ui, "Message"));
                             header.col(|ui| heading(ui, "Host"));
                             header.col(|ui| heading(ui, "File name"));
@@ -134,27 +67,121 @@ pub fn main_view(app_state: &mut AppState, ctx: &Context) {
                     table.ui_mut().set_max_width(max_rect.width());
 
                     table.body(|mut body| {
-                        let received_vec = app_state.received.read().unwrap();
-                        let mut received_vec: Vec<_> = received_vec.iter().collect();
+                        let received = app_state.received.read().unwrap();
+
+                        // Syntax errors are shown in the filter bar, every log is
+                        // shown until they're fixed.
+                        let filter = LogFilter {
+                            severity_filter: app_state.severity_filter,
+                            time_range: app_state.time_range,
+                            query: app_state
+                                .parsed_query
+                                .update(
+                                    &app_state.query,
+                                    app_state.is_case_sensitive,
+                                    app_state.is_using_regex,
+                                )
+                                .as_ref()
+                                .ok(),
+                            now: Local::now(),
+                        };
+
+                        let is_shown = |received: &&ReceivedLog| filter.matches(received);
+
+                        if app_state.is_grouping_logs {
+                            app_state.log_groups.update(&received);
+
+                            let LogGroups {
+                                groups, expanded, ..
+                            } = &mut app_state.log_groups;
+
+                            // The entries of each group that match the filters,
+                            // oldest first.
+                            let mut groups: Vec<_> = groups
+                                .iter()
+                                .map(|group| {
+                                    let entries: Vec<_> = group
+                                        .entries
+                                        .iter()
+                                        .filter_map(|(sequence, _)| {
+                                            LogGroups::entry(&received, *sequence)
+                                        })
+                                        .filter(is_shown)
+                                        .collect();
+
+                                    (&group.key, entries)
+                                })
+                                .filter(|(_, entries)| !entries.is_empty())
+                                .collect();
+
+                            groups.sort_by(|(_, a_entries), (_, b_entries)| {
+                                let a_time = a_entries.last().map(|last| last.time);
+                                let b_time = b_entries.last().map(|last| last.time);
+
+                                if app_state.is_newest_first {
+                                    b_time.cmp(&a_time)
+                                } else {
+                                    a_time.cmp(&b_time)
+                                }
+                            });
+
+                            for (key, mut entries) in groups {
+                                let mut is_expanded = expanded.contains(key);
+                                let times: Vec<_> = entries
+                                    .iter()
+                                    .map(|received| received.time)
+                                    .collect();
+
+                                body.row(60.0, |mut row| {
+                                    draw_log_group(
+                                        &mut is_expanded,
+                                        key,
+                                        entries[entries.len() - 1],
+                                        &times,
+                                        &mut row,
+                                    );
+                                });
+
+                                if is_expanded {
+                                    expanded.insert(key.clone());
+                                } else {
+                                    expanded.remove(key);
+                                    continue;
+                                }
+
+                                if app_state.is_newest_first {
+                                    entries.reverse();
+                                }
+
+                                for received in entries {
+                                    body.row(60.0, |mut row| {
+                                        draw_log_item(
+                                            &app_state.message_alerts,
+                                            &mut app_state.clicked_item,
+                                            app_state.do_scroll_to_selected_log,
+                                            received,
+                                            &mut row,
+                                        );
+                                    });
+                                }
+                            }
 
-                        received_vec.sort_by(|(_, a_time), (_, b_time)| {
+                            return;
+                        }
+
+                        let mut received_vec: Vec<_> = received.iter().collect();
+
+                        received_vec.sort_by(|a, b| {
                             if app_state.is_newest_first {
-                                b_time.partial_cmp(a_time).unwrap()
+                                b.time.partial_cmp(&a.time).unwrap()
                             } else {
-                                a_time.partial_cmp(b_time).unwrap()
+                                a.time.partial_cmp(&b.time).unwrap()
                             }
                         });
 
-                        for received in received_vec.iter().filter(|(log, time)| {
-                            app_state_filter(
-                                app_state.is_case_sensitive,
-                                app_state.is_using_regex,
-                                &app_state.search_filter,
-                                &app_state.filter_by,
-                                log,
-                                time,
-                            )
-                        }) {
+                        for received in
+                            received_vec.iter().filter(|received| is_shown(received))
+                        {
                             body.row(60.0, |mut row| {
                                 draw_log_item(
                                     &app_state.message_alerts,
@@ -171,6 +198,34 @@ pub fn main_view(app_state: &mut AppState, ctx: &Context) {
     });
 }
 
+fn saved_filter_tabs(app_state: &mut AppState, ui: &mut Ui) {
+    ui.horizontal_wrapped(|ui| {
+        let is_unfiltered =
+            app_state.query.is_empty() && app_state.severity_filter.is_none();
+
+        if ui.selectable_label(is_unfiltered, "All").clicked() {
+            app_state.query.clear();
+            app_state.severity_filter = None;
+        }
+
+        let mut applied = None;
+
+        for filter in &app_state.application_settings.saved_filters {
+            if ui
+                .selectable_label(filter.is_applied(app_state), &filter.name)
+                .on_hover_text(&filter.query)
+                .clicked()
+            {
+                applied = Some(filter.clone());
+            }
+        }
+
+        if let Some(filter) = applied {
+            filter.apply(app_state);
+        }
+    });
+}
+
 pub fn main_view_empty(ctx: &Context, socket_address: &str) {
     egui::CentralPanel::default().show(ctx, |ui| {
         ui.vertical_centered(|ui| {
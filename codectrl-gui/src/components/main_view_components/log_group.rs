@@ -0,0 +1,141 @@
+use super::truncate_message;
+use crate::data::{
+    sparkline_buckets, GroupKey, ReceivedLog, SeverityStyle, ISO_8601_TIME_FORMAT,
+};
+use authentura_egui_styling::CODECTRL_GREEN;
+use chrono::{DateTime, Local};
+use egui::{pos2, Label, Rect, RichText, Sense, Ui, Vec2};
+use egui_extras::TableRow;
+
+fn draw_hover(ui: &mut Ui, is_expanded: bool) {
+    if is_expanded {
+        ui.label("Click to hide the logs in this group");
+    } else {
+        ui.label("Click to show the logs in this group");
+    }
+}
+
+/// Draws how many logs were received over time, from the first log in the
+/// group to the last.
+#[allow(clippy::cast_precision_loss)]
+fn draw_sparkline(ui: &mut Ui, times: &[DateTime<Local>]) {
+    let (rect, _) = ui.allocate_exact_size(Vec2::new(120.0, 16.0), Sense::hover());
+
+    if times.is_empty() {
+        return;
+    }
+
+    let buckets = sparkline_buckets(times);
+    let most = buckets.iter().copied().max().unwrap_or(1).max(1);
+    let width = rect.width() / buckets.len() as f32;
+
+    for (index, count) in buckets.into_iter().enumerate() {
+        if count == 0 {
+            continue;
+        }
+
+        let height = (rect.height() * count as f32 / most as f32).max(1.0);
+        let left = rect.left() + index as f32 * width;
+
+        ui.painter().rect_filled(
+            Rect::from_min_max(
+                pos2(left, rect.bottom() - height),
+                pos2(left + width - 1.0, rect.bottom()),
+            ),
+            0.0,
+            CODECTRL_GREEN,
+        );
+    }
+}
+
+/// Draws a group of logs sent from the same place with the same message, as a
+/// single row. `times` are the times that each log in the group was received,
+/// oldest first, and `latest` is the newest log.
+pub fn draw_log_group(
+    is_expanded: &mut bool,
+    key: &GroupKey,
+    latest: &ReceivedLog,
+    times: &[DateTime<Local>],
+    row: &mut TableRow,
+) {
+    let ReceivedLog {
+        log: latest,
+        severity,
+        ..
+    } = latest;
+
+    let mut template = key.template.clone();
+
+    if template.contains('\n') {
+        template = "Message contains newlines...".to_string();
+    } else {
+        truncate_message(&mut template);
+    }
+
+    let labels = vec![
+        Label::new(
+            RichText::new(severity.label())
+                .monospace()
+                .color(severity.colour()),
+        ),
+        Label::new(
+            RichText::new(format!("{template} \u{d7}{}", times.len())).monospace(), /* ud7 = × */
+        ),
+        Label::new(RichText::new(&latest.address).monospace()),
+        Label::new(RichText::new(&key.file_name).monospace()),
+        Label::new(RichText::new(format!("{}", key.line_number)).monospace()),
+    ];
+
+    let mut responses = vec![];
+
+    responses.push(
+        row.col(|ui| {
+            ui.horizontal_centered(|ui| {
+                ui.add_space(4.0);
+                // u25bc = ▼, u25b6 = ▶
+                ui.label(if *is_expanded { "\u{25bc}" } else { "\u{25b6}" });
+            });
+        })
+        .1
+        .interact(Sense::click()),
+    );
+
+    for label in labels {
+        responses.push(
+            row.col(|ui| {
+                ui.add(label.wrap(true));
+            })
+            .1
+            .interact(Sense::click()),
+        );
+    }
+
+    responses.push(
+        row.col(|ui| {
+            ui.vertical(|ui| {
+                if let (Some(first), Some(last)) = (times.first(), times.last()) {
+                    ui.label(format!("First: {}", first.format(ISO_8601_TIME_FORMAT)));
+                    ui.label(format!("Last: {}", last.format(ISO_8601_TIME_FORMAT)));
+                }
+
+                draw_sparkline(ui, times);
+            });
+        })
+        .1
+        .interact(Sense::click()),
+    );
+
+    let response = responses
+        .iter()
+        .fold(responses[0].clone(), |mut overall, current| {
+            overall |= current.clone();
+            overall
+        });
+
+    if response
+        .on_hover_ui_at_pointer(|ui| draw_hover(ui, *is_expanded))
+        .clicked()
+    {
+        *is_expanded = !*is_expanded;
+    }
+}
@@ -1,12 +1,10 @@
-use crate::data::ISO_8601_TIME_FORMAT;
-use chrono::{DateTime, Local};
+use super::truncate_message;
+use crate::data::{ReceivedLog, SeverityStyle, ISO_8601_TIME_FORMAT};
 use codectrl_protobuf_bindings::data::Log;
 use egui::{Align, Color32, Label, RichText, Sense, Ui};
 use egui_extras::TableRow;
 use std::collections::BTreeSet;
 
-type Received = (Log, DateTime<Local>);
-
 fn draw_hover(ui: &mut Ui) { ui.label("Click to view log"); }
 
 fn draw_warnings(ui: &mut Ui, log: &Log) {
@@ -35,9 +33,13 @@ fn draw_alerts(ui: &mut Ui, exact: &str, contains: &[&String]) {
 
 pub fn draw_log_item(
     message_alerts: &BTreeSet<String>,
-    clicked_item: &mut Option<Received>,
+    clicked_item: &mut Option<ReceivedLog>,
     do_scroll_to_selected_log: bool,
-    received @ (log, time): &Received,
+    received @ ReceivedLog {
+        log,
+        time,
+        severity,
+    }: &ReceivedLog,
     row: &mut TableRow,
 ) {
     let mut message = log.message.replace('\"', "");
@@ -58,12 +60,16 @@ pub fn draw_log_item(
     if log.message.contains('\n') {
         message = "Message contains newlines...".to_string();
         contains_newlines = true;
-    } else if log.message.len() > 100 {
-        message.truncate(97);
-        message.push_str("...");
+    } else {
+        truncate_message(&mut message);
     }
 
     let labels = vec![
+        Label::new(
+            RichText::new(severity.label())
+                .monospace()
+                .color(severity.colour()),
+        ),
         if contains_newlines {
             Label::new(message)
         } else {
@@ -1,3 +1,23 @@
+mod log_group;
 mod log_item;
+mod timeline;
 
+pub use log_group::draw_log_group;
 pub use log_item::draw_log_item;
+pub use timeline::draw_timeline;
+
+/// The most characters of a message that are shown in the log table.
+const MAX_MESSAGE_LENGTH: usize = 100;
+
+/// Cuts `message` down to [`MAX_MESSAGE_LENGTH`] characters, ending in `...`.
+/// The message is cut on a character boundary, as messages aren't only ASCII.
+fn truncate_message(message: &mut String) {
+    if message.chars().nth(MAX_MESSAGE_LENGTH).is_none() {
+        return;
+    }
+
+    if let Some((end, _)) = message.char_indices().nth(MAX_MESSAGE_LENGTH - 3) {
+        message.truncate(end);
+        message.push_str("...");
+    }
+}
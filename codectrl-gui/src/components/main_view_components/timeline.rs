@@ -0,0 +1,234 @@
+use crate::data::{AppState, TimeRange, ISO_8601_TIME_FORMAT};
+use authentura_egui_styling::CODECTRL_GREEN;
+use chrono::{DateTime, Duration, Local};
+use egui::{
+    pos2, Align, Button, Color32, Key, Layout, Rect, RichText, Sense, Stroke, Ui, Vec2,
+};
+
+const TIMELINE_BUCKETS: usize = 80;
+const TIMELINE_HEIGHT: f32 = 60.0;
+const QUICK_RANGES: [(&str, i64); 4] = [
+    ("1 minute", 60),
+    ("5 minutes", 60 * 5),
+    ("15 minutes", 60 * 15),
+    ("hour", 60 * 60),
+];
+
+fn set_time_range(app_state: &mut AppState, time_range: Option<TimeRange>) {
+    app_state.time_range_input = time_range
+        .map(|time_range| time_range.to_string())
+        .unwrap_or_default();
+    app_state.time_range = time_range;
+    app_state.time_range_error = None;
+}
+
+fn draw_range_inputs(app_state: &mut AppState, ui: &mut Ui) {
+    ui.horizontal_wrapped(|ui| {
+        ui.label("Time range:");
+
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut app_state.time_range_input)
+                .hint_text("last 5 minutes or 10:00..10:30")
+                .text_color_opt(
+                    app_state
+                        .time_range_error
+                        .is_some()
+                        .then_some(Color32::LIGHT_RED),
+                ),
+        );
+
+        if (response.lost_focus() && ui.input().key_pressed(Key::Enter))
+            || ui.button("Apply").clicked()
+        {
+            if app_state.time_range_input.trim().is_empty() {
+                set_time_range(app_state, None);
+            } else {
+                match TimeRange::parse(&app_state.time_range_input) {
+                    Ok(time_range) => set_time_range(app_state, Some(time_range)),
+                    Err(error) => app_state.time_range_error = Some(error),
+                }
+            }
+        }
+
+        for (name, seconds) in QUICK_RANGES {
+            if ui.button(format!("Last {name}")).clicked() {
+                set_time_range(
+                    app_state,
+                    Some(TimeRange::Last(Duration::seconds(seconds))),
+                );
+            }
+        }
+
+        // u1f5d9 = 🗙
+        if ui
+            .add_enabled(
+                app_state.time_range.is_some(),
+                Button::new("\u{1f5d9} Clear range"),
+            )
+            .clicked()
+        {
+            set_time_range(app_state, None);
+        }
+
+        if let Some(error) = &app_state.time_range_error {
+            // u26a0 = ⚠
+            ui.label(
+                RichText::new(format!("\u{26a0} {error}")).color(Color32::LIGHT_RED),
+            );
+        }
+    });
+}
+
+/// Draws a histogram of how many logs were received over time, from the first
+/// log to the last. Dragging across it filters the log table to the logs
+/// received in the selected range, and double-clicking clears the range.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+pub fn draw_timeline(app_state: &mut AppState, ui: &mut Ui) {
+    draw_range_inputs(app_state, ui);
+
+    // Relative ranges move along as time passes, so logs need to be dropped
+    // from the table even when no new ones are received.
+    if app_state.time_range.map_or(false, TimeRange::is_relative) {
+        ui.ctx()
+            .request_repaint_after(std::time::Duration::from_secs(1));
+    }
+
+    let times: Vec<DateTime<Local>> = app_state
+        .received
+        .read()
+        .unwrap()
+        .iter()
+        .map(|received| received.time)
+        .collect();
+
+    let (first, last) = match (times.iter().min(), times.iter().max()) {
+        (Some(first), Some(last)) => (*first, *last),
+        _ => return,
+    };
+    let span = (last - first).num_milliseconds().max(1000);
+
+    let (rect, response) = ui.allocate_exact_size(
+        Vec2::new(ui.available_width(), TIMELINE_HEIGHT),
+        Sense::click_and_drag(),
+    );
+
+    let time_at = |x: f32| {
+        let fraction = ((x - rect.left()) / rect.width()).clamp(0.0, 1.0);
+
+        first + Duration::milliseconds((f64::from(fraction) * span as f64) as i64)
+    };
+    let x_of = |time: DateTime<Local>| {
+        let fraction = (time - first).num_milliseconds() as f32 / span as f32;
+
+        rect.left() + rect.width() * fraction.clamp(0.0, 1.0)
+    };
+    let bucket_of = |time: &DateTime<Local>| {
+        let bucket = (*time - first).num_milliseconds() * TIMELINE_BUCKETS as i64 / span;
+
+        (bucket.max(0) as usize).min(TIMELINE_BUCKETS - 1)
+    };
+
+    let mut buckets = [0_usize; TIMELINE_BUCKETS];
+
+    for time in &times {
+        buckets[bucket_of(time)] += 1;
+    }
+
+    if let Some(position) = response.interact_pointer_pos() {
+        if response.drag_started() {
+            app_state.timeline_drag = Some((time_at(position.x), time_at(position.x)));
+        } else if let Some((start, _)) = app_state.timeline_drag {
+            app_state.timeline_drag = Some((start, time_at(position.x)));
+        }
+    }
+
+    if response.drag_released() {
+        if let Some((start, end)) = app_state.timeline_drag.take() {
+            if start != end {
+                set_time_range(
+                    app_state,
+                    Some(TimeRange::Between {
+                        from: Some(start.min(end)),
+                        to: Some(start.max(end)),
+                    }),
+                );
+            }
+        }
+    }
+
+    if response.double_clicked() {
+        set_time_range(app_state, None);
+    }
+
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+
+    let most = buckets.iter().copied().max().unwrap_or(1).max(1);
+    let width = rect.width() / TIMELINE_BUCKETS as f32;
+
+    for (index, count) in buckets.into_iter().enumerate() {
+        if count == 0 {
+            continue;
+        }
+
+        let height = (rect.height() * count as f32 / most as f32).max(1.0);
+        let left = rect.left() + index as f32 * width;
+
+        painter.rect_filled(
+            Rect::from_min_max(
+                pos2(left, rect.bottom() - height),
+                pos2(left + width - 1.0, rect.bottom()),
+            ),
+            0.0,
+            CODECTRL_GREEN,
+        );
+    }
+
+    let selection = match (app_state.timeline_drag, app_state.time_range) {
+        (Some((start, end)), _) => Some((Some(start.min(end)), Some(start.max(end)))),
+        (None, Some(time_range)) => match time_range.at(Local::now()) {
+            TimeRange::Between { from, to } => Some((from, to)),
+            TimeRange::Last(_) => None,
+        },
+        (None, None) => None,
+    };
+
+    if let Some((from, to)) = selection {
+        let selected = Rect::from_x_y_ranges(
+            from.map_or(rect.left(), x_of)..=to.map_or(rect.right(), x_of),
+            rect.y_range(),
+        );
+
+        painter.rect(
+            selected,
+            0.0,
+            Color32::from_white_alpha(24),
+            Stroke::new(1.0, ui.visuals().strong_text_color()),
+        );
+    }
+
+    if let Some(position) = response.hover_pos() {
+        let bucket = bucket_of(&time_at(position.x));
+        let start = first
+            + Duration::milliseconds(span * bucket as i64 / TIMELINE_BUCKETS as i64);
+
+        response.on_hover_text_at_pointer(format!(
+            "{} log(s) from {}\n\nDrag to filter the logs to a time range, double-click \
+             to clear it",
+            buckets[bucket],
+            start.format(ISO_8601_TIME_FORMAT)
+        ));
+    }
+
+    ui.horizontal(|ui| {
+        ui.small(first.format(ISO_8601_TIME_FORMAT).to_string());
+
+        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+            ui.small(last.format(ISO_8601_TIME_FORMAT).to_string());
+        });
+    });
+}
@@ -1,8 +1,61 @@
-use crate::widgets::CopyableLabel;
-use egui::{Context, Id};
+// region: imports
+
+use super::details_view_components::line_highlighter;
+use crate::{
+    data::{FoldNode, MessageFormat, MessagePreview},
+    widgets::CopyableLabel,
+};
+use egui::{text::LayoutJob, CollapsingHeader, Context, Id, Ui};
+use xxhash_rust::xxh3::xxh3_128 as xxhash;
+
+// endregion
+
+fn draw_fold_nodes(ui: &mut Ui, nodes: &[FoldNode], lines: &[LayoutJob]) {
+    for node in nodes {
+        let line = lines.get(node.line).cloned().unwrap_or_default();
+
+        if node.children.is_empty() {
+            ui.label(line);
+        } else {
+            CollapsingHeader::new(line)
+                .id_source(("message_preview_node", node.line))
+                .default_open(true)
+                .show(ui, |ui| draw_fold_nodes(ui, &node.children, lines));
+        }
+    }
+}
+
+fn update_preview(
+    preview: &mut MessagePreview,
+    ctx: &Context,
+    message: &str,
+    message_type: &str,
+) {
+    let hash = xxhash(format!("{message_type}\n{message}").as_bytes());
+
+    if preview.hash == hash {
+        return;
+    }
+
+    let pretty = MessageFormat::detect(message, message_type)
+        .and_then(|format| Some((format, format.pretty(message)?)));
+
+    *preview = match pretty {
+        Some((format, pretty)) => MessagePreview {
+            hash,
+            format: Some(format),
+            lines: line_highlighter(&pretty, format.syntax_name(), ctx),
+            tree: FoldNode::tree(format, &pretty.lines().collect::<Vec<_>>()),
+        },
+        None => MessagePreview {
+            hash,
+            ..MessagePreview::default()
+        },
+    };
+}
 
 pub fn message_preview_view(
-    is_open: &mut bool,
+    (is_open, is_raw, preview): (&mut bool, &mut bool, &mut MessagePreview),
     ctx: &Context,
     message: &str,
     message_type: &str,
@@ -20,19 +73,31 @@ pub fn message_preview_view(
         .show(ctx, |ui| {
             let mut message = message.to_string();
 
+            update_preview(preview, ctx, &message, message_type);
+
             ui.horizontal(|ui| {
-                ui.add(CopyableLabel::new(format!("Message type: {message_type}")))
+                ui.add(CopyableLabel::new(format!("Message type: {message_type}")));
+
+                if let Some(format) = preview.format {
+                    ui.separator();
+                    ui.label(format!("Formatted as {format}"));
+                    ui.checkbox(is_raw, "Raw");
+                }
             });
 
             egui::ScrollArea::vertical()
                 .max_height(ui.available_height())
                 .auto_shrink([false, false])
                 .show(ui, |ui| {
-                    ui.add(
-                        egui::TextEdit::multiline(&mut message)
-                            .code_editor()
-                            .desired_width(ui.available_width()),
-                    );
+                    if preview.format.is_some() && !*is_raw {
+                        draw_fold_nodes(ui, &preview.tree, &preview.lines);
+                    } else {
+                        ui.add(
+                            egui::TextEdit::multiline(&mut message)
+                                .code_editor()
+                                .desired_width(ui.available_width()),
+                        );
+                    }
                 });
         });
 }
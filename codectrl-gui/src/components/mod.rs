@@ -2,7 +2,6 @@
 
 mod about_view;
 mod about_view_components;
-mod common;
 mod details_view;
 mod details_view_components;
 mod main_view;
@@ -13,8 +12,6 @@ mod settings_view_components;
 
 // endregion
 
-use common::regex_filter;
-
 // region: re-exports
 
 pub use about_view::*;
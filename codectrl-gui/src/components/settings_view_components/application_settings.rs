@@ -1,4 +1,4 @@
-use crate::data::{ApplicationSettings, TimeFormatString};
+use crate::data::{ApplicationSettings, SavedFilter, SeverityStyle, TimeFormatString};
 
 use authentura_egui_styling::DARK_HEADER_FOREGROUND_COLOUR;
 use egui::{Button, RichText, Ui};
@@ -47,7 +47,84 @@ pub fn draw_application_settings(
             });
         });
 
+        ui.collapsing("Saved filters", |ui| {
+            draw_saved_filters(&mut application_settings.saved_filters, ui);
+        });
+
         // ui.collapsing("Server settings", |ui| {}); // TODO: enable editing
         // server settings (i.e: port or host).
     });
 }
+
+fn draw_saved_filters(saved_filters: &mut Vec<SavedFilter>, ui: &mut Ui) {
+    if saved_filters.is_empty() {
+        ui.label("No saved filters, save one from the \"Saved filters\" menu.");
+        return;
+    }
+
+    let mut deleted = None;
+    let mut assigned = None;
+
+    for (index, filter) in saved_filters.iter_mut().enumerate() {
+        ui.push_id(index, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Name:");
+                ui.text_edit_singleline(&mut filter.name);
+
+                let previous_shortcut = filter.shortcut;
+
+                egui::ComboBox::from_id_source("shortcut")
+                    .selected_text(match filter.shortcut {
+                        Some(shortcut) => format!("Ctrl+{shortcut}"),
+                        None => "No shortcut".into(),
+                    })
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut filter.shortcut, None, "No shortcut");
+
+                        for shortcut in 1..=9 {
+                            ui.selectable_value(
+                                &mut filter.shortcut,
+                                Some(shortcut),
+                                format!("Ctrl+{shortcut}"),
+                            );
+                        }
+                    });
+
+                if filter.shortcut.is_some() && filter.shortcut != previous_shortcut {
+                    assigned = Some(index);
+                }
+
+                // u1f5d1 = 🗑
+                if ui.button("\u{1f5d1}").on_hover_text("Delete").clicked() {
+                    deleted = Some(index);
+                }
+            });
+
+            ui.horizontal_wrapped(|ui| {
+                ui.label("Query:");
+                ui.monospace(&filter.query);
+
+                if let Some(severity) = filter.severity_filter {
+                    ui.label(format!("({} and above)", severity.label()));
+                }
+            });
+        });
+
+        ui.add_space(4.0);
+    }
+
+    // A shortcut can only apply one filter.
+    if let Some(index) = assigned {
+        let shortcut = saved_filters[index].shortcut;
+
+        for (other, filter) in saved_filters.iter_mut().enumerate() {
+            if other != index && filter.shortcut == shortcut {
+                filter.shortcut = None;
+            }
+        }
+    }
+
+    if let Some(index) = deleted {
+        saved_filters.remove(index);
+    }
+}
@@ -1,13 +1,13 @@
 // region: imports
 
-use super::{window_states::AboutState, ApplicationSettings, Filter, Received};
+use super::{
+    window_states::AboutState, ApplicationSettings, LogFilter, LogGroups, MessagePreview,
+    ParsedQuery, Received, ReceivedLog, Severity, TimeRange,
+};
 use crate::data::DEFAULT_FILENAME_FORMAT;
 use authentura_egui_styling::dark_theme;
 use chrono::{DateTime, Local};
-use codectrl_protobuf_bindings::{
-    data::Log,
-    logs_service::{Connection, ServerDetails},
-};
+use codectrl_protobuf_bindings::logs_service::{Connection, ServerDetails};
 use egui::Visuals;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -25,6 +25,7 @@ use std::time::Instant;
 
 pub fn time_details_last_checked_default() -> Instant { Instant::now() }
 pub fn refresh_server_details_default() -> bool { true }
+pub fn is_timeline_open_default() -> bool { true }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct AppState {
@@ -36,8 +37,22 @@ pub struct AppState {
     pub time_details_last_checked: Instant,
     #[serde(skip, default = "refresh_server_details_default")]
     pub refresh_server_details: bool,
-    pub search_filter: String,
-    pub filter_by: Filter,
+    /// The query in the filter bar, see `data::query` for its syntax.
+    #[serde(default, alias = "search_filter")]
+    pub query: String,
+    #[serde(skip)]
+    pub parsed_query: ParsedQuery,
+    /// Hides the logs below this severity, if set.
+    #[serde(default)]
+    pub severity_filter: Option<Severity>,
+    #[serde(skip)]
+    pub time_range: Option<TimeRange>,
+    #[serde(skip)]
+    pub time_range_input: String,
+    #[serde(skip)]
+    pub time_range_error: Option<String>,
+    #[serde(skip)]
+    pub timeline_drag: Option<(DateTime<Local>, DateTime<Local>)>,
     pub received: Received,
     pub do_scroll_to_selected_log: bool,
     #[serde(skip)]
@@ -48,10 +63,16 @@ pub struct AppState {
     pub is_case_sensitive: bool,
     pub is_copying_line_indicator: bool,
     pub is_copying_line_numbers: bool,
+    #[serde(default)]
+    pub is_grouping_logs: bool,
     pub is_message_preview_open: bool,
+    #[serde(default)]
+    pub is_message_preview_raw: bool,
     pub is_newest_first: bool,
+    #[serde(default = "is_timeline_open_default")]
+    pub is_timeline_open: bool,
     pub is_using_regex: bool,
-    pub clicked_item: Option<(Log, DateTime<Local>)>,
+    pub clicked_item: Option<ReceivedLog>,
     #[serde(skip)]
     pub preview_height: f32,
     #[serde(skip)]
@@ -61,6 +82,8 @@ pub struct AppState {
     pub copy_language: String,
     #[serde(skip)]
     pub alert_string: String,
+    #[serde(skip)]
+    pub saved_filter_name: String,
     pub message_alerts: BTreeSet<String>,
     #[serde(skip)]
     pub session_timestamp: String,
@@ -71,6 +94,10 @@ pub struct AppState {
     pub code_hash: u128,
     #[serde(skip)]
     pub code_job: egui::text::LayoutJob,
+    #[serde(skip)]
+    pub message_preview: MessagePreview,
+    #[serde(skip)]
+    pub log_groups: LogGroups,
 }
 
 impl Default for AppState {
@@ -80,14 +107,21 @@ impl Default for AppState {
             grpc_client_connection: None,
             time_details_last_checked: time_details_last_checked_default(),
             refresh_server_details: refresh_server_details_default(),
-            search_filter: "".into(),
-            filter_by: Filter::Message,
+            query: "".into(),
+            parsed_query: ParsedQuery::default(),
+            severity_filter: None,
+            time_range: None,
+            time_range_input: "".into(),
+            time_range_error: None,
+            timeline_drag: None,
             received: Arc::new(RwLock::new(VecDeque::new())),
             is_case_sensitive: false,
             is_using_regex: false,
             is_newest_first: true,
+            is_timeline_open: is_timeline_open_default(),
             is_about_open: false,
             is_message_preview_open: false,
+            is_message_preview_raw: false,
             clicked_item: None,
             preview_height: 0.0,
             about_state: AboutState::About,
@@ -95,10 +129,12 @@ impl Default for AppState {
             copy_language: "".into(),
             is_copying_line_numbers: false,
             is_copying_line_indicator: false,
+            is_grouping_logs: false,
             do_scroll_to_selected_log: false,
             is_autosave: false,
             is_settings_open: false,
             alert_string: "".into(),
+            saved_filter_name: "".into(),
             message_alerts: BTreeSet::new(),
             session_timestamp: "".into(),
             application_settings: ApplicationSettings::default(),
@@ -106,6 +142,45 @@ impl Default for AppState {
             preserve_session: true,
             code_hash: 0,
             code_job: egui::text::LayoutJob::default(),
+            message_preview: MessagePreview::default(),
+            log_groups: LogGroups::default(),
         }
     }
 }
+
+impl AppState {
+    /// The logs that pass the filters, in the order that they're shown in the
+    /// log table when they aren't grouped.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn shown_logs(&mut self) -> Vec<ReceivedLog> {
+        let filter = LogFilter {
+            severity_filter: self.severity_filter,
+            time_range: self.time_range,
+            query: self
+                .parsed_query
+                .update(&self.query, self.is_case_sensitive, self.is_using_regex)
+                .as_ref()
+                .ok(),
+            now: Local::now(),
+        };
+
+        let mut logs: Vec<_> = self
+            .received
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|received| filter.matches(received))
+            .cloned()
+            .collect();
+
+        logs.sort_by(|a, b| {
+            if self.is_newest_first {
+                b.time.cmp(&a.time)
+            } else {
+                a.time.cmp(&b.time)
+            }
+        });
+
+        logs
+    }
+}
@@ -0,0 +1,323 @@
+use super::{ReceivedLog, SeverityStyle, ISO_8601_TIME_FORMAT};
+use chrono::Local;
+use codectrl_protobuf_bindings::data::Log;
+use serde_json::{json, Value};
+use std::{collections::BTreeMap, fmt::Write, path::Path};
+use syntect::{
+    easy::HighlightLines,
+    highlighting::{Style, Theme, ThemeSet},
+    html::{styled_line_to_highlighted_html, IncludeBackground},
+    parsing::{SyntaxReference, SyntaxSet},
+};
+
+const CSV_HEADER: [&str; 11] = [
+    "received_at",
+    "severity",
+    "message",
+    "message_type",
+    "file_name",
+    "line_number",
+    "address",
+    "language",
+    "uuid",
+    "warnings",
+    "fields",
+];
+
+const HTML_STYLE: &str = "
+body { font-family: sans-serif; background: #1b1b1b; color: #dcdcdc; margin: 2em; }
+h1 { color: #42b983; }
+section { border: 1px solid #3c3c3c; border-radius: 4px; margin-bottom: 1.5em; padding: \
+                          0 1em 1em; }
+th { text-align: left; padding-right: 1em; color: #a0a0a0; vertical-align: top; }
+pre { background: #002b36; padding: 0.5em; overflow-x: auto; }
+.line-number { color: #657b83; user-select: none; }
+.current { background: #073642; display: inline-block; width: 100%; }
+.severity { font-weight: bold; }
+";
+
+/// A format that the shown logs can be exported to, for other tools or to
+/// attach to bug reports.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ExportFormat {
+    JsonLines,
+    Csv,
+    Html,
+}
+
+impl ExportFormat {
+    pub const ALL: [Self; 3] = [Self::JsonLines, Self::Csv, Self::Html];
+
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::JsonLines => "JSON Lines",
+            Self::Csv => "CSV",
+            Self::Html => "HTML report",
+        }
+    }
+
+    #[must_use]
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::JsonLines => "jsonl",
+            Self::Csv => "csv",
+            Self::Html => "html",
+        }
+    }
+
+    #[must_use]
+    pub fn export(self, logs: &[ReceivedLog]) -> String {
+        match self {
+            Self::JsonLines => logs
+                .iter()
+                .map(|received| format!("{}\n", log_to_json(received)))
+                .collect(),
+            Self::Csv => export_csv(logs),
+            Self::Html => export_html(logs),
+        }
+    }
+}
+
+fn log_to_json(
+    ReceivedLog {
+        log,
+        time,
+        severity,
+        fields,
+    }: &ReceivedLog,
+) -> Value {
+    let code_snippet: BTreeMap<_, _> = log.code_snippet.iter().collect();
+
+    json!({
+        "uuid": log.uuid,
+        "received_at": time.to_rfc3339(),
+        "severity": severity.to_string(),
+        "message": log.message,
+        "message_type": log.message_type,
+        "file_name": log.file_name,
+        "line_number": log.line_number,
+        "address": log.address,
+        "language": log.language,
+        "fields": fields,
+        "warnings": log.warnings,
+        "stack": log.stack.iter().map(|stack| json!({
+            "file_path": stack.file_path,
+            "line_number": stack.line_number,
+            "column_number": stack.column_number,
+            "code": stack.code,
+        })).collect::<Vec<_>>(),
+        "code_snippet": code_snippet,
+    })
+}
+
+/// Quotes a CSV field if it needs to be, as in RFC 4180.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn export_csv(logs: &[ReceivedLog]) -> String {
+    let mut csv = format!("{}\r\n", CSV_HEADER.join(","));
+
+    for ReceivedLog {
+        log,
+        time,
+        severity,
+        fields,
+    } in logs
+    {
+        let record = [
+            time.to_rfc3339(),
+            severity.to_string(),
+            log.message.clone(),
+            log.message_type.clone(),
+            log.file_name.clone(),
+            log.line_number.to_string(),
+            log.address.clone(),
+            log.language.clone(),
+            log.uuid.clone(),
+            log.warnings.join("; "),
+            if fields.is_empty() {
+                String::new()
+            } else {
+                Value::Object(fields.clone()).to_string()
+            },
+        ];
+
+        let record: Vec<_> = record.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&record.join(","));
+        csv.push_str("\r\n");
+    }
+
+    csv
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Highlights a single line of code as HTML, falling back to the escaped line
+/// if it can't be highlighted.
+fn highlighted_line(
+    highlight: &mut HighlightLines,
+    syntax_set: &SyntaxSet,
+    line: &str,
+) -> String {
+    let line = format!("{line}\n");
+    let ranges = highlight
+        .highlight_line(&line, syntax_set)
+        .unwrap_or_else(|_| vec![(Style::default(), line.as_str())]);
+
+    styled_line_to_highlighted_html(&ranges, IncludeBackground::No)
+        .unwrap_or_else(|_| escape_html(&line))
+}
+
+fn syntax_of<'a>(log: &Log, syntax_set: &'a SyntaxSet) -> &'a SyntaxReference {
+    if let Some(syntax) = syntax_set.find_syntax_by_name(&log.language) {
+        syntax
+    } else if let Ok(Some(syntax)) = syntax_set
+        .find_syntax_for_file(Path::new(&log.file_name).extension().unwrap_or_default())
+    {
+        syntax
+    } else {
+        syntax_set.find_syntax_plain_text()
+    }
+}
+
+fn html_log(
+    html: &mut String,
+    ReceivedLog {
+        log,
+        time,
+        severity,
+        fields,
+    }: &ReceivedLog,
+    syntax_set: &SyntaxSet,
+    theme: &Theme,
+) {
+    let syntax = syntax_of(log, syntax_set);
+    let [r, g, b, _] = severity.colour().to_array();
+
+    let _ = write!(
+        html,
+        "<section>\n<h2>{file_name}:{line_number}</h2>\n<table>\n<tr><th>Severity</\
+         th><td class=\"severity\" style=\"color: \
+         #{r:02x}{g:02x}{b:02x}\">{severity}</td></tr>\n<tr><th>Message</\
+         th><td><pre>{message}</pre></td></tr>\n<tr><th>Message \
+         type</th><td><code>{message_type}</code></td></tr>\n<tr><th>Received \
+         at</th><td>{time}</td></tr>\n<tr><th>Received \
+         from</th><td><code>{address}</code></td></tr>\n</table>\n",
+        file_name = escape_html(&log.file_name),
+        line_number = log.line_number,
+        severity = severity.label(),
+        message = escape_html(&log.message),
+        message_type = escape_html(&log.message_type),
+        time = time.format(ISO_8601_TIME_FORMAT),
+        address = escape_html(&log.address),
+    );
+
+    if !log.code_snippet.is_empty() {
+        let code_snippet: BTreeMap<_, _> = log.code_snippet.iter().collect();
+        let mut highlight = HighlightLines::new(syntax, theme);
+
+        html.push_str("<h3>Code</h3>\n<pre>");
+
+        for (line_number, line) in code_snippet {
+            let class = if *line_number == log.line_number {
+                " class=\"current\""
+            } else {
+                ""
+            };
+
+            let _ = write!(
+                html,
+                "<span{class}><span class=\"line-number\">{line_number:>3}  \
+                 </span>{}</span>",
+                highlighted_line(&mut highlight, syntax_set, line)
+            );
+        }
+
+        html.push_str("</pre>\n");
+    }
+
+    if !log.stack.is_empty() {
+        let _ = write!(
+            html,
+            "<details>\n<summary>Stacktrace ({} layer(s))</summary>\n<ol>\n",
+            log.stack.len()
+        );
+
+        for stack in log.stack.iter().rev() {
+            let mut highlight = HighlightLines::new(syntax, theme);
+
+            let _ = write!(
+                html,
+                "<li><code>{file_path}:{line_number} column \
+                 {column_number}</code><pre>{code}</pre></li>\n",
+                file_path = escape_html(&stack.file_path),
+                line_number = stack.line_number,
+                column_number = stack.column_number,
+                code = highlighted_line(&mut highlight, syntax_set, &stack.code),
+            );
+        }
+
+        html.push_str("</ol>\n</details>\n");
+    }
+
+    if !fields.is_empty() {
+        let fields = serde_json::to_string_pretty(fields).unwrap_or_default();
+
+        let _ = write!(
+            html,
+            "<details>\n<summary>Fields</summary>\n<pre>{}</pre>\n</details>\n",
+            escape_html(&fields)
+        );
+    }
+
+    if !log.warnings.is_empty() {
+        let _ = write!(
+            html,
+            "<details>\n<summary>{} Warning(s)</summary>\n<ol>\n",
+            log.warnings.len()
+        );
+
+        for warning in &log.warnings {
+            let _ = writeln!(html, "<li>{}</li>", escape_html(warning));
+        }
+
+        html.push_str("</ol>\n</details>\n");
+    }
+
+    html.push_str("</section>\n");
+}
+
+/// Builds a standalone HTML page of the logs, with their code snippets and
+/// stack traces highlighted inline so it can be attached to a bug report.
+fn export_html(logs: &[ReceivedLog]) -> String {
+    let syntax_set = SyntaxSet::load_defaults_newlines();
+    let theme_set = ThemeSet::load_defaults();
+    let theme = &theme_set.themes["Solarized (dark)"];
+
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta \
+         charset=\"utf-8\">\n<title>CodeCTRL \
+         logs</title>\n<style>{HTML_STYLE}</style>\n</head>\n<body>\n<h1>CodeCTRL \
+         logs</h1>\n<p>{} log(s), exported at {}.</p>\n",
+        logs.len(),
+        Local::now().format(ISO_8601_TIME_FORMAT)
+    );
+
+    for received in logs {
+        html_log(&mut html, received, &syntax_set, theme);
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
@@ -0,0 +1,27 @@
+use super::{query::Query, ReceivedLog, Severity, TimeRange};
+use chrono::{DateTime, Local};
+
+/// The filters that decide which logs are shown in the log table and
+/// exported, taken from the `AppState` once per frame.
+#[derive(Debug, Clone, Copy)]
+pub struct LogFilter<'a> {
+    pub severity_filter: Option<Severity>,
+    pub time_range: Option<TimeRange>,
+    /// The parsed query, or `None` if it has a syntax error, in which case
+    /// every log is shown until it's fixed.
+    pub query: Option<&'a Query>,
+    /// When relative time ranges are counted back from.
+    pub now: DateTime<Local>,
+}
+
+impl LogFilter<'_> {
+    #[must_use]
+    pub fn matches(&self, received: &ReceivedLog) -> bool {
+        self.severity_filter
+            .map_or(true, |min_severity| received.severity >= min_severity)
+            && self.time_range.map_or(true, |time_range| {
+                time_range.contains(&received.time, self.now)
+            })
+            && self.query.map_or(true, |query| query.matches(received))
+    }
+}
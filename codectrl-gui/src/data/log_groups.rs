@@ -0,0 +1,148 @@
+use super::ReceivedLog;
+use chrono::{DateTime, Local};
+use codectrl_protobuf_bindings::data::Log;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+/// What logs are grouped by: where they were sent from and their message with
+/// the numbers taken out, so that a loop logging `Iteration 1`, `Iteration 2`,
+/// ... ends up in one group.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+pub struct GroupKey {
+    pub file_name: String,
+    pub line_number: u32,
+    pub template: String,
+}
+
+impl GroupKey {
+    #[must_use]
+    pub fn of(log: &Log) -> Self {
+        Self {
+            file_name: log.file_name.clone(),
+            line_number: log.line_number,
+            template: message_template(&log.message),
+        }
+    }
+}
+
+/// The number of bars in the sparkline of a group.
+pub const SPARKLINE_BUCKETS: usize = 24;
+
+/// Replaces every number in `message` with `#`. Double quotes are taken out,
+/// like the log table does, as string messages arrive wrapped in the quotes of
+/// their `Debug` form.
+#[must_use]
+pub fn message_template(message: &str) -> String {
+    let mut template = String::with_capacity(message.len());
+    let mut is_in_number = false;
+
+    for character in message.replace('\"', "").chars() {
+        if character.is_ascii_digit() {
+            if !is_in_number {
+                template.push('#');
+                is_in_number = true;
+            }
+        } else {
+            template.push(character);
+            is_in_number = false;
+        }
+    }
+
+    template
+}
+
+/// Counts how many of `times` fall into each of [`SPARKLINE_BUCKETS`] equal
+/// spans of time, from the first of `times` to the last. `times` have to be
+/// oldest first.
+#[must_use]
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub fn sparkline_buckets(times: &[DateTime<Local>]) -> [usize; SPARKLINE_BUCKETS] {
+    let mut buckets = [0_usize; SPARKLINE_BUCKETS];
+
+    let (first, last) = match (times.first(), times.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return buckets,
+    };
+    let span = (*last - *first).num_milliseconds().max(1);
+
+    for time in times {
+        let bucket =
+            (*time - *first).num_milliseconds() * (SPARKLINE_BUCKETS as i64 - 1) / span;
+
+        buckets[(bucket.max(0) as usize).min(SPARKLINE_BUCKETS - 1)] += 1;
+    }
+
+    buckets
+}
+
+/// The logs in a group, as their sequence numbers and the times they were
+/// received. Sequence numbers count up from the oldest log in `Received`, and
+/// don't change as new logs are pushed to the front of it.
+#[derive(Debug, Clone)]
+pub struct LogGroup {
+    pub key: GroupKey,
+    pub entries: Vec<(usize, DateTime<Local>)>,
+}
+
+/// The logs in `Received` grouped by [`GroupKey`]. The groups are updated
+/// incrementally as logs arrive, and only built again from scratch when
+/// `Received` is cleared or replaced.
+#[derive(Debug, Clone, Default)]
+pub struct LogGroups {
+    pub groups: Vec<LogGroup>,
+    pub expanded: BTreeSet<GroupKey>,
+    indices: HashMap<GroupKey, usize>,
+    seen: usize,
+    oldest: Option<(String, DateTime<Local>)>,
+}
+
+impl LogGroups {
+    pub fn update(&mut self, received: &VecDeque<ReceivedLog>) {
+        let oldest = received
+            .back()
+            .map(|oldest| (oldest.log.uuid.clone(), oldest.time));
+
+        // New logs are only ever pushed to the front, so if the oldest log has
+        // changed then the logs have been cleared or a session was loaded.
+        if oldest != self.oldest || received.len() < self.seen {
+            self.groups.clear();
+            self.indices.clear();
+            self.seen = 0;
+            self.oldest = oldest;
+        }
+
+        let new = received.len() - self.seen;
+
+        for (index, ReceivedLog { log, time, .. }) in
+            received.iter().enumerate().take(new).rev()
+        {
+            let sequence = received.len() - 1 - index;
+            let key = GroupKey::of(log);
+
+            let group = match self.indices.get(&key) {
+                Some(&group) => group,
+                None => {
+                    self.indices.insert(key.clone(), self.groups.len());
+                    self.groups.push(LogGroup {
+                        key,
+                        entries: vec![],
+                    });
+
+                    self.groups.len() - 1
+                },
+            };
+
+            self.groups[group].entries.push((sequence, *time));
+        }
+
+        self.seen = received.len();
+    }
+
+    /// The log with the `sequence` number in `received`.
+    #[must_use]
+    pub fn entry(
+        received: &VecDeque<ReceivedLog>,
+        sequence: usize,
+    ) -> Option<&ReceivedLog> {
+        received.get(received.len().checked_sub(sequence + 1)?)
+    }
+}
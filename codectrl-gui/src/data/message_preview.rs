@@ -0,0 +1,205 @@
+use egui::text::LayoutJob;
+use quick_xml::{events::Event, Reader, Writer};
+use std::{
+    borrow::Cow,
+    fmt::{self, Display},
+};
+
+/// A structured format that a message can be pretty-printed as.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MessageFormat {
+    Json,
+    Yaml,
+    Xml,
+    Toml,
+}
+
+impl MessageFormat {
+    /// Works out the format of a message from its type, i.e.
+    /// `serde_json::Value`, or failing that from what the message looks
+    /// like. YAML and TOML are only sniffed from messages that span
+    /// multiple lines, as a lot of plain text is valid YAML.
+    #[must_use]
+    pub fn detect(message: &str, message_type: &str) -> Option<Self> {
+        let message_type = message_type.to_lowercase();
+
+        let from_type = if message_type.contains("json") {
+            Some(Self::Json)
+        } else if message_type.contains("yaml") || message_type.contains("yml") {
+            Some(Self::Yaml)
+        } else if message_type.contains("xml") {
+            Some(Self::Xml)
+        } else if message_type.contains("toml") {
+            Some(Self::Toml)
+        } else {
+            None
+        };
+
+        if from_type.is_some() {
+            return from_type;
+        }
+
+        let message = unquote(message);
+        let message = message.trim();
+
+        if (message.starts_with('{') || message.starts_with('['))
+            && serde_json::from_str::<serde_json::Value>(message).is_ok()
+        {
+            return Some(Self::Json);
+        }
+
+        if message.starts_with('<') && message.ends_with('>') {
+            return Self::Xml.pretty(message).map(|_| Self::Xml);
+        }
+
+        if !message.contains('\n') {
+            return None;
+        }
+
+        if matches!(
+            toml::from_str::<toml::Value>(message),
+            Ok(toml::Value::Table(table)) if !table.is_empty()
+        ) {
+            return Some(Self::Toml);
+        }
+
+        match serde_yaml::from_str::<serde_yaml::Value>(message) {
+            Ok(serde_yaml::Value::Mapping(_) | serde_yaml::Value::Sequence(_)) =>
+                Some(Self::Yaml),
+            _ => None,
+        }
+    }
+
+    /// The name of the syntax of this format in syntect.
+    #[must_use]
+    pub fn syntax_name(self) -> &'static str {
+        match self {
+            Self::Json => "JSON",
+            Self::Yaml => "YAML",
+            Self::Xml => "XML",
+            Self::Toml => "TOML",
+        }
+    }
+
+    /// Pretty-prints `message` in this format, if it's valid.
+    #[must_use]
+    pub fn pretty(self, message: &str) -> Option<String> {
+        let message = unquote(message);
+        let message = message.trim();
+
+        match self {
+            Self::Json => serde_json::from_str::<serde_json::Value>(message)
+                .ok()
+                .and_then(|value| serde_json::to_string_pretty(&value).ok()),
+            Self::Yaml => serde_yaml::from_str::<serde_yaml::Value>(message)
+                .ok()
+                .and_then(|value| serde_yaml::to_string(&value).ok()),
+            Self::Toml => toml::from_str::<toml::Value>(message)
+                .ok()
+                .and_then(|value| toml::to_string_pretty(&value).ok()),
+            Self::Xml => {
+                let mut reader = Reader::from_str(message);
+                reader.trim_text(true);
+
+                let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+
+                loop {
+                    match reader.read_event().ok()? {
+                        Event::Eof => break,
+                        event => writer.write_event(event).ok()?,
+                    }
+                }
+
+                String::from_utf8(writer.into_inner()).ok()
+            },
+        }
+    }
+
+    /// How deeply nested a line of pretty-printed text is, for folding. Tables
+    /// aren't indented in TOML, so the lines under a table header are nested in
+    /// it.
+    fn depth(self, line: &str) -> usize {
+        let indentation = line.len() - line.trim_start().len();
+
+        match self {
+            Self::Toml if line.starts_with('[') => 0,
+            Self::Toml => indentation + 1,
+            _ => indentation,
+        }
+    }
+}
+
+impl Display for MessageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.syntax_name())
+    }
+}
+
+/// Loggers that format their messages with `Debug` send strings quoted and
+/// escaped.
+fn unquote(message: &str) -> Cow<'_, str> {
+    match serde_json::from_str::<serde_json::Value>(message.trim()) {
+        Ok(serde_json::Value::String(message)) => Cow::Owned(message),
+        _ => Cow::Borrowed(message),
+    }
+}
+
+/// A line of a pretty-printed message, along with the lines nested in it that
+/// it can be collapsed over.
+#[derive(Debug, Clone)]
+pub struct FoldNode {
+    pub line: usize,
+    pub children: Vec<FoldNode>,
+}
+
+impl FoldNode {
+    /// Builds the folding tree of the lines of a pretty-printed message. Blank
+    /// lines aren't part of the tree.
+    #[must_use]
+    pub fn tree(format: MessageFormat, lines: &[&str]) -> Vec<Self> {
+        let lines: Vec<(usize, usize)> = lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| (index, format.depth(line)))
+            .collect();
+
+        Self::children(&lines, &mut 0, None)
+    }
+
+    fn children(
+        lines: &[(usize, usize)],
+        position: &mut usize,
+        parent_depth: Option<usize>,
+    ) -> Vec<Self> {
+        let mut nodes = vec![];
+
+        while let Some(&(line, depth)) = lines.get(*position) {
+            if parent_depth.map_or(false, |parent_depth| depth <= parent_depth) {
+                break;
+            }
+
+            *position += 1;
+
+            let children = match lines.get(*position) {
+                Some(&(_, next_depth)) if next_depth > depth =>
+                    Self::children(lines, position, Some(depth)),
+                _ => vec![],
+            };
+
+            nodes.push(Self { line, children });
+        }
+
+        nodes
+    }
+}
+
+/// The pretty-printed and highlighted message in the message preview, which is
+/// only worked out again when the message changes.
+#[derive(Debug, Clone, Default)]
+pub struct MessagePreview {
+    pub hash: u128,
+    pub format: Option<MessageFormat>,
+    pub lines: Vec<LayoutJob>,
+    pub tree: Vec<FoldNode>,
+}
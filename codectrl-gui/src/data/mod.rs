@@ -1,8 +1,16 @@
 // region: modules
 
 mod app_state;
-mod filter;
+#[cfg(not(target_arch = "wasm32"))]
+mod export;
+mod log_filter;
+mod log_groups;
+mod message_preview;
+mod query;
+mod saved_filter;
 mod settings;
+mod severity;
+mod time_range;
 mod types;
 
 pub mod window_states;
@@ -11,9 +19,20 @@ pub mod window_states;
 // region: re-exports
 
 pub use app_state::AppState;
-pub use filter::Filter;
+pub use codectrl_common::fields::{value_to_string as field_value_to_string, Fields};
+#[cfg(not(target_arch = "wasm32"))]
+pub use export::ExportFormat;
+pub use log_filter::LogFilter;
+pub use log_groups::{
+    message_template, sparkline_buckets, GroupKey, LogGroups, SPARKLINE_BUCKETS,
+};
+pub use message_preview::{FoldNode, MessageFormat, MessagePreview};
+pub use query::{ParsedQuery, Query, QueryError, QUERY_HELP};
+pub use saved_filter::{merge_saved_filters, SavedFilter};
 pub use settings::ApplicationSettings;
-pub use types::{Received, TimeFormatString};
+pub use severity::{Severity, SeverityStyle};
+pub use time_range::TimeRange;
+pub use types::{Received, ReceivedLog, TimeFormatString};
 
 // endregion
 
@@ -0,0 +1,468 @@
+//! The query language of the filter bar. A query is a list of terms separated
+//! by spaces, all of which have to match for a log to be shown:
+//!
+//! - `timeout` or `"timed out"`: the message contains the text.
+//! - `file:main.rs`, `msg:timeout`, `host:10.0.*`, `type:String`, `lang:Rust`:
+//!   the field contains the text, where `*` matches anything.
+//! - `msg~/time(d )?out/` or `file:/\.rs$/`: the field matches the regex.
+//! - `line:>40`, `warnings:0`: compares a number with `=`, `<`, `<=`, `>` or
+//!   `>=`, which defaults to `=`.
+//! - `severity:>=warn`: compares the severity of the log.
+//! - `after:10:30`, `before:2023-04-01T12:00`: when the log was received, times
+//!   without a date are today.
+//! - `field:user.id=42`: matches a structured field, or just `field:user.id`
+//!   for logs that have it.
+//!
+//! Any term can be negated with a `-` in front of it, i.e. `-warnings:0`.
+
+use super::{ReceivedLog, Severity};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use codectrl_common::fields::FieldFilter;
+use regex::{escape, Regex, RegexBuilder};
+use std::{
+    fmt::{self, Display},
+    ops::Range,
+};
+
+/// A summary of the syntax, for the filter bar.
+pub const QUERY_HELP: &str = "Terms are separated by spaces and all have to match:
+  timeout or \"timed out\"   message contains
+  file:main.rs              file name, msg, host, type or lang contains, * matches \
+                              anything
+  msg~/time(d )?out/        matches a regex
+  line:>40 warnings:0       compares with =, <, <=, > or >=
+  severity:>=warn           compares the severity
+  after:10:30 before:2023-04-01T12:00
+  field:user.id=42          structured field
+  -term                     negates a term";
+
+/// A syntax error in a query, along with where it is in the query.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct QueryError {
+    pub message: String,
+    pub span: Range<usize>,
+}
+
+impl QueryError {
+    fn new(message: impl Into<String>, span: Range<usize>) -> Self {
+        Self {
+            message: message.into(),
+            span,
+        }
+    }
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (column {})", self.message, self.span.start + 1)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TextField {
+    Message,
+    FileName,
+    Address,
+    MessageType,
+    Language,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum NumberField {
+    LineNumber,
+    Warnings,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Comparison {
+    Equal,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    /// Splits the comparison off the front of `value`.
+    fn split(value: &str) -> (Self, &str) {
+        [
+            (">=", Self::GreaterOrEqual),
+            ("<=", Self::LessOrEqual),
+            (">", Self::Greater),
+            ("<", Self::Less),
+            ("=", Self::Equal),
+        ]
+        .into_iter()
+        .find_map(|(operator, comparison)| {
+            value
+                .strip_prefix(operator)
+                .map(|value| (comparison, value))
+        })
+        .unwrap_or((Self::Equal, value))
+    }
+
+    fn compare<T: Ord>(self, left: &T, right: &T) -> bool {
+        match self {
+            Self::Equal => left == right,
+            Self::Less => left < right,
+            Self::LessOrEqual => left <= right,
+            Self::Greater => left > right,
+            Self::GreaterOrEqual => left >= right,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Predicate {
+    Text(TextField, Regex),
+    Number(NumberField, Comparison, u32),
+    Severity(Comparison, Severity),
+    After(DateTime<Local>),
+    Before(DateTime<Local>),
+    Field(FieldFilter),
+}
+
+impl Predicate {
+    fn matches(
+        &self,
+        ReceivedLog {
+            log,
+            time,
+            severity: log_severity,
+            fields,
+        }: &ReceivedLog,
+    ) -> bool {
+        match self {
+            Self::Text(field, regex) => regex.is_match(match field {
+                TextField::Message => &log.message,
+                TextField::FileName => &log.file_name,
+                TextField::Address => &log.address,
+                TextField::MessageType => &log.message_type,
+                TextField::Language => &log.language,
+            }),
+            Self::Number(field, comparison, value) => {
+                let number = match field {
+                    NumberField::LineNumber => log.line_number,
+                    NumberField::Warnings =>
+                        u32::try_from(log.warnings.len()).unwrap_or(u32::MAX),
+                };
+
+                comparison.compare(&number, value)
+            },
+            Self::Severity(comparison, severity) =>
+                comparison.compare(log_severity, severity),
+            Self::After(after) => time >= after,
+            Self::Before(before) => time < before,
+            Self::Field(filter) => filter.matches(fields),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Term {
+    pub is_negated: bool,
+    pub predicate: Predicate,
+}
+
+/// A parsed query, which matches every log if it has no terms.
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    pub terms: Vec<Term>,
+}
+
+const TEXT_FIELDS: [&str; 6] = ["msg", "message", "file", "host", "type", "lang"];
+const FIELDS: [&str; 12] = [
+    "msg", "message", "file", "host", "type", "lang", "line", "warnings", "severity",
+    "after", "before", "field",
+];
+
+struct Token<'a> {
+    text: &'a str,
+    start: usize,
+}
+
+/// Splits a query on whitespace, except for whitespace in quotes or regexes.
+fn tokenise(query: &str) -> Result<Vec<Token<'_>>, QueryError> {
+    let mut tokens = vec![];
+    let mut characters = query.char_indices().peekable();
+
+    while let Some(&(start, character)) = characters.peek() {
+        if character.is_whitespace() {
+            characters.next();
+            continue;
+        }
+
+        let mut end = query.len();
+        let mut is_in_quotes = false;
+        let mut is_in_regex = false;
+        let mut previous = None;
+
+        while let Some(&(index, character)) = characters.peek() {
+            if character.is_whitespace() && !is_in_quotes && !is_in_regex {
+                end = index;
+                break;
+            }
+
+            match character {
+                '"' if !is_in_regex => is_in_quotes = !is_in_quotes,
+                '/' if is_in_regex && previous != Some('\\') => is_in_regex = false,
+                // A regex can only start a value.
+                '/' if !is_in_quotes
+                    && (matches!(previous, None | Some(':' | '~'))
+                        || (previous == Some('-') && index == start + 1)) =>
+                    is_in_regex = true,
+                _ => (),
+            }
+
+            previous = Some(character);
+            characters.next();
+        }
+
+        if is_in_quotes {
+            return Err(QueryError::new(
+                "Missing a closing `\"`",
+                start..query.len(),
+            ));
+        }
+
+        if is_in_regex {
+            return Err(QueryError::new("Missing a closing `/`", start..query.len()));
+        }
+
+        tokens.push(Token {
+            text: &query[start..end],
+            start,
+        });
+    }
+
+    Ok(tokens)
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+fn strip_slashes(value: &str) -> Option<&str> {
+    value
+        .strip_prefix('/')
+        .and_then(|value| value.strip_suffix('/'))
+}
+
+pub(super) fn parse_time(value: &str) -> Option<DateTime<Local>> {
+    let naive = ["%H:%M", "%H:%M:%S"]
+        .into_iter()
+        .find_map(|format| NaiveTime::parse_from_str(value, format).ok())
+        .map(|time| Local::now().date_naive().and_time(time))
+        .or_else(|| {
+            ["%FT%H:%M", "%FT%H:%M:%S", "%F %H:%M", "%F %H:%M:%S"]
+                .into_iter()
+                .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        })
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%F")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })?;
+
+    Local.from_local_datetime(&naive).earliest()
+}
+
+impl Query {
+    /// Parses `query`, where `is_case_sensitive` and `is_using_regex` apply to
+    /// the text terms.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first syntax error in the query.
+    pub fn parse(
+        query: &str,
+        is_case_sensitive: bool,
+        is_using_regex: bool,
+    ) -> Result<Self, QueryError> {
+        let terms = tokenise(query)?
+            .into_iter()
+            .map(|token| Self::parse_term(&token, is_case_sensitive, is_using_regex))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { terms })
+    }
+
+    fn parse_term(
+        token: &Token<'_>,
+        is_case_sensitive: bool,
+        is_using_regex: bool,
+    ) -> Result<Term, QueryError> {
+        let (is_negated, text, start) = match token.text.strip_prefix('-') {
+            Some(text) if !text.is_empty() => (true, text, token.start + 1),
+            _ => (false, token.text, token.start),
+        };
+        let span = start..token.start + token.text.len();
+
+        let name_length = text
+            .find(|character: char| !character.is_ascii_alphabetic())
+            .unwrap_or(text.len());
+        let operator = text[name_length..].chars().next();
+
+        // Anything that isn't `name:value` or `name~value` searches the message.
+        let (name, operator, value) = match operator {
+            Some(operator @ (':' | '~')) if name_length > 0 => (
+                text[..name_length].to_lowercase(),
+                operator,
+                &text[name_length + 1..],
+            ),
+            _ => ("msg".into(), ':', text),
+        };
+        let value_span = span.end - value.len()..span.end;
+        let value = unquote(value);
+
+        if !FIELDS.contains(&name.as_str()) {
+            return Err(QueryError::new(
+                format!(
+                    "Unknown field `{name}`, put the term in quotes to search messages \
+                     for it"
+                ),
+                span,
+            ));
+        }
+
+        if value.is_empty() {
+            return Err(QueryError::new(
+                format!("Expected a value after `{name}{operator}`"),
+                span,
+            ));
+        }
+
+        let text_predicate = |field| {
+            let pattern = match strip_slashes(value) {
+                Some(regex) => regex.to_string(),
+                None if operator == '~' || is_using_regex => value.to_string(),
+                None => value.split('*').map(escape).collect::<Vec<_>>().join(".*"),
+            };
+
+            RegexBuilder::new(&pattern)
+                .case_insensitive(!is_case_sensitive)
+                .build()
+                .map(|regex| Predicate::Text(field, regex))
+                .map_err(|error| {
+                    QueryError::new(
+                        format!("Invalid regex: {}", error.to_string().trim()),
+                        value_span.clone(),
+                    )
+                })
+        };
+
+        let number_predicate = |field| {
+            let (comparison, number) = Comparison::split(value);
+
+            number
+                .trim()
+                .parse()
+                .map(|number| Predicate::Number(field, comparison, number))
+                .map_err(|_| {
+                    QueryError::new(
+                        format!("Expected a number after `{name}:`"),
+                        value_span.clone(),
+                    )
+                })
+        };
+
+        let time = || {
+            parse_time(value).ok_or_else(|| {
+                QueryError::new(
+                    "Expected a time like 10:30 or a date like 2023-04-01",
+                    value_span.clone(),
+                )
+            })
+        };
+
+        if operator == '~' && !TEXT_FIELDS.contains(&name.as_str()) {
+            return Err(QueryError::new(
+                format!("`{name}` can't be matched with a regex"),
+                span,
+            ));
+        }
+
+        let predicate = match name.as_str() {
+            "msg" | "message" => text_predicate(TextField::Message)?,
+            "file" => text_predicate(TextField::FileName)?,
+            "host" => text_predicate(TextField::Address)?,
+            "type" => text_predicate(TextField::MessageType)?,
+            "lang" => text_predicate(TextField::Language)?,
+            "line" => number_predicate(NumberField::LineNumber)?,
+            "warnings" => number_predicate(NumberField::Warnings)?,
+            "severity" => {
+                let (comparison, severity) = Comparison::split(value);
+
+                Predicate::Severity(
+                    comparison,
+                    severity
+                        .parse()
+                        .map_err(|error| QueryError::new(error, value_span.clone()))?,
+                )
+            },
+            "after" => Predicate::After(time()?),
+            "before" => Predicate::Before(time()?),
+            "field" => Predicate::Field(FieldFilter::parse(value)),
+            _ => unreachable!("the name has been checked"),
+        };
+
+        Ok(Term {
+            is_negated,
+            predicate,
+        })
+    }
+
+    #[must_use]
+    pub fn matches(&self, received: &ReceivedLog) -> bool {
+        self.terms
+            .iter()
+            .all(|term| term.predicate.matches(received) != term.is_negated)
+    }
+}
+
+/// The last query that was parsed, so that it's only parsed again when it or
+/// the options it was parsed with change.
+#[derive(Debug, Clone)]
+pub struct ParsedQuery {
+    source: String,
+    is_case_sensitive: bool,
+    is_using_regex: bool,
+    result: Result<Query, QueryError>,
+}
+
+impl Default for ParsedQuery {
+    fn default() -> Self {
+        Self {
+            source: String::new(),
+            is_case_sensitive: false,
+            is_using_regex: false,
+            result: Ok(Query::default()),
+        }
+    }
+}
+
+impl ParsedQuery {
+    pub fn update(
+        &mut self,
+        query: &str,
+        is_case_sensitive: bool,
+        is_using_regex: bool,
+    ) -> &Result<Query, QueryError> {
+        if self.source != query
+            || self.is_case_sensitive != is_case_sensitive
+            || self.is_using_regex != is_using_regex
+        {
+            *self = Self {
+                source: query.to_string(),
+                is_case_sensitive,
+                is_using_regex,
+                result: Query::parse(query, is_case_sensitive, is_using_regex),
+            };
+        }
+
+        &self.result
+    }
+}
@@ -0,0 +1,62 @@
+use super::{AppState, Severity};
+use serde::{Deserialize, Serialize};
+
+/// A named filter that can be applied again later, from the saved filters
+/// dropdown, the tabs above the log table or a keyboard shortcut.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SavedFilter {
+    pub name: String,
+    pub query: String,
+    #[serde(default)]
+    pub severity_filter: Option<Severity>,
+    #[serde(default)]
+    pub is_case_sensitive: bool,
+    #[serde(default)]
+    pub is_using_regex: bool,
+    /// The number key that applies the filter along with Ctrl (or Cmd), 1 to 9.
+    #[serde(default)]
+    pub shortcut: Option<u8>,
+}
+
+impl SavedFilter {
+    /// Saves the filter that is currently applied.
+    #[must_use]
+    pub fn from_state(name: &str, app_state: &AppState) -> Self {
+        Self {
+            name: name.trim().to_string(),
+            query: app_state.query.clone(),
+            severity_filter: app_state.severity_filter,
+            is_case_sensitive: app_state.is_case_sensitive,
+            is_using_regex: app_state.is_using_regex,
+            shortcut: None,
+        }
+    }
+
+    pub fn apply(&self, app_state: &mut AppState) {
+        app_state.query = self.query.clone();
+        app_state.severity_filter = self.severity_filter;
+        app_state.is_case_sensitive = self.is_case_sensitive;
+        app_state.is_using_regex = self.is_using_regex;
+    }
+
+    #[must_use]
+    pub fn is_applied(&self, app_state: &AppState) -> bool {
+        app_state.query == self.query
+            && app_state.severity_filter == self.severity_filter
+            && app_state.is_case_sensitive == self.is_case_sensitive
+            && app_state.is_using_regex == self.is_using_regex
+    }
+}
+
+/// Adds the filters in `saved` that aren't in `filters` yet, by name, i.e. the
+/// filters of a loaded session.
+pub fn merge_saved_filters(filters: &mut Vec<SavedFilter>, saved: Vec<SavedFilter>) {
+    for filter in saved {
+        if !filters.iter().any(|existing| existing.name == filter.name) {
+            filters.push(SavedFilter {
+                shortcut: None,
+                ..filter
+            });
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::data::DEFAULT_FILENAME_FORMAT;
+use crate::data::{SavedFilter, DEFAULT_FILENAME_FORMAT};
 use authentura_egui_styling::FontSizes;
 use serde::{Deserialize, Serialize};
 
@@ -7,6 +7,8 @@ pub struct ApplicationSettings {
     pub font_sizes: FontSizes,
     pub do_autosave: bool,
     pub filename_format: String,
+    #[serde(default)]
+    pub saved_filters: Vec<SavedFilter>,
 }
 
 impl Default for ApplicationSettings {
@@ -15,6 +17,7 @@ impl Default for ApplicationSettings {
             font_sizes: FontSizes::default(),
             do_autosave: false,
             filename_format: DEFAULT_FILENAME_FORMAT.into(),
+            saved_filters: vec![],
         }
     }
 }
@@ -0,0 +1,34 @@
+use egui::Color32;
+
+pub use codectrl_common::severity::Severity;
+
+/// How a [`Severity`] is shown in the GUI.
+pub trait SeverityStyle {
+    fn colour(self) -> Color32;
+    /// The capitalised name of the severity, i.e. `Warn`.
+    fn label(self) -> &'static str;
+}
+
+impl SeverityStyle for Severity {
+    fn colour(self) -> Color32 {
+        match self {
+            Self::Trace => Color32::GRAY,
+            Self::Debug => Color32::LIGHT_BLUE,
+            Self::Info => Color32::LIGHT_GREEN,
+            Self::Warn => Color32::YELLOW,
+            Self::Error => Color32::LIGHT_RED,
+            Self::Fatal => Color32::RED,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Trace => "Trace",
+            Self::Debug => "Debug",
+            Self::Info => "Info",
+            Self::Warn => "Warn",
+            Self::Error => "Error",
+            Self::Fatal => "Fatal",
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use super::query::parse_time;
+use chrono::{DateTime, Duration, Local};
+use std::fmt::{self, Display};
+
+const RANGE_TIME_FORMAT: &str = "%F %H:%M:%S";
+
+/// The units a relative range can be written in, along with how many seconds
+/// are in each. The first name is the one used when displaying a range.
+const UNITS: [(&[&str], i64); 4] = [
+    (&["day", "days", "d"], 60 * 60 * 24),
+    (&["hour", "hours", "hr", "hrs", "h"], 60 * 60),
+    (&["minute", "minutes", "min", "mins", "m"], 60),
+    (&["second", "seconds", "sec", "secs", "s"], 1),
+];
+
+/// The range of times that logs were received in that the log table is
+/// filtered to, either selected on the timeline or typed in.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TimeRange {
+    /// Between two times, either of which can be left open.
+    Between {
+        from: Option<DateTime<Local>>,
+        to: Option<DateTime<Local>>,
+    },
+    /// The last however long up until now, which moves along as time passes.
+    Last(Duration),
+}
+
+impl TimeRange {
+    /// Parses a range written as either a relative range, i.e. `last 5
+    /// minutes`, `last hour` or `30s`, or as two times separated by `..`,
+    /// i.e. `10:00..10:30` or `2023-03-01 09:00..`. The times are in the
+    /// same formats as the `after:` and `before:` filters of the query.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message describing why the range couldn't be parsed.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let input = input.trim();
+
+        if let Some((from, to)) = input.split_once("..") {
+            let parse_bound = |value: &str| {
+                let value = value.trim();
+
+                if value.is_empty() {
+                    Ok(None)
+                } else {
+                    parse_time(value)
+                        .map(Some)
+                        .ok_or_else(|| format!("'{value}' is not a valid time"))
+                }
+            };
+
+            let (from, to) = (parse_bound(from)?, parse_bound(to)?);
+
+            if let (Some(from), Some(to)) = (from, to) {
+                if from > to {
+                    return Err("The start of the range is after the end".into());
+                }
+            }
+
+            return Ok(Self::Between { from, to });
+        }
+
+        let lowercase = input.to_lowercase();
+        let relative = lowercase.strip_prefix("last").unwrap_or(&lowercase).trim();
+        let split = relative
+            .find(|character: char| !character.is_ascii_digit())
+            .unwrap_or(relative.len());
+        let (amount, unit) = relative.split_at(split);
+
+        let amount: i64 = if amount.is_empty() {
+            1
+        } else {
+            amount
+                .parse()
+                .map_err(|_| format!("'{amount}' is too large"))?
+        };
+
+        let seconds = UNITS
+            .iter()
+            .find(|(names, _)| names.contains(&unit.trim()))
+            .map(|(_, seconds)| *seconds)
+            .ok_or_else(|| {
+                format!(
+                    "'{input}' is not a range, try 'last 5 minutes' or '10:00..10:30'"
+                )
+            })?;
+
+        if amount == 0 {
+            return Err("The range can't be empty".into());
+        }
+
+        amount
+            .checked_mul(seconds)
+            .filter(|seconds| *seconds <= Duration::max_value().num_seconds())
+            .map(|seconds| Self::Last(Duration::seconds(seconds)))
+            .ok_or_else(|| format!("'{amount}' is too large"))
+    }
+
+    /// The range as two times, as of `now`.
+    #[must_use]
+    pub fn at(self, now: DateTime<Local>) -> Self {
+        match self {
+            Self::Last(duration) => Self::Between {
+                from: now.checked_sub_signed(duration),
+                to: None,
+            },
+            between => between,
+        }
+    }
+
+    /// Whether `time` is in the range as of `now`.
+    #[must_use]
+    pub fn contains(self, time: &DateTime<Local>, now: DateTime<Local>) -> bool {
+        match self {
+            Self::Between { from, to } =>
+                from.map_or(true, |from| *time >= from)
+                    && to.map_or(true, |to| *time <= to),
+            Self::Last(duration) => now
+                .checked_sub_signed(duration)
+                .map_or(true, |from| *time >= from),
+        }
+    }
+
+    #[must_use]
+    pub fn is_relative(self) -> bool { matches!(self, Self::Last(_)) }
+}
+
+impl Display for TimeRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Between { from, to } => {
+                if let Some(from) = from {
+                    write!(f, "{}", from.format(RANGE_TIME_FORMAT))?;
+                }
+
+                write!(f, "..")?;
+
+                if let Some(to) = to {
+                    write!(f, "{}", to.format(RANGE_TIME_FORMAT))?;
+                }
+
+                Ok(())
+            },
+            Self::Last(duration) => {
+                let seconds = duration.num_seconds();
+                let (names, unit) = UNITS
+                    .iter()
+                    .find(|(_, unit)| seconds % unit == 0)
+                    .unwrap_or(&UNITS[UNITS.len() - 1]);
+                let amount = seconds / unit;
+
+                if amount == 1 {
+                    write!(f, "last {}", names[0])
+                } else {
+                    write!(f, "last {amount} {}", names[1])
+                }
+            },
+        }
+    }
+}
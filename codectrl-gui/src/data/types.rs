@@ -1,9 +1,11 @@
 // region: imports
 
+use super::{Fields, Severity};
 use chrono::{
     format::{format_item, StrftimeItems},
-    DateTime, Local, NaiveDateTime,
+    DateTime, Local, NaiveDateTime, TimeZone,
 };
+use codectrl_common::{fields, proto::log_search::QueriedLog};
 use codectrl_protobuf_bindings::data::Log;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -14,12 +16,81 @@ use std::{
 
 // endregion
 
-pub type Received = Arc<RwLock<VecDeque<(Log, DateTime<Local>)>>>;
+pub type Received = Arc<RwLock<VecDeque<ReceivedLog>>>;
+
+/// A log that the server sent, along with when it was received and what the
+/// server worked out about it: how severe it is and its structured fields,
+/// including those from the `x-fields` header.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(from = "SavedLog")]
+pub struct ReceivedLog {
+    pub log: Log,
+    pub time: DateTime<Local>,
+    pub severity: Severity,
+    pub fields: Fields,
+}
+
+impl ReceivedLog {
+    /// Returns `None` if the server didn't send the log itself.
+    #[must_use]
+    pub fn from_queried(queried: QueriedLog) -> Option<Self> {
+        Some(Self {
+            time: Local
+                .timestamp_millis_opt(queried.received_at)
+                .single()
+                .unwrap_or_else(Local::now),
+            severity: queried.severity.parse().unwrap_or_default(),
+            fields: serde_json::from_str(&queried.fields).unwrap_or_default(),
+            log: queried.log?,
+        })
+    }
+}
+
+/// A [`ReceivedLog`] as it's saved in a session. Sessions from before the
+/// server sent anything but the log itself only have the log and the time it
+/// was received.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SavedLog {
+    Received {
+        log: Log,
+        time: DateTime<Local>,
+        severity: Severity,
+        fields: Fields,
+    },
+    Legacy(Log, DateTime<Local>),
+}
+
+impl From<SavedLog> for ReceivedLog {
+    fn from(saved: SavedLog) -> Self {
+        match saved {
+            SavedLog::Received {
+                log,
+                time,
+                severity,
+                fields,
+            } => Self {
+                log,
+                time,
+                severity,
+                fields,
+            },
+            SavedLog::Legacy(log, time) => Self {
+                severity: Severity::from_message_type(&log.message_type)
+                    .unwrap_or_default(),
+                fields: fields::from_message(&log.message).unwrap_or_default(),
+                log,
+                time,
+            },
+        }
+    }
+}
 
 #[derive(Default, Clone, Debug, Serialize, Deserialize)]
 pub struct TimeFormatString(String);
 
 impl TimeFormatString {
+    #[must_use]
     pub fn new(fmt: &str) -> Self { Self(fmt.to_owned()) }
 }
 
@@ -11,11 +11,12 @@
 mod app;
 mod components;
 mod consts;
-mod data;
+pub mod data;
 mod login;
 mod widgets;
 mod wrapper;
 
+use codectrl_common::proto::log_search::log_search_client::LogSearchClient as SearchClient;
 use codectrl_protobuf_bindings::logs_service::log_server_client::LogServerClient as Client;
 
 // region: native-only imports
@@ -23,7 +24,7 @@ use codectrl_protobuf_bindings::logs_service::log_server_client::LogServerClient
 #[cfg(not(target_arch = "wasm32"))]
 use clap::{crate_authors, crate_name, crate_version, Arg, Command};
 #[cfg(not(target_arch = "wasm32"))]
-use codectrl_server::run_server;
+use codectrl_server::{auth::BearerToken, config::ServerConfig, run_server};
 #[cfg(not(target_arch = "wasm32"))]
 use egui_toast::Toasts;
 #[cfg(not(target_arch = "wasm32"))]
@@ -33,11 +34,11 @@ use rfd::MessageDialog;
 #[cfg(not(target_arch = "wasm32"))]
 use std::cell::RefCell;
 #[cfg(not(target_arch = "wasm32"))]
-use std::{collections::HashMap, env, path::Path};
+use std::path::Path;
 #[cfg(not(target_arch = "wasm32"))]
 use tokio::runtime::Handle;
 #[cfg(not(target_arch = "wasm32"))]
-use tonic::transport::Channel;
+use tonic::{codegen::InterceptedService, transport::Channel};
 #[cfg(not(target_arch = "wasm32"))]
 use wrapper::Wrapper;
 
@@ -56,10 +57,30 @@ use eframe::web::AppRunnerRef;
 use grpc_web_client::Client as WasmClient;
 
 // endregion
+
+/// The clients of the `gRPC` services that the GUI uses, which share one
+/// channel.
+#[derive(Debug, Clone)]
+pub struct Clients<T> {
+    pub logs: Client<T>,
+    pub search: SearchClient<T>,
+}
+
 // region: wasm-only
 
 #[cfg(target_arch = "wasm32")]
-type GrpcClient = Client<WasmClient>;
+type GrpcClient = Clients<WasmClient>;
+
+#[cfg(target_arch = "wasm32")]
+impl GrpcClient {
+    #[must_use]
+    pub fn new(client: WasmClient) -> Self {
+        Self {
+            logs: Client::new(client.clone()),
+            search: SearchClient::new(client),
+        }
+    }
+}
 
 #[cfg(target_arch = "wasm32")]
 #[wasm_bindgen]
@@ -116,7 +137,18 @@ pub async fn run(
 pub static mut TOASTS: OnceCell<RefCell<Toasts>> = OnceCell::new();
 
 #[cfg(not(target_arch = "wasm32"))]
-type GrpcClient = Client<Channel>;
+type GrpcClient = Clients<InterceptedService<Channel, BearerToken>>;
+
+#[cfg(not(target_arch = "wasm32"))]
+impl GrpcClient {
+    #[must_use]
+    pub fn new(channel: Channel, bearer_token: BearerToken) -> Self {
+        Self {
+            logs: Client::with_interceptor(channel.clone(), bearer_token.clone()),
+            search: SearchClient::with_interceptor(channel, bearer_token),
+        }
+    }
+}
 
 #[cfg(not(target_arch = "wasm32"))]
 #[tokio::main]
@@ -134,8 +166,6 @@ pub async fn run() {
         )
     });
 
-    let command_line = env::vars().collect::<HashMap<String, String>>();
-
     let matches = Command::new(crate_name!())
         .version(crate_version!())
         .author(crate_authors!(", "))
@@ -145,8 +175,8 @@ pub async fn run() {
                 .short('p')
                 .long("port")
                 .help(
-                    "Specifies the port for the server to run on, can also be specified \
-                     by the PORT environment variable",
+                    "Specifies the port for the server to run on, overriding the config \
+                     file and the PORT environment variable.",
                 ),
         )
         .arg(
@@ -155,8 +185,20 @@ pub async fn run() {
                 .short('H')
                 .long("host")
                 .help(
-                    "Specifies the IP address for the server to run on. Can also be \
-                     specified with the HOST environment variable. Defaults to 0.0.0.0.",
+                    "Specifies the IP address for the server to run on, overriding the \
+                     config file and the HOST environment variable. Defaults to \
+                     127.0.0.1.",
+                ),
+        )
+        .arg(
+            Arg::new("config")
+                .takes_value(true)
+                .short('c')
+                .long("config")
+                .help(
+                    "The server config file to load. Can also be specified with the \
+                     CODECTRL_CONFIG environment variable. Defaults to config.toml in \
+                     the platform's config directory, if it exists.",
                 ),
         )
         .arg(
@@ -172,18 +214,6 @@ pub async fn run() {
         )
         .get_matches();
 
-    let has_port = matches.is_present("port");
-
-    let port = if has_port {
-        matches.value_of("port").unwrap()
-    } else if command_line.contains_key("PORT") {
-        command_line.get("PORT").unwrap()
-    } else {
-        "3002"
-    }
-    .parse::<u32>()
-    .expect("Port was not a valid value: needs to be an integer value.");
-
     let has_project = matches.is_present("PROJECT");
 
     let project_file = if has_project {
@@ -192,20 +222,34 @@ pub async fn run() {
         None
     };
 
-    let has_host = matches.is_present("host");
+    let mut config = match ServerConfig::load(matches.value_of("config").map(Path::new)) {
+        Ok(config) => config,
+        Err(error) => {
+            MessageDialog::new()
+                .set_title("Could not load the CodeCtrl server config")
+                .set_level(rfd::MessageLevel::Error)
+                .set_description(&error.to_string())
+                .set_buttons(rfd::MessageButtons::Ok)
+                .show();
 
-    let host = if has_host {
-        matches.value_of("host").unwrap().to_owned()
-    } else if command_line.contains_key("HOST") {
-        command_line.get("HOST").unwrap().clone()
-    } else {
-        "127.0.0.1".to_owned()
+            std::process::exit(1);
+        },
     };
 
+    if let Some(port) = matches.value_of("port") {
+        config.listen.port = port
+            .parse()
+            .expect("Port was not a valid value: needs to be an integer value.");
+    }
+
+    if let Some(host) = matches.value_of("host") {
+        config.listen.host = host.to_owned();
+    }
+
     let server_only = matches.is_present("server_only");
 
     let spawn = async move {
-        if let Err(error) = run_server(Some(host), Some(port), None, None).await {
+        if let Err(error) = run_server(config).await {
             if MessageDialog::new()
                 .set_title("Could not start CodeCtrl server")
                 .set_level(rfd::MessageLevel::Error)
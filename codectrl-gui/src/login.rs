@@ -2,30 +2,152 @@
 
 // region: imports
 
-use crate::{widgets::CopyableLabel, wrapper::WrapperMsg};
+use crate::{widgets::CopyableLabel, wrapper::WrapperMsg, GrpcClient};
 use authentura_egui_styling::{application_style, fonts, FontSizes};
-use codectrl_protobuf_bindings::{
-    auth_service::{authentication_client::AuthenticationClient, LoginUrl},
-    logs_service::{log_server_client::LogServerClient, Connection, ServerDetails},
+use codectrl_protobuf_bindings::logs_service::{
+    log_server_client::LogServerClient, Connection, ServerDetails,
+};
+use codectrl_server::{
+    auth::BearerToken,
+    proto::login_providers::{
+        login_providers_client::LoginProvidersClient, CollectTokenRequest, LoginRequest,
+        Provider, ProviderLoginUrl,
+    },
 };
 use eframe::{App, Frame};
 use egui::{
-    Button, CentralPanel, Color32, Context, Grid, Pos2, Response, TopBottomPanel, Ui,
-    Vec2, Window,
+    Button, CentralPanel, Color32, Context, Grid, Pos2, Response, TextEdit,
+    TopBottomPanel, Ui, Vec2, Window,
 };
-use once_cell::race::OnceBool;
 use poll_promise::Promise;
 use std::{
     cell::RefCell,
+    error::Error,
+    fs,
+    path::PathBuf,
     sync::Arc,
     time::{Duration, Instant},
 };
-use tokio::{runtime::Handle, task::JoinHandle};
-use tonic::transport::Channel;
+use tokio::{runtime::Handle, task::JoinHandle, time::sleep};
+use tonic::{
+    transport::{Certificate, Channel, ClientTlsConfig, Identity},
+    Response as GrpcResponse,
+};
+
+#[cfg(unix)]
+use tokio::net::UnixStream;
+#[cfg(unix)]
+use tonic::transport::Uri;
+#[cfg(unix)]
+use tower::service_fn;
 
 // endregion
 
-static GITHUB_BUTTON_HAS_BEEN_CLICKED: OnceBool = OnceBool::new();
+/// How long to wait between asking the server whether the user has logged in.
+const COLLECT_TOKEN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Asks the server for the token of the login with `state` until the user has
+/// finished logging in. This goes over the same connection as everything else,
+/// so that it works with remote servers and the token is sent over TLS if the
+/// connection uses it.
+async fn wait_for_token(
+    mut providers_client: LoginProvidersClient<Channel>,
+    state: String,
+) -> Result<String, String> {
+    loop {
+        let token = providers_client
+            .collect_token(CollectTokenRequest {
+                state: state.clone(),
+            })
+            .await
+            .map_err(|status| format!("Could not log in: {}", status.message()))?
+            .into_inner()
+            .token;
+
+        if !token.is_empty() {
+            return Ok(token);
+        }
+
+        sleep(COLLECT_TOKEN_INTERVAL).await;
+    }
+}
+
+/// The prefix of hosts that are a Unix socket rather than a network address,
+/// i.e. `unix:/run/codectrl/codectrl.sock`.
+const UNIX_SOCKET_PREFIX: &str = "unix:";
+
+/// How many times [`connect`] tries to connect before giving up, as the local
+/// server may still be starting. It waits [`CONNECT_RETRY_DELAY`] after the
+/// first failed attempt, and twice as long after each one after that.
+const CONNECT_ATTEMPTS: u32 = 5;
+const CONNECT_RETRY_DELAY: Duration = Duration::from_millis(250);
+
+/// Connects to the server at `host` and `port`, or to the Unix socket in
+/// `host` if it has the [`UNIX_SOCKET_PREFIX`]. Returns why it couldn't connect
+/// if none of the [`CONNECT_ATTEMPTS`] succeed.
+async fn connect(
+    host: &str,
+    port: &str,
+    tls_config: Option<ClientTlsConfig>,
+) -> Result<Channel, String> {
+    let scheme = if tls_config.is_some() {
+        "https"
+    } else {
+        "http"
+    };
+    let unix_socket = host.strip_prefix(UNIX_SOCKET_PREFIX).map(PathBuf::from);
+
+    // The address isn't used to connect to a Unix socket, but tonic still needs
+    // one for the requests and to check the server's certificate against.
+    let address = if unix_socket.is_some() {
+        format!("{scheme}://localhost")
+    } else {
+        format!("{scheme}://{host}:{port}")
+    };
+
+    let mut endpoint =
+        Channel::from_shared(address).map_err(|error| error.to_string())?;
+
+    if let Some(tls_config) = tls_config {
+        endpoint = endpoint
+            .tls_config(tls_config)
+            .map_err(|error| error.to_string())?;
+    }
+
+    let mut delay = CONNECT_RETRY_DELAY;
+    let mut attempt = 1;
+
+    loop {
+        let channel = match &unix_socket {
+            #[cfg(unix)]
+            Some(path) => {
+                let path = path.clone();
+
+                endpoint
+                    .connect_with_connector(service_fn(move |_: Uri| {
+                        UnixStream::connect(path.clone())
+                    }))
+                    .await
+            },
+            _ => endpoint.connect().await,
+        };
+
+        match channel {
+            Ok(channel) => return Ok(channel),
+            // tonic's transport errors only say what went wrong in their source.
+            Err(error) if attempt == CONNECT_ATTEMPTS =>
+                return Err(match error.source() {
+                    Some(source) => format!("{error}: {source}"),
+                    None => error.to_string(),
+                }),
+            Err(_) => {
+                sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            },
+        }
+    }
+}
 
 #[derive(Default)]
 pub struct Login {
@@ -34,14 +156,21 @@ pub struct Login {
     host: String,
     port: String,
     is_local: bool,
+    is_using_tls: bool,
+    /// Paths to PEM files, the system's root certificates are trusted if the
+    /// CA certificate is empty and no client certificate is sent if the
+    /// client certificate and key are empty.
+    ca_certificate: String,
+    client_certificate: String,
+    client_key: String,
+    tls_error: Option<String>,
     handle: Option<Arc<Handle>>,
-    connection_promise: Option<(
-        Promise<(LogServerClient<Channel>, AuthenticationClient<Channel>)>,
-        JoinHandle<()>,
-    )>,
+    connection_promise: Option<(Promise<Result<Channel, String>>, JoinHandle<()>)>,
     server_details_promise: Option<Promise<ServerDetails>>,
-    registration_promise: Option<Promise<Connection>>,
-    github_login_url_promise: Option<Promise<LoginUrl>>,
+    registration_promise: Option<Promise<Result<(GrpcClient, Connection), String>>>,
+    providers_promise: Option<Promise<Vec<Provider>>>,
+    login_url_promise: Option<Promise<Result<ProviderLoginUrl, String>>>,
+    oauth_token_promise: Option<Promise<Result<String, String>>>,
     connection_promise_initialised: Option<Instant>,
     reset_connection: bool,
 }
@@ -61,25 +190,77 @@ impl Login {
             host: String::from("127.0.0.1"),
             port: String::from("3002"),
             is_local: true,
+            is_using_tls: false,
+            ca_certificate: String::new(),
+            client_certificate: String::new(),
+            client_key: String::new(),
+            tls_error: None,
             handle: Some(handle),
             connection_promise: None,
             server_details_promise: None,
             registration_promise: None,
             connection_promise_initialised: None,
-            github_login_url_promise: None,
+            providers_promise: None,
+            login_url_promise: None,
+            oauth_token_promise: None,
             reset_connection: false,
         }
     }
 
-    fn register(&mut self, mut channel: LogServerClient<Channel>) {
+    /// Reads the PEM files that were given into the TLS config of the
+    /// connection, or returns `None` if TLS isn't used.
+    fn client_tls_config(&self) -> Result<Option<ClientTlsConfig>, String> {
+        if !self.is_using_tls {
+            return Ok(None);
+        }
+
+        let read = |path: &str, what: &str| {
+            fs::read(path).map_err(|error| format!("Could not read the {what}: {error}"))
+        };
+
+        let mut tls_config = ClientTlsConfig::new();
+
+        if !self.ca_certificate.is_empty() {
+            tls_config = tls_config.ca_certificate(Certificate::from_pem(read(
+                &self.ca_certificate,
+                "CA certificate",
+            )?));
+        }
+
+        match (
+            self.client_certificate.is_empty(),
+            self.client_key.is_empty(),
+        ) {
+            (true, true) => (),
+            (false, false) =>
+                tls_config = tls_config.identity(Identity::from_pem(
+                    read(&self.client_certificate, "client certificate")?,
+                    read(&self.client_key, "client key")?,
+                )),
+            _ =>
+                return Err(
+                    "A client certificate needs both a certificate and a key".into()
+                ),
+        }
+
+        Ok(Some(tls_config))
+    }
+
+    fn register(&mut self, channel: Channel) {
+        let bearer_token = BearerToken::new(&self.token);
+
         self.registration_promise.get_or_insert_with(|| {
             let (sender, promise) = Promise::new();
 
             if let Some(handle) = self.handle.as_deref() {
                 handle.spawn(async move {
-                    if let Ok(registered_client) = channel.register_client(()).await {
-                        sender.send(registered_client.into_inner());
-                    }
+                    let mut grpc_client = GrpcClient::new(channel, bearer_token);
+
+                    sender.send(match grpc_client.logs.register_client(()).await {
+                        Ok(registered_client) =>
+                            Ok((grpc_client, registered_client.into_inner())),
+                        Err(status) => Err(status.message().to_string()),
+                    });
                 });
             }
 
@@ -91,13 +272,9 @@ impl Login {
         &mut self,
         ctx: &Context,
         frame: &mut Frame,
-        (channel, auth_channel): &(
-            LogServerClient<Channel>,
-            AuthenticationClient<Channel>,
-        ),
+        channel: &Channel,
     ) -> Response {
         let window_size = frame.info().window_info.size;
-        let mut auth_channel = auth_channel.clone();
 
         Window::new("token_input")
             .title_bar(false)
@@ -107,56 +284,143 @@ impl Login {
                 (window_size.y / 2.0) - 100.0,
             ))
             .show(ctx, |ui| {
-                ui.heading("Please login with your GitHub");
+                ui.heading("Please log in");
+
+                let providers_promise = self.providers_promise.get_or_insert_with(|| {
+                    let (sender, promise) = Promise::new();
+                    let mut providers_client = LoginProvidersClient::new(channel.clone());
+
+                    if let Some(handle) = self.handle.as_deref() {
+                        handle.spawn(async move {
+                            sender.send(
+                                providers_client
+                                    .list_providers(())
+                                    .await
+                                    .map(|providers| providers.into_inner().providers)
+                                    .unwrap_or_default(),
+                            );
+                        });
+                    }
 
-                if ui.button("GitHub Login").clicked()
-                    || matches!(GITHUB_BUTTON_HAS_BEEN_CLICKED.get(), Some(true))
-                {
-                    GITHUB_BUTTON_HAS_BEEN_CLICKED.get_or_init(|| true);
+                    promise
+                });
 
-                    if self.github_login_url_promise.is_none() {
-                        let (sender, promise) = Promise::new();
+                let mut selected_provider = None;
 
-                        if let Some(handle) = self.handle.as_deref() {
-                            handle.spawn(async move {
-                                if let Ok(result) = auth_channel.github_login(()).await {
-                                    sender.send(result.into_inner());
-                                }
-                            });
-                        }
+                match providers_promise.ready() {
+                    Some(providers) if providers.is_empty() => {
+                        ui.label(
+                            "No login providers are configured, enter a token instead.",
+                        );
+                    },
+                    Some(providers) =>
+                        for provider in providers {
+                            if ui
+                                .button(format!("Log in with {}", provider.name))
+                                .clicked()
+                            {
+                                selected_provider = Some(provider.name.clone());
+                            }
+                        },
+                    None => {
+                        ui.spinner();
+                    },
+                }
 
-                        self.github_login_url_promise = Some(promise);
+                if let Some(provider) = selected_provider {
+                    let (sender, promise) = Promise::new();
+                    let mut providers_client = LoginProvidersClient::new(channel.clone());
+
+                    if let Some(handle) = self.handle.as_deref() {
+                        handle.spawn(async move {
+                            sender.send(
+                                providers_client
+                                    .login(LoginRequest { provider })
+                                    .await
+                                    .map(GrpcResponse::into_inner)
+                                    .map_err(|status| status.message().to_string()),
+                            );
+                        });
                     }
 
-                    if let Some(login_url_promise) = &self.github_login_url_promise {
-                        match login_url_promise.ready() {
-                            Some(login_url) => {
+                    self.login_url_promise = Some(promise);
+                    self.oauth_token_promise = None;
+                }
+
+                if let Some(login_url_promise) = &self.login_url_promise {
+                    match login_url_promise.ready() {
+                        // only open the browser once, then wait for the server to
+                        // hand us the token
+                        Some(Ok(login_url)) =>
+                            if self.oauth_token_promise.is_none() {
                                 let _res = open::that(&login_url.url);
-                                ui.spinner()
+
+                                let (sender, promise) = Promise::new();
+                                let providers_client =
+                                    LoginProvidersClient::new(channel.clone());
+                                let state = login_url.state.clone();
+
+                                if let Some(handle) = self.handle.as_deref() {
+                                    handle.spawn(async move {
+                                        sender.send(
+                                            wait_for_token(providers_client, state).await,
+                                        );
+                                    });
+                                }
+
+                                self.oauth_token_promise = Some(promise);
                             },
-                            None => ui.spinner(),
-                        };
+                        Some(Err(error)) => {
+                            ui.colored_label(Color32::LIGHT_RED, error);
+                        },
+                        None => {
+                            ui.spinner();
+                        },
                     }
+                }
+
+                let oauth_token = self
+                    .oauth_token_promise
+                    .as_ref()
+                    .map(|promise| promise.ready().cloned());
 
-                    // open::that(path);
+                match oauth_token {
+                    Some(Some(Ok(token))) =>
+                        if self.token.is_empty() {
+                            self.token = token;
+                            self.register(channel.clone());
+                        },
+                    Some(Some(Err(error))) => {
+                        ui.colored_label(Color32::LIGHT_RED, error);
+                    },
+                    Some(None) => {
+                        ui.spinner();
+                    },
+                    None => (),
                 }
 
                 ui.add_space(5.0);
 
+                ui.horizontal(|ui| {
+                    ui.label("Token");
+                    ui.add(TextEdit::singleline(&mut self.token).password(true));
+                });
+
                 if ui
                     .add_enabled(!self.token.is_empty(), Button::new("Login"))
                     .clicked()
                 {
+                    self.registration_promise = None;
                     self.register(channel.clone());
                 }
 
                 if let Some(registration_promise) = &self.registration_promise {
                     match registration_promise.ready() {
-                        Some(registered_client) => {
+                        Some(Ok((grpc_client, registered_client))) => {
                             if let Ok(mut wrapper_msg) = self.wrapper_msg.try_borrow_mut()
                             {
                                 *wrapper_msg = WrapperMsg::Main {
-                                    grpc_client: channel.clone(),
+                                    grpc_client: grpc_client.clone(),
                                     grpc_client_connection: registered_client.clone(),
                                 };
                             }
@@ -166,6 +430,10 @@ impl Login {
                                 registered_client.uuid
                             )))
                         },
+                        Some(Err(error)) => ui.colored_label(
+                            Color32::LIGHT_RED,
+                            format!("Could not log in: {error}"),
+                        ),
                         None => ui.spinner(),
                     }
                 } else {
@@ -191,6 +459,12 @@ fn responsive_row(ctx: &Context, ui: &mut Ui, text: &str, data: &mut String) {
     ui.end_row();
 }
 
+fn optional_row(ui: &mut Ui, text: &str, data: &mut String) {
+    ui.label(text);
+    ui.add(TextEdit::singleline(data).hint_text("Optional"));
+    ui.end_row();
+}
+
 impl App for Login {
     fn update(&mut self, ctx: &Context, frame: &mut Frame) {
         TopBottomPanel::top("top_bar")
@@ -220,7 +494,23 @@ impl App for Login {
                         responsive_row(ctx, ui, "Host", &mut self.host);
                     }
 
-                    responsive_row(ctx, ui, "Port", &mut self.port);
+                    // Unix sockets don't have a port.
+                    if self.is_local || !self.host.starts_with(UNIX_SOCKET_PREFIX) {
+                        responsive_row(ctx, ui, "Port", &mut self.port);
+                    }
+
+                    ui.checkbox(&mut self.is_using_tls, "Use TLS?");
+                    ui.end_row();
+
+                    if self.is_using_tls {
+                        optional_row(ui, "CA certificate", &mut self.ca_certificate);
+                        optional_row(
+                            ui,
+                            "Client certificate",
+                            &mut self.client_certificate,
+                        );
+                        optional_row(ui, "Client key", &mut self.client_key);
+                    }
                 });
 
             ui.add_space(5.0);
@@ -229,7 +519,10 @@ impl App for Login {
                 .button(if self.is_local { "Start" } else { "Login" })
                 .clicked()
             {
-                {
+                let tls_config = self.client_tls_config();
+                self.tls_error = tls_config.as_ref().err().cloned();
+
+                if let Ok(tls_config) = tls_config {
                     let fun = || {
                         let (sender, promise) = Promise::new();
 
@@ -239,29 +532,7 @@ impl App for Login {
                         let promise_handle = if let Some(handle) = self.handle.as_deref()
                         {
                             handle.spawn(async move {
-                                let grpc_client = loop {
-                                    let res = LogServerClient::connect(format!(
-                                        "http://{host}:{port}"
-                                    ))
-                                    .await;
-
-                                    if let Ok(res) = res {
-                                        break res;
-                                    }
-                                };
-
-                                let auth_client = loop {
-                                    let res = AuthenticationClient::connect(format!(
-                                        "http://{host}:{port}"
-                                    ))
-                                    .await;
-
-                                    if let Ok(res) = res {
-                                        break res;
-                                    }
-                                };
-
-                                sender.send((grpc_client, auth_client));
+                                sender.send(connect(&host, &port, tls_config).await);
                             })
                         } else {
                             panic!("No tokio runtime!")
@@ -281,6 +552,10 @@ impl App for Login {
                 }
             }
 
+            if let Some(tls_error) = &self.tls_error {
+                ui.colored_label(Color32::LIGHT_RED, tls_error);
+            }
+
             if let Some(connection_promise) = &mut self.connection_promise {
                 match connection_promise.0.ready_mut() {
                     None => {
@@ -302,15 +577,22 @@ impl App for Login {
                             ui.spinner()
                         }
                     },
-                    Some(channel) => {
-                        let mut channel_clone = channel.clone();
+                    Some(Err(error)) => {
+                        self.reset_connection = true;
+                        ui.colored_label(
+                            Color32::RED,
+                            format!("Could not connect to gRPC server: {error}"),
+                        )
+                    },
+                    Some(Ok(channel)) => {
+                        let mut grpc_client = LogServerClient::new(channel.clone());
                         self.server_details_promise.get_or_insert_with(|| {
                             let (sender, promise) = Promise::new();
 
                             if let Some(handle) = self.handle.as_deref() {
                                 handle.spawn(async move {
                                     if let Ok(server_details) =
-                                        channel_clone.0.get_server_details(()).await
+                                        grpc_client.get_server_details(()).await
                                     {
                                         sender.send(server_details.into_inner());
                                     }
@@ -328,7 +610,7 @@ impl App for Login {
                                     if server_details.requires_authentication {
                                         self.draw_token_window(ctx, frame, &channel_clone)
                                     } else {
-                                        self.register(channel_clone.clone().0);
+                                        self.register(channel_clone.clone());
                                         ui.label("")
                                     },
                                 None => ui.colored_label(
@@ -342,12 +624,12 @@ impl App for Login {
 
                         if let Some(registration_promise) = &self.registration_promise {
                             match registration_promise.ready() {
-                                Some(registered_client) => {
+                                Some(Ok((grpc_client, registered_client))) => {
                                     if let Ok(mut wrapper_msg) =
                                         self.wrapper_msg.try_borrow_mut()
                                     {
                                         *wrapper_msg = WrapperMsg::Main {
-                                            grpc_client: channel_clone.0,
+                                            grpc_client: grpc_client.clone(),
                                             grpc_client_connection: registered_client
                                                 .clone(),
                                         };
@@ -358,6 +640,10 @@ impl App for Login {
                                         registered_client.uuid
                                     )))
                                 },
+                                Some(Err(error)) => ui.colored_label(
+                                    Color32::LIGHT_RED,
+                                    format!("Could not register: {error}"),
+                                ),
                                 None => ui.spinner(),
                             }
                         } else {
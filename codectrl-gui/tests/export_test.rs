@@ -0,0 +1,101 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+use chrono::{DateTime, Local, TimeZone};
+use codectrl_gui::data::{ExportFormat, Fields, ReceivedLog, Severity};
+use codectrl_protobuf_bindings::data::Log;
+use serde_json::{json, Value};
+
+fn time() -> DateTime<Local> { Local.timestamp_opt(1_000_000, 0).unwrap() }
+
+fn received(message: &str) -> ReceivedLog {
+    let fields = match json!({ "user": "alice" }) {
+        Value::Object(fields) => fields,
+        _ => Fields::new(),
+    };
+
+    ReceivedLog {
+        log: Log {
+            uuid: "0b5f2d4e".into(),
+            message: message.into(),
+            message_type: "String".into(),
+            file_name: "src/<main>.rs".into(),
+            line_number: 42,
+            address: "10.0.0.5:52000".into(),
+            language: "Rust".into(),
+            warnings: vec!["Stacktrace is empty".into(), "<no code>".into()],
+            ..Log::default()
+        },
+        time: time(),
+        severity: Severity::Warn,
+        fields,
+    }
+}
+
+#[test]
+fn test_json_lines() {
+    let export =
+        ExportFormat::JsonLines.export(&[received("first"), received("second\nline")]);
+    let lines = export.lines().collect::<Vec<_>>();
+
+    assert_eq!(lines.len(), 2);
+
+    let second: Value = serde_json::from_str(lines[1]).unwrap();
+    assert_eq!(second["message"], "second\nline");
+    assert_eq!(second["severity"], "warn");
+    assert_eq!(second["fields"]["user"], "alice");
+}
+
+#[test]
+fn test_csv_quoting() {
+    let export = ExportFormat::Csv.export(&[
+        received("plain"),
+        received("He said \"hi\", then left\nfor good"),
+    ]);
+    let mut records = export.split("\r\n");
+
+    assert_eq!(
+        records.next(),
+        Some(
+            "received_at,severity,message,message_type,file_name,line_number,address,\
+             language,uuid,warnings,fields"
+        )
+    );
+
+    let tail = "String,src/<main>.rs,42,10.0.0.5:52000,Rust,0b5f2d4e,Stacktrace is \
+                empty; <no code>,\"{\"\"user\"\":\"\"alice\"\"}\"";
+
+    assert_eq!(
+        records.next(),
+        Some(format!("{},warn,plain,{tail}", time().to_rfc3339()).as_str())
+    );
+    // Quotes are doubled, and fields with quotes, commas or newlines are
+    // quoted.
+    assert_eq!(
+        records.next(),
+        Some(
+            format!(
+                "{},warn,\"He said \"\"hi\"\", then left\nfor good\",{tail}",
+                time().to_rfc3339()
+            )
+            .as_str()
+        )
+    );
+    assert_eq!(records.next(), Some(""));
+    assert_eq!(records.next(), None);
+}
+
+#[test]
+fn test_html_escaping() {
+    let export =
+        ExportFormat::Html.export(&[received("<script>alert(\"x\") & more</script>")]);
+
+    assert!(export.starts_with("<!DOCTYPE html>"));
+    assert!(export.contains(
+        "<pre>&lt;script&gt;alert(&quot;x&quot;) &amp; more&lt;/script&gt;</pre>"
+    ));
+    assert!(export.contains("<h2>src/&lt;main&gt;.rs:42</h2>"));
+    assert!(export.contains("<li>&lt;no code&gt;</li>"));
+    assert!(export.contains("&quot;user&quot;: &quot;alice&quot;"));
+    assert!(!export.contains("<script>"));
+    assert!(!export.contains("<main>"));
+}
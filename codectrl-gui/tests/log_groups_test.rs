@@ -0,0 +1,129 @@
+use chrono::{DateTime, Duration, Local, TimeZone};
+use codectrl_gui::data::{
+    message_template, sparkline_buckets, Fields, GroupKey, LogGroups, ReceivedLog,
+    Severity, SPARKLINE_BUCKETS,
+};
+use codectrl_protobuf_bindings::data::Log;
+use std::collections::VecDeque;
+
+fn start() -> DateTime<Local> { Local.timestamp_opt(1_000_000, 0).unwrap() }
+
+fn received_log(
+    uuid: &str,
+    message: &str,
+    line_number: u32,
+    seconds: i64,
+) -> ReceivedLog {
+    ReceivedLog {
+        log: Log {
+            uuid: uuid.into(),
+            message: message.into(),
+            file_name: "src/main.rs".into(),
+            line_number,
+            ..Log::default()
+        },
+        time: start() + Duration::seconds(seconds),
+        severity: Severity::Info,
+        fields: Fields::new(),
+    }
+}
+
+#[test]
+fn test_message_template() {
+    assert_eq!(message_template("Iteration 12 of 300"), "Iteration # of #");
+    assert_eq!(message_template("v1.20.3"), "v#.#.#");
+    assert_eq!(message_template("No numbers"), "No numbers");
+    assert_eq!(message_template("\"Took 5ms\""), "Took #ms");
+    assert_eq!(message_template("Über 9000"), "Über #");
+}
+
+#[test]
+fn test_groups_are_updated_incrementally() {
+    let mut groups = LogGroups::default();
+    // New logs are pushed to the front, like the GUI does.
+    let mut received = VecDeque::new();
+
+    received.push_front(received_log("1", "Iteration 1", 10, 0));
+    received.push_front(received_log("2", "Iteration 2", 10, 1));
+    received.push_front(received_log("3", "Done", 20, 2));
+    groups.update(&received);
+
+    assert_eq!(groups.groups.len(), 2);
+    assert_eq!(groups.groups[0].key, GroupKey::of(&received[2].log));
+    assert_eq!(groups.groups[0].key.template, "Iteration #");
+    assert_eq!(
+        groups.groups[0]
+            .entries
+            .iter()
+            .map(|(sequence, _)| *sequence)
+            .collect::<Vec<_>>(),
+        [0, 1]
+    );
+    assert_eq!(groups.groups[1].entries[0].0, 2);
+
+    received.push_front(received_log("4", "Iteration 3", 10, 3));
+    groups.update(&received);
+
+    assert_eq!(groups.groups.len(), 2);
+    assert_eq!(groups.groups[0].entries.len(), 3);
+
+    let (sequence, time) = groups.groups[0].entries[2];
+    let entry = LogGroups::entry(&received, sequence).unwrap();
+    assert_eq!(entry.log.uuid, "4");
+    assert_eq!(entry.time, time);
+    assert!(LogGroups::entry(&received, 4).is_none());
+
+    // The same message from another line is another group.
+    received.push_front(received_log("5", "Iteration 4", 11, 4));
+    groups.update(&received);
+    assert_eq!(groups.groups.len(), 3);
+}
+
+#[test]
+fn test_groups_are_rebuilt_when_replaced() {
+    let mut groups = LogGroups::default();
+    let mut received = VecDeque::new();
+
+    received.push_front(received_log("1", "Iteration 1", 10, 0));
+    received.push_front(received_log("2", "Done", 20, 1));
+    groups.update(&received);
+    assert_eq!(groups.groups.len(), 2);
+
+    received.clear();
+    groups.update(&received);
+    assert!(groups.groups.is_empty());
+
+    // A loaded session with as many logs, but a different oldest log.
+    received.push_front(received_log("3", "Loaded 1", 30, 0));
+    received.push_front(received_log("4", "Loaded 2", 30, 1));
+    groups.update(&received);
+
+    assert_eq!(groups.groups.len(), 1);
+    assert_eq!(groups.groups[0].key.template, "Loaded #");
+    assert_eq!(groups.groups[0].entries.len(), 2);
+}
+
+#[test]
+fn test_sparkline_buckets() {
+    assert_eq!(sparkline_buckets(&[]), [0; SPARKLINE_BUCKETS]);
+
+    // A single log, or logs received at the same time, all go in the first
+    // bucket.
+    let mut buckets = [0; SPARKLINE_BUCKETS];
+    buckets[0] = 3;
+    assert_eq!(sparkline_buckets(&[start(); 3]), buckets);
+
+    // The first log goes in the first bucket and the last in the last one.
+    let span = i64::try_from(SPARKLINE_BUCKETS - 1).unwrap();
+    let times = [
+        start(),
+        start() + Duration::seconds(1),
+        start() + Duration::seconds(span),
+    ];
+    let buckets = sparkline_buckets(&times);
+
+    assert_eq!(buckets[0], 1);
+    assert_eq!(buckets[1], 1);
+    assert_eq!(buckets[SPARKLINE_BUCKETS - 1], 1);
+    assert_eq!(buckets.iter().sum::<usize>(), times.len());
+}
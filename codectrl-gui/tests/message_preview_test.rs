@@ -0,0 +1,102 @@
+use codectrl_gui::data::{FoldNode, MessageFormat};
+
+/// Writes out a folding tree as i.e. `0(1,2),3`, where the lines nested in a
+/// line are in brackets after it.
+fn shape(nodes: &[FoldNode]) -> String {
+    nodes
+        .iter()
+        .map(|node| {
+            if node.children.is_empty() {
+                node.line.to_string()
+            } else {
+                format!("{}({})", node.line, shape(&node.children))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[test]
+fn test_detect_from_type() {
+    assert_eq!(
+        MessageFormat::detect("anything", "serde_json::Value"),
+        Some(MessageFormat::Json)
+    );
+    assert_eq!(
+        MessageFormat::detect("anything", "serde_yaml::Value"),
+        Some(MessageFormat::Yaml)
+    );
+    assert_eq!(
+        MessageFormat::detect("anything", "XmlDocument"),
+        Some(MessageFormat::Xml)
+    );
+    assert_eq!(
+        MessageFormat::detect("anything", "toml::Value"),
+        Some(MessageFormat::Toml)
+    );
+}
+
+#[test]
+fn test_detect_from_message() {
+    let detect = |message| MessageFormat::detect(message, "String");
+
+    assert_eq!(detect(r#"{"a": 1}"#), Some(MessageFormat::Json));
+    assert_eq!(detect("[1, 2]"), Some(MessageFormat::Json));
+    // Messages formatted with `Debug` are quoted.
+    assert_eq!(detect(r#""{\"a\": 1}""#), Some(MessageFormat::Json));
+    assert_eq!(detect("<a><b>1</b></a>"), Some(MessageFormat::Xml));
+    assert_eq!(detect("[server]\nport = 80\n"), Some(MessageFormat::Toml));
+    assert_eq!(
+        detect("name: test\nitems:\n  - 1\n"),
+        Some(MessageFormat::Yaml)
+    );
+
+    // Plain text is valid YAML, so it's only sniffed from multiple lines.
+    assert_eq!(detect("key: value"), None);
+    assert_eq!(detect("Hello\nworld"), None);
+    assert_eq!(detect("{ not json"), None);
+    assert_eq!(detect("Upstream timed out"), None);
+}
+
+#[test]
+fn test_pretty() {
+    assert_eq!(
+        MessageFormat::Json.pretty(r#"{"a":1,"b":[1,2]}"#).unwrap(),
+        "{\n  \"a\": 1,\n  \"b\": [\n    1,\n    2\n  ]\n}"
+    );
+    assert_eq!(
+        MessageFormat::Json.pretty(r#""{\"a\":1}""#).unwrap(),
+        "{\n  \"a\": 1\n}"
+    );
+    assert_eq!(
+        MessageFormat::Xml
+            .pretty("<a><b>1</b></a>")
+            .unwrap()
+            .lines()
+            .collect::<Vec<_>>(),
+        ["<a>", "  <b>1</b>", "</a>"]
+    );
+
+    assert_eq!(MessageFormat::Json.pretty("{"), None);
+    assert_eq!(MessageFormat::Toml.pretty("= 1"), None);
+}
+
+#[test]
+fn test_fold_tree() {
+    let json = MessageFormat::Json.pretty(r#"{"a":1,"b":[1,2]}"#).unwrap();
+    let lines = json.lines().collect::<Vec<_>>();
+
+    assert_eq!(
+        shape(&FoldNode::tree(MessageFormat::Json, &lines)),
+        "0(1,2(3,4),5),6"
+    );
+
+    // Tables aren't indented in TOML, the lines under a header are nested in
+    // it. Blank lines aren't part of the tree.
+    let toml = ["[server]", "port = 80", "", "[client]", "name = \"x\""];
+
+    assert_eq!(
+        shape(&FoldNode::tree(MessageFormat::Toml, &toml)),
+        "0(1),3(4)"
+    );
+}
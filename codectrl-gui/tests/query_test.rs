@@ -0,0 +1,207 @@
+use chrono::{Duration, Local};
+use codectrl_gui::data::{Fields, Query, QueryError, ReceivedLog, Severity};
+use codectrl_protobuf_bindings::data::Log;
+use serde_json::{json, Value};
+
+fn received() -> ReceivedLog {
+    let fields = match json!({ "user": { "id": 42 }, "env": "prod" }) {
+        Value::Object(fields) => fields,
+        _ => Fields::new(),
+    };
+
+    ReceivedLog {
+        log: Log {
+            message: "Upstream timed out".into(),
+            message_type: "String".into(),
+            file_name: "src/main.rs".into(),
+            line_number: 42,
+            address: "10.0.0.5:52000".into(),
+            language: "Rust".into(),
+            warnings: vec!["Stacktrace is empty".into()],
+            ..Log::default()
+        },
+        time: Local::now() - Duration::minutes(5),
+        severity: Severity::Warn,
+        fields,
+    }
+}
+
+fn matches(query: &str) -> bool {
+    Query::parse(query, false, false)
+        .unwrap_or_else(|error| panic!("{query}: {error}"))
+        .matches(&received())
+}
+
+fn error(query: &str) -> QueryError {
+    Query::parse(query, false, false)
+        .err()
+        .unwrap_or_else(|| panic!("{query} should not parse"))
+}
+
+#[test]
+fn test_empty_query() {
+    assert!(Query::parse("", false, false).unwrap().terms.is_empty());
+    assert!(matches(""));
+    assert!(matches("   "));
+}
+
+#[test]
+fn test_text_terms() {
+    assert!(matches("timed"));
+    assert!(matches("msg:upstream"));
+    assert!(matches("message:timed"));
+    assert!(!matches("timeout"));
+    assert!(matches("file:main.rs"));
+    assert!(!matches("file:lib.rs"));
+    assert!(matches("host:10.0.*"));
+    assert!(!matches("host:10.1.*"));
+    assert!(matches("type:string"));
+    assert!(matches("lang:rust"));
+    assert!(matches("FILE:main.rs"));
+}
+
+#[test]
+fn test_every_term_has_to_match() {
+    assert!(matches("timed file:main.rs line:42"));
+    assert!(!matches("timed file:lib.rs"));
+}
+
+#[test]
+fn test_negation() {
+    assert!(matches("-file:lib.rs"));
+    assert!(!matches("-file:main.rs"));
+    assert!(!matches("-warnings:1"));
+    assert!(matches("-warnings:0"));
+    // A lone `-` searches the message for it.
+    assert!(!matches("-"));
+}
+
+#[test]
+fn test_quoting() {
+    assert!(matches("\"timed out\""));
+    assert!(!matches("\"out timed\""));
+    assert!(matches("msg:\"Upstream timed\""));
+    assert!(matches("\"timed out\" file:main.rs"));
+    // Quotes make names that aren't fields searchable.
+    assert!(!matches("\"foo:bar\""));
+}
+
+#[test]
+fn test_number_operators() {
+    assert!(matches("line:42"));
+    assert!(matches("line:=42"));
+    assert!(!matches("line:41"));
+    assert!(matches("line:>40"));
+    assert!(!matches("line:>42"));
+    assert!(matches("line:>=42"));
+    assert!(matches("line:<43"));
+    assert!(!matches("line:<42"));
+    assert!(matches("line:<=42"));
+    assert!(matches("warnings:1"));
+    assert!(matches("warnings:>0"));
+}
+
+#[test]
+fn test_severity_operators() {
+    assert!(matches("severity:warn"));
+    assert!(matches("severity:>=warn"));
+    assert!(matches("severity:>info"));
+    assert!(matches("severity:<error"));
+    assert!(!matches("severity:error"));
+    assert!(!matches("severity:>=error"));
+    assert!(!matches("severity:<=info"));
+}
+
+#[test]
+fn test_times() {
+    assert!(matches("after:2000-01-01"));
+    assert!(!matches("before:2000-01-01"));
+    assert!(matches("after:2000-01-01T12:00"));
+    assert!(matches("after:\"2000-01-01 12:00:30\""));
+
+    let now = Local::now().format("%FT%H:%M:%S").to_string();
+    assert!(matches(&format!("before:{now}")));
+    assert!(!matches(&format!("after:{now}")));
+}
+
+#[test]
+fn test_regexes() {
+    assert!(matches("msg~/time(d )?out/"));
+    assert!(matches("msg~time.*out"));
+    assert!(!matches("msg~time.out"));
+    assert!(matches("msg:/^upstream/"));
+    assert!(!matches("msg:/^timed/"));
+    assert!(matches("file:/\\.rs$/"));
+    assert!(matches("-file:/\\.py$/"));
+    // Without a regex, `.` is matched literally and only `*` is a wildcard.
+    assert!(!matches("msg:time.out"));
+    assert!(matches("msg:time*out"));
+}
+
+#[test]
+fn test_modes() {
+    let received = received();
+    let parse = |query, is_case_sensitive, is_using_regex| {
+        Query::parse(query, is_case_sensitive, is_using_regex).unwrap()
+    };
+
+    assert!(parse("upstream", false, false).matches(&received));
+    assert!(!parse("upstream", true, false).matches(&received));
+    assert!(parse("Upstream", true, false).matches(&received));
+    assert!(!parse("msg~/upstream/", true, false).matches(&received));
+
+    assert!(parse("time.*out", false, true).matches(&received));
+    assert!(!parse("time.*out", false, false).matches(&received));
+    assert!(parse("file:main\\.rs$", false, true).matches(&received));
+}
+
+#[test]
+fn test_field_filters() {
+    assert!(matches("field:user.id=42"));
+    assert!(!matches("field:user.id=43"));
+    assert!(matches("field:env=prod"));
+    assert!(matches("field:user.id"));
+    assert!(matches("field:user"));
+    assert!(!matches("field:user.name"));
+    assert!(matches("-field:missing"));
+    assert!(!matches("-field:env=prod"));
+}
+
+#[test]
+fn test_syntax_error_positions() {
+    let unclosed_quote = error("file:main.rs \"timed out");
+    assert_eq!(unclosed_quote.message, "Missing a closing `\"`");
+    assert_eq!(unclosed_quote.span, 13..23);
+
+    let unclosed_regex = error("msg~/time");
+    assert_eq!(unclosed_regex.message, "Missing a closing `/`");
+    assert_eq!(unclosed_regex.span, 0..9);
+
+    assert!(
+        error("timed foo:bar")
+            .message
+            .starts_with("Unknown field `foo`")
+    );
+    assert_eq!(error("timed foo:bar").span, 6..13);
+
+    assert_eq!(error("file:").message, "Expected a value after `file:`");
+    assert_eq!(error("file:").span, 0..5);
+
+    assert_eq!(
+        error("line~5").message,
+        "`line` can't be matched with a regex"
+    );
+    assert_eq!(error("line~5").span, 0..6);
+
+    let not_a_number = error("timed line:abc");
+    assert_eq!(not_a_number.message, "Expected a number after `line:`");
+    assert_eq!(not_a_number.span, 11..14);
+    assert!(not_a_number.to_string().ends_with("(column 12)"));
+
+    // The span of a negated term starts after the `-`.
+    assert_eq!(error("-severity:bad").span, 10..13);
+    assert_eq!(error("-after:noon").span, 7..11);
+
+    assert!(error("msg~/(/").message.starts_with("Invalid regex"));
+    assert_eq!(error("msg~/(/").span, 4..7);
+}
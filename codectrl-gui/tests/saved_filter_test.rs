@@ -0,0 +1,65 @@
+use codectrl_gui::data::{merge_saved_filters, AppState, SavedFilter, Severity};
+
+fn filter(name: &str, query: &str, shortcut: Option<u8>) -> SavedFilter {
+    SavedFilter {
+        name: name.into(),
+        query: query.into(),
+        severity_filter: None,
+        is_case_sensitive: false,
+        is_using_regex: false,
+        shortcut,
+    }
+}
+
+#[test]
+fn test_merge_saved_filters() {
+    let mut filters = vec![filter("Errors", "severity:>=error", Some(1))];
+
+    merge_saved_filters(
+        &mut filters,
+        vec![
+            filter("Errors", "severity:fatal", Some(2)),
+            filter("Slow", "msg:timed", Some(1)),
+        ],
+    );
+
+    // Filters that are already there are kept as they are, and the merged ones
+    // don't take their shortcuts along, which could clash.
+    assert_eq!(
+        filters,
+        [
+            filter("Errors", "severity:>=error", Some(1)),
+            filter("Slow", "msg:timed", None),
+        ]
+    );
+
+    merge_saved_filters(&mut filters, vec![]);
+    assert_eq!(filters.len(), 2);
+}
+
+#[test]
+fn test_apply_saved_filter() {
+    let mut app_state = AppState {
+        query: "file:main.rs".into(),
+        severity_filter: Some(Severity::Warn),
+        is_using_regex: true,
+        ..AppState::default()
+    };
+
+    let saved = SavedFilter::from_state("  Main  ", &app_state);
+    assert_eq!(saved.name, "Main");
+    assert!(saved.is_applied(&app_state));
+
+    let other = filter("Everything", "", None);
+    assert!(!other.is_applied(&app_state));
+
+    other.apply(&mut app_state);
+    assert!(other.is_applied(&app_state));
+    assert!(!saved.is_applied(&app_state));
+    assert_eq!(app_state.severity_filter, None);
+
+    saved.apply(&mut app_state);
+    assert_eq!(app_state.query, "file:main.rs");
+    assert_eq!(app_state.severity_filter, Some(Severity::Warn));
+    assert!(app_state.is_using_regex);
+}
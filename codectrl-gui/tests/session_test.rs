@@ -0,0 +1,69 @@
+use chrono::{DateTime, Local, TimeZone};
+use codectrl_gui::data::{Fields, ReceivedLog, Severity};
+use codectrl_protobuf_bindings::data::Log;
+use serde::Serialize;
+
+fn time() -> DateTime<Local> { Local.timestamp_opt(1_000_000, 0).unwrap() }
+
+fn log(message: &str, message_type: &str) -> Log {
+    Log {
+        message: message.into(),
+        message_type: message_type.into(),
+        file_name: "src/main.rs".into(),
+        line_number: 42,
+        ..Log::default()
+    }
+}
+
+/// Saves `value` and loads it as a log, like sessions are.
+fn load<T: Serialize>(value: &T) -> ReceivedLog {
+    let mut data = vec![];
+    ciborium::ser::into_writer(value, &mut data).unwrap();
+
+    ciborium::de::from_reader(&data[..]).unwrap()
+}
+
+#[test]
+fn test_received_log_round_trip() {
+    let received = ReceivedLog {
+        log: log("Upstream timed out", "String"),
+        time: time(),
+        severity: Severity::Error,
+        fields: Fields::new(),
+    };
+
+    assert_eq!(load(&received), received);
+}
+
+#[test]
+fn test_legacy_log_severity() {
+    // Sessions from before severities only have the log and when it was
+    // received, the severity is worked out from the message type.
+    let legacy = load(&(log("Disk almost full", "WARNING"), time()));
+
+    assert_eq!(legacy.log, log("Disk almost full", "WARNING"));
+    assert_eq!(legacy.time, time());
+    assert_eq!(legacy.severity, Severity::Warn);
+
+    let legacy = load(&(log("Hello", "String"), time()));
+    assert_eq!(legacy.severity, Severity::default());
+}
+
+#[test]
+fn test_legacy_log_fields() {
+    // The fields of logs from before the server sent them are parsed out of
+    // the message, like the server does.
+    let legacy = load(&(
+        log(r#"{"user": {"id": 42}, "env": "prod"}"#, "Value"),
+        time(),
+    ));
+    assert_eq!(legacy.fields["env"], "prod");
+    assert_eq!(legacy.fields["user"]["id"], 42);
+
+    // Messages formatted with `Debug` are quoted.
+    let legacy = load(&(log(r#""{\"env\": \"prod\"}""#, "String"), time()));
+    assert_eq!(legacy.fields["env"], "prod");
+
+    let legacy = load(&(log("Hello", "String"), time()));
+    assert!(legacy.fields.is_empty());
+}
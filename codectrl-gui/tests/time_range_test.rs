@@ -0,0 +1,83 @@
+use chrono::{Duration, Local, TimeZone};
+use codectrl_gui::data::TimeRange;
+
+fn round_trip(input: &str) -> String {
+    let range =
+        TimeRange::parse(input).unwrap_or_else(|error| panic!("{input}: {error}"));
+    let displayed = range.to_string();
+
+    assert_eq!(TimeRange::parse(&displayed), Ok(range), "{displayed}");
+
+    displayed
+}
+
+#[test]
+fn test_relative_ranges() {
+    assert_eq!(
+        TimeRange::parse("last 5 minutes"),
+        Ok(TimeRange::Last(Duration::minutes(5)))
+    );
+    assert_eq!(
+        TimeRange::parse("Last Hour"),
+        Ok(TimeRange::Last(Duration::hours(1)))
+    );
+    assert_eq!(
+        TimeRange::parse("30s"),
+        Ok(TimeRange::Last(Duration::seconds(30)))
+    );
+
+    assert_eq!(round_trip("last 5 minutes"), "last 5 minutes");
+    assert_eq!(round_trip("last hour"), "last hour");
+    assert_eq!(round_trip("30s"), "last 30 seconds");
+    assert_eq!(round_trip("90m"), "last 90 minutes");
+    assert_eq!(round_trip("48h"), "last 2 days");
+}
+
+#[test]
+fn test_between_ranges() {
+    let today = Local::now().date_naive();
+    let at = |hour, minute| {
+        Local
+            .from_local_datetime(&today.and_hms_opt(hour, minute, 0).unwrap())
+            .earliest()
+    };
+
+    assert_eq!(
+        TimeRange::parse("10:00.."),
+        Ok(TimeRange::Between {
+            from: at(10, 0),
+            to: None,
+        })
+    );
+    assert_eq!(
+        TimeRange::parse(" 10:00 .. 10:30 "),
+        Ok(TimeRange::Between {
+            from: at(10, 0),
+            to: at(10, 30),
+        })
+    );
+
+    let displayed = round_trip("10:00..");
+    assert!(displayed.ends_with(" 10:00:00.."), "{displayed}");
+    assert_eq!(round_trip("..2023-03-01 09:00"), "..2023-03-01 09:00:00");
+    assert_eq!(round_trip(".."), "..");
+}
+
+#[test]
+fn test_invalid_ranges() {
+    assert!(TimeRange::parse("10:30..10:00").is_err());
+    assert!(TimeRange::parse("noon..").is_err());
+    assert!(TimeRange::parse("last 0 minutes").is_err());
+    assert!(TimeRange::parse("last fortnight").is_err());
+    assert!(TimeRange::parse("").is_err());
+}
+
+#[test]
+fn test_overflow() {
+    // Too large for an `i64`.
+    assert!(TimeRange::parse("last 99999999999999999999 seconds").is_err());
+    // Overflows when converted to seconds.
+    assert!(TimeRange::parse(&format!("last {} days", i64::MAX)).is_err());
+    // Fits in an `i64` of seconds, but not in a `Duration`.
+    assert!(TimeRange::parse("last 1000000000000 days").is_err());
+}
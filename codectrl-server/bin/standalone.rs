@@ -1,8 +1,359 @@
-use codectrl_server::run_server;
+#![warn(clippy::pedantic)]
+
+// region: imports
+
+use anyhow::Context;
+use clap::{crate_authors, crate_name, crate_version, Arg, ArgMatches, Command};
+use codectrl_server::{
+    admin, config::ServerConfig, open_database, run_server, tls::TlsConfig, unix_socket,
+};
+use dotenv::dotenv;
+use std::{
+    fs::File,
+    io::{self, BufWriter},
+    net::IpAddr,
+    path::Path,
+};
+
+// endregion
+
+/// Loads the config file and applies the options given on the command line on
+/// top of it.
+fn config_from_matches(matches: &ArgMatches) -> anyhow::Result<ServerConfig> {
+    let mut config = ServerConfig::load(matches.value_of("config").map(Path::new))?;
+
+    if let Some(host) = matches.value_of("host") {
+        config.listen.host = host
+            .parse::<IpAddr>()
+            .with_context(|| format!("\"{host}\" is not a valid IP address"))?
+            .to_string();
+    }
+
+    if let Some(port) = matches.value_of("port") {
+        config.listen.port = port
+            .parse()
+            .context("Port was not a valid value: needs to be between 1 and 65535.")?;
+    }
+
+    if let Some(port) = matches.value_of("http_api_port") {
+        config.listen.http_api_port = port.parse().context(
+            "HTTP API port was not a valid value: needs to be between 1 and 65535.",
+        )?;
+    }
+
+    if matches.is_present("no_http_api") {
+        config.listen.http_api = false;
+    }
+
+    if let Some(port) = matches.value_of("redirect_port") {
+        config.auth.redirect_port = port.parse().context(
+            "Redirect port was not a valid value: needs to be between 1 and 65535.",
+        )?;
+    }
+
+    if let Some(path) = matches.value_of("unix_socket") {
+        config.listen.unix_socket = Some(path.into());
+    }
+
+    if let Some(mode) = matches.value_of("unix_socket_mode") {
+        config.listen.unix_socket_mode = Some(
+            unix_socket::parse_mode(mode)
+                .with_context(|| format!("\"{mode}\" is not an octal file mode"))?,
+        );
+    }
+
+    if matches.is_present("no_tcp") {
+        config.listen.tcp = false;
+    }
+
+    if let Some(data_dir) = matches.value_of("data_dir") {
+        config.data_dir = Some(data_dir.into());
+    }
+
+    if matches.is_present("require_auth") {
+        config.auth.required = true;
+    }
+
+    if matches.is_present("no_censor") {
+        config.censor_usernames = false;
+    }
+
+    // clap makes sure that the certificate and key are given together.
+    if let (Some(cert), Some(key)) =
+        (matches.value_of("tls_cert"), matches.value_of("tls_key"))
+    {
+        config.tls = Some(TlsConfig {
+            cert: cert.into(),
+            key: key.into(),
+            client_ca: matches.value_of("tls_client_ca").map(Into::into),
+        });
+    }
+
+    Ok(config)
+}
+
+async fn db(config: &ServerConfig, matches: &ArgMatches) -> anyhow::Result<()> {
+    // Opening the database creates anything that is missing from it, so that's
+    // all a migration needs.
+    let db_connection = open_database(&config.data_dir()).await?;
+
+    match matches.subcommand() {
+        Some(("migrate", _)) => println!("The database is up to date"),
+        Some(("vacuum", _)) => {
+            admin::vacuum(&db_connection)
+                .await
+                .context("Could not vacuum the database, is the server still running?")?;
+
+            println!("Vacuumed the database");
+        },
+        _ => unreachable!("a subcommand is required"),
+    }
+
+    Ok(())
+}
+
+async fn connections(config: &ServerConfig, matches: &ArgMatches) -> anyhow::Result<()> {
+    let db_connection = open_database(&config.data_dir()).await?;
+
+    match matches.subcommand() {
+        Some(("list", _)) => {
+            let connections = admin::list_connections(&db_connection).await?;
+
+            println!("{:<36}  {:>9}  OWNER", "UUID", "LAST SENT");
+
+            for connection in connections {
+                println!(
+                    "{:<36}  {:>9}  {}",
+                    connection.uuid,
+                    connection
+                        .last_sent_log_id
+                        .map_or_else(|| "-".into(), |id| id.to_string()),
+                    connection.owner.as_deref().unwrap_or("-")
+                );
+            }
+        },
+        Some(("prune", matches)) => {
+            let pruned =
+                admin::prune_connections(&db_connection, matches.is_present("all"))
+                    .await?;
+
+            println!("Pruned {pruned} connection(s)");
+        },
+        _ => unreachable!("a subcommand is required"),
+    }
+
+    Ok(())
+}
+
+async fn logs(config: &ServerConfig, matches: &ArgMatches) -> anyhow::Result<()> {
+    let db_connection = open_database(&config.data_dir()).await?;
+
+    match matches.subcommand() {
+        Some(("export", matches)) => {
+            let project = matches.value_of("project");
+
+            let exported = if let Some(output) = matches.value_of("output") {
+                let file = File::create(output)
+                    .with_context(|| format!("Could not create \"{output}\""))?;
+
+                admin::export_logs(&db_connection, project, BufWriter::new(file)).await?
+            } else {
+                admin::export_logs(&db_connection, project, io::stdout().lock()).await?
+            };
+
+            eprintln!("Exported {exported} log(s)");
+        },
+        _ => unreachable!("a subcommand is required"),
+    }
+
+    Ok(())
+}
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    run_server(None, None, None, None).await?;
+    let matches = Command::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!(", "))
+        .about("The CodeCTRL log server")
+        .arg(
+            Arg::new("config")
+                .takes_value(true)
+                .short('c')
+                .long("config")
+                .global(true)
+                .help(
+                    "The config file to load. Can also be specified with the \
+                     CODECTRL_CONFIG environment variable. Defaults to config.toml in \
+                     the platform's config directory, if it exists.",
+                ),
+        )
+        .arg(
+            Arg::new("data_dir")
+                .takes_value(true)
+                .long("data-dir")
+                .global(true)
+                .help("The directory that the database is kept in"),
+        )
+        .arg(
+            Arg::new("log_level")
+                .takes_value(true)
+                .short('l')
+                .long("log-level")
+                .global(true)
+                .possible_values(["off", "error", "warn", "info", "debug", "trace"])
+                .help("How much to log, overriding the RUST_LOG environment variable"),
+        )
+        .arg(
+            Arg::new("host")
+                .takes_value(true)
+                .short('H')
+                .long("host")
+                .help("The IP address to listen on"),
+        )
+        .arg(
+            Arg::new("port")
+                .takes_value(true)
+                .short('p')
+                .long("port")
+                .help("The port of the gRPC server"),
+        )
+        .arg(
+            Arg::new("http_api_port")
+                .takes_value(true)
+                .long("http-api-port")
+                .conflicts_with("no_http_api")
+                .help("The port of the JSON API and the metrics"),
+        )
+        .arg(
+            Arg::new("no_http_api")
+                .long("no-http-api")
+                .help("Does not serve the JSON API and the metrics"),
+        )
+        .arg(
+            Arg::new("unix_socket")
+                .takes_value(true)
+                .long("unix-socket")
+                .help("A Unix socket for the gRPC server to listen on as well"),
+        )
+        .arg(
+            Arg::new("unix_socket_mode")
+                .takes_value(true)
+                .long("unix-socket-mode")
+                .help("The permissions of the Unix socket in octal, i.e. 660"),
+        )
+        .arg(Arg::new("no_tcp").long("no-tcp").help(
+            "Only listens on the Unix socket, not on the gRPC port, and does not serve \
+             the JSON API and the metrics. The socket can come from --unix-socket or \
+             the config file",
+        ))
+        .arg(
+            Arg::new("require_auth")
+                .long("require-auth")
+                .help("Requires clients to log in before they can send or view logs"),
+        )
+        .arg(
+            Arg::new("redirect_port")
+                .takes_value(true)
+                .long("redirect-port")
+                .help("The port that OAuth providers redirect back to after logging in"),
+        )
+        .arg(
+            Arg::new("no_censor")
+                .long("no-censor")
+                .help("Keeps usernames in the file paths of logs"),
+        )
+        .arg(
+            Arg::new("tls_cert")
+                .takes_value(true)
+                .long("tls-cert")
+                .requires("tls_key")
+                .help("The PEM certificate chain to serve gRPC over TLS with"),
+        )
+        .arg(
+            Arg::new("tls_key")
+                .takes_value(true)
+                .long("tls-key")
+                .requires("tls_cert")
+                .help("The PEM private key of the TLS certificate"),
+        )
+        .arg(
+            Arg::new("tls_client_ca")
+                .takes_value(true)
+                .long("tls-client-ca")
+                .requires("tls_cert")
+                .help(
+                    "Requires clients to present a certificate signed by this PEM CA \
+                     (mutual TLS)",
+                ),
+        )
+        .subcommand(
+            Command::new("db")
+                .about("Maintains the database, while the server is stopped")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("migrate")
+                        .about("Creates the database or brings it up to date"),
+                )
+                .subcommand(
+                    Command::new("vacuum")
+                        .about("Reclaims the space left behind by deleted logs"),
+                ),
+        )
+        .subcommand(
+            Command::new("connections")
+                .about("Manages the connections that clients have registered")
+                .subcommand_required(true)
+                .subcommand(Command::new("list").about("Lists the stored connections"))
+                .subcommand(
+                    Command::new("prune")
+                        .about("Deletes the connections that have never been sent a log")
+                        .arg(
+                            Arg::new("all")
+                                .long("all")
+                                .help("Deletes every connection instead"),
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("logs")
+                .about("Works with the stored logs")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("export")
+                        .about("Writes the stored logs as JSON Lines, oldest first")
+                        .arg(
+                            Arg::new("output")
+                                .takes_value(true)
+                                .short('o')
+                                .long("output")
+                                .help("The file to write to, instead of stdout"),
+                        )
+                        .arg(
+                            Arg::new("project")
+                                .takes_value(true)
+                                .long("project")
+                                .help("Only exports the logs of this project"),
+                        ),
+                ),
+        )
+        .get_matches();
 
-    Ok(())
+    dotenv().ok();
+
+    let mut logger = env_logger::Builder::from_default_env();
+
+    if let Some(log_level) = matches.value_of("log_level") {
+        logger.parse_filters(log_level);
+    }
+
+    logger.try_init().ok();
+
+    let config = config_from_matches(&matches)?;
+
+    match matches.subcommand() {
+        Some(("db", matches)) => db(&config, matches).await,
+        Some(("connections", matches)) => connections(&config, matches).await,
+        Some(("logs", matches)) => logs(&config, matches).await,
+        _ => run_server(config).await,
+    }
 }
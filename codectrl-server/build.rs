@@ -0,0 +1,11 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tonic_build::configure().compile(
+        &[
+            "proto/login_providers.proto",
+            "proto/opentelemetry/proto/collector/logs/v1/logs_service.proto",
+        ],
+        &["proto"],
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,147 @@
+// region: imports
+
+use crate::{
+    entity::{
+        connection::{Column as ConnectionColumn, Entity as ConnectionEntity},
+        log::{Column as LogColumn, Entity as LogEntity},
+    },
+    StoredLog,
+};
+use futures::StreamExt;
+use sea_orm::{
+    ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, Statement,
+};
+use serde_json::json;
+use std::io::Write;
+
+// endregion
+
+// Maintenance tasks for the standalone binary's subcommands. They work on the
+// database directly, so they're meant to be run while the server is stopped.
+
+/// A connection that a client registered with the server, as stored in the
+/// database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredConnection {
+    pub uuid: String,
+    /// The subject of the token that registered the connection, if any.
+    pub owner: Option<String>,
+    /// The ID of the newest log sent down the connection, if any have been.
+    pub last_sent_log_id: Option<i64>,
+}
+
+/// Rebuilds the database file to reclaim the space left behind by deleted
+/// logs.
+///
+/// # Errors
+///
+/// Returns an error if SQLite fails to vacuum the database, i.e. because the
+/// server is still using it.
+pub async fn vacuum(db_connection: &DatabaseConnection) -> Result<(), DbErr> {
+    db_connection
+        .execute(Statement::from_string(
+            db_connection.get_database_backend(),
+            "VACUUM".to_owned(),
+        ))
+        .await?;
+
+    Ok(())
+}
+
+/// # Errors
+///
+/// Returns an error if the connections couldn't be read from the database.
+pub async fn list_connections(
+    db_connection: &DatabaseConnection,
+) -> Result<Vec<StoredConnection>, DbErr> {
+    Ok(ConnectionEntity::find()
+        .order_by_asc(ConnectionColumn::Uuid)
+        .all(db_connection)
+        .await?
+        .into_iter()
+        .map(|connection| StoredConnection {
+            uuid: connection.uuid,
+            owner: connection.owner,
+            last_sent_log_id: connection.last_sent_log_id.filter(|id| *id > 0),
+        })
+        .collect())
+}
+
+/// Deletes the connections that were registered but never had a log sent down
+/// them, or every connection if `all` is set. Clients register again when
+/// their connection is gone, so this is safe to do. Returns how many
+/// connections were deleted.
+///
+/// # Errors
+///
+/// Returns an error if the connections couldn't be deleted from the database.
+pub async fn prune_connections(
+    db_connection: &DatabaseConnection,
+    all: bool,
+) -> Result<u64, DbErr> {
+    let mut delete = ConnectionEntity::delete_many();
+
+    if !all {
+        delete = delete.filter(
+            Condition::all()
+                .add(
+                    Condition::any()
+                        .add(ConnectionColumn::LastSentLogId.is_null())
+                        .add(ConnectionColumn::LastSentLogId.eq(0)),
+                )
+                .add(
+                    Condition::any()
+                        .add(ConnectionColumn::SentLogs.is_null())
+                        .add(ConnectionColumn::SentLogs.eq("[]")),
+                ),
+        );
+    }
+
+    Ok(delete.exec(db_connection).await?.rows_affected)
+}
+
+/// Writes the stored logs, oldest first, to `writer` as JSON Lines in the same
+/// shape as `codectrl-cli --json`. Only the logs of `project` are written if
+/// it's given. Returns how many logs were written.
+///
+/// # Errors
+///
+/// Returns an error if the logs couldn't be read from the database or written
+/// to `writer`.
+pub async fn export_logs(
+    db_connection: &DatabaseConnection,
+    project: Option<&str>,
+    mut writer: impl Write,
+) -> anyhow::Result<usize> {
+    let mut select = LogEntity::find().order_by_asc(LogColumn::Id);
+
+    if let Some(project) = project {
+        select = select.filter(LogColumn::Project.eq(project));
+    }
+
+    let mut rows = select.stream(db_connection).await?;
+    let mut exported = 0;
+
+    while let Some(row) = rows.next().await {
+        let stored = StoredLog::try_from(row?)?;
+
+        serde_json::to_writer(
+            &mut writer,
+            &json!({
+                "received_at": stored.received_at.to_rfc3339(),
+                "project": stored.project,
+                "severity": stored.severity.to_string(),
+                "fields": stored.fields,
+                "log": stored.log,
+            }),
+        )?;
+        writeln!(writer)?;
+
+        exported += 1;
+    }
+
+    writer.flush()?;
+
+    Ok(exported)
+}
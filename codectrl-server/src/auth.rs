@@ -0,0 +1,334 @@
+// region: imports
+
+use dashmap::DashSet;
+use jsonwebtoken::{
+    decode, encode, errors::Error as JwtError, Algorithm, DecodingKey, EncodingKey,
+    Header, Validation,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tonic::{
+    metadata::{Ascii, MetadataMap, MetadataValue},
+    service::Interceptor,
+    Request, Status,
+};
+use uuid::Uuid;
+
+// endregion
+
+pub const TOKEN_ISSUER: &str = "codectrl-server";
+pub const DEFAULT_TOKEN_LIFETIME: Duration = Duration::from_secs(60 * 60 * 24);
+/// The subject of tokens generated while the server doesn't require
+/// authentication, as there is no one logged in to generate them for.
+pub const ANONYMOUS_SUBJECT: &str = "anonymous";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TokenClaims {
+    #[serde(rename = "iat")]
+    pub issued_at: usize,
+    #[serde(rename = "iss")]
+    pub issuer: String,
+    #[serde(rename = "exp")]
+    pub expire: usize,
+    #[serde(rename = "sub")]
+    pub subject: String,
+    #[serde(rename = "nbf")]
+    pub not_before: usize,
+    #[serde(rename = "jti")]
+    pub id: String,
+    /// The ID shared by a token and the tokens it is refreshed into, so that
+    /// revoking any of them revokes the others too.
+    #[serde(rename = "sid")]
+    pub session: String,
+    /// The projects whose logs the token may send and see. An empty list
+    /// means the token isn't limited to any project.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub projects: Vec<String>,
+    /// What the token is for, i.e. the name of the logger it was generated for.
+    /// Only a label, it says nothing about who holds the token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// The session of the token that this token was generated with, if any.
+    /// The session is used rather than the token's ID so that refreshing the
+    /// parent doesn't invalidate its children.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    /// The sessions of the tokens that the parent was generated with, nearest
+    /// first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ancestors: Vec<String>,
+}
+
+fn seconds_since_epoch() -> usize {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| usize::try_from(duration.as_secs()).unwrap_or(usize::MAX))
+        .unwrap_or_default()
+}
+
+impl TokenClaims {
+    #[must_use]
+    pub fn new(subject: &str, lifetime: Duration) -> Self {
+        let now = seconds_since_epoch();
+        let lifetime = usize::try_from(lifetime.as_secs()).unwrap_or(usize::MAX);
+
+        Self {
+            issued_at: now,
+            issuer: TOKEN_ISSUER.into(),
+            expire: now.saturating_add(lifetime),
+            subject: subject.into(),
+            not_before: now,
+            id: Uuid::new_v4().hyphenated().to_string(),
+            session: Uuid::new_v4().hyphenated().to_string(),
+            projects: Vec::new(),
+            label: None,
+            parent: None,
+            ancestors: Vec::new(),
+        }
+    }
+
+    /// The claims of a token generated by the holder of these claims, i.e. for
+    /// one of their loggers. The new token has the same subject as its parent,
+    /// so it can't be used to act as anyone else.
+    #[must_use]
+    pub fn child(&self, lifetime: Duration) -> Self {
+        let mut claims = Self::new(&self.subject, lifetime);
+        claims.parent = Some(self.session.clone());
+        claims.ancestors = self.parent.iter().chain(&self.ancestors).cloned().collect();
+        claims
+    }
+
+    /// The claims of the token that this token is refreshed into. Everything
+    /// but the ID and the validity period is kept, including the session.
+    #[must_use]
+    pub fn refreshed(&self, lifetime: Duration) -> Self {
+        let fresh = Self::new(&self.subject, lifetime);
+
+        Self {
+            issued_at: fresh.issued_at,
+            expire: fresh.expire,
+            not_before: fresh.not_before,
+            id: fresh.id,
+            ..self.clone()
+        }
+    }
+
+    /// Every ID that invalidates this token when revoked: its own ID, its
+    /// session and the sessions of the tokens it was generated with.
+    pub fn revocable_ids(&self) -> impl Iterator<Item = &String> {
+        [&self.id, &self.session]
+            .into_iter()
+            .chain(&self.parent)
+            .chain(&self.ancestors)
+    }
+
+    #[must_use]
+    pub fn with_label(mut self, label: &str) -> Self {
+        self.label = Some(label.trim())
+            .filter(|label| !label.is_empty())
+            .map(Into::into);
+        self
+    }
+
+    #[must_use]
+    pub fn with_projects(mut self, projects: Vec<String>) -> Self {
+        self.projects = projects;
+        self
+    }
+}
+
+/// The secret used to sign and verify tokens. Wrapped so that it doesn't end up
+/// in the output of `Debug`.
+#[derive(Clone)]
+pub struct TokenSecret(Arc<String>);
+
+impl TokenSecret {
+    #[must_use]
+    pub fn new(secret: String) -> Self { Self(Arc::new(secret)) }
+
+    /// Signs `claims` into a JWT.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the claims could not be encoded.
+    pub fn sign(&self, claims: &TokenClaims) -> Result<String, JwtError> {
+        encode(
+            &Header::new(Algorithm::HS256),
+            claims,
+            &EncodingKey::from_secret(self.0.as_bytes()),
+        )
+    }
+
+    /// Checks the signature, issuer, expiry and "not before" time of `token`,
+    /// returning its claims if it is valid. This does _not_ check whether the
+    /// token has been revoked.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the token is malformed, badly signed or expired.
+    pub fn verify(&self, token: &str) -> Result<TokenClaims, JwtError> {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.set_issuer(&[TOKEN_ISSUER]);
+        validation.validate_nbf = true;
+
+        decode::<TokenClaims>(
+            token,
+            &DecodingKey::from_secret(self.0.as_bytes()),
+            &validation,
+        )
+        .map(|data| data.claims)
+    }
+}
+
+impl fmt::Debug for TokenSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TokenSecret(<hidden>)")
+    }
+}
+
+/// Extracts the token from an `authorization: Bearer <token>` metadata entry.
+#[must_use]
+pub fn bearer_token(metadata: &MetadataMap) -> Option<&str> {
+    metadata
+        .get("authorization")?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+}
+
+/// Attaches a token to every request that a client sends to the server, i.e.
+/// the token the user of the GUI logged in with. Does nothing if the token is
+/// empty.
+#[derive(Debug, Clone, Default)]
+pub struct BearerToken(Option<MetadataValue<Ascii>>);
+
+impl BearerToken {
+    #[must_use]
+    pub fn new(token: &str) -> Self {
+        if token.is_empty() {
+            return Self(None);
+        }
+
+        Self(format!("Bearer {token}").parse().ok())
+    }
+}
+
+impl Interceptor for BearerToken {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        if let Some(token) = &self.0 {
+            request
+                .metadata_mut()
+                .insert("authorization", token.clone());
+        }
+
+        Ok(request)
+    }
+}
+
+/// Who sent a request, added to the request's extensions by
+/// [`AuthInterceptor`].
+#[derive(Debug, Clone)]
+pub enum Identity {
+    /// No valid token was given, along with the reason why.
+    Anonymous(String),
+    Authenticated(TokenClaims),
+}
+
+impl Identity {
+    /// Gets the identity that [`AuthInterceptor`] attached to `request`.
+    #[must_use]
+    pub fn of<T>(request: &Request<T>) -> Option<&Self> { request.extensions().get() }
+
+    /// Gets the claims of an authenticated identity, or why the identity is
+    /// anonymous if `requires_authentication` is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Status::unauthenticated` if authentication is required and the
+    /// request had no valid token.
+    pub fn require<T>(
+        request: &Request<T>,
+        requires_authentication: bool,
+    ) -> Result<Option<&TokenClaims>, Status> {
+        match Self::of(request) {
+            Some(Self::Authenticated(claims)) => Ok(Some(claims)),
+            Some(Self::Anonymous(reason)) if requires_authentication =>
+                Err(Status::unauthenticated(reason)),
+            None if requires_authentication =>
+                Err(Status::unauthenticated("No bearer token was supplied.")),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Checks the bearer token of every request and attaches the resulting
+/// [`Identity`] to the request's extensions.
+///
+/// If `rejects_anonymous` is set, requests without a valid token are rejected
+/// outright when the server requires authentication. Otherwise it is up to the
+/// service to call [`Identity::require`], which allows services to have
+/// methods that anyone can call (i.e. `get_server_details`, which tells clients
+/// whether they need to log in in the first place).
+#[derive(Debug, Clone)]
+pub struct AuthInterceptor {
+    pub requires_authentication: bool,
+    pub rejects_anonymous: bool,
+    pub token_secret: TokenSecret,
+    pub revoked_tokens: Arc<DashSet<String>>,
+}
+
+impl AuthInterceptor {
+    /// Verifies `token` and checks that neither it nor any of the tokens it
+    /// was generated with have been revoked.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Status::unauthenticated` if the token is invalid or revoked.
+    pub fn verify_token(&self, token: &str) -> Result<TokenClaims, Status> {
+        let claims = self.token_secret.verify(token).map_err(|error| {
+            Status::unauthenticated(format!("Invalid token: {error}"))
+        })?;
+
+        if claims
+            .revocable_ids()
+            .any(|id| self.revoked_tokens.contains(id))
+        {
+            return Err(Status::unauthenticated("Token has been revoked."));
+        }
+
+        Ok(claims)
+    }
+
+    /// Verifies the bearer token in `metadata`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Status::unauthenticated` if there is no token, or the token is
+    /// invalid or revoked.
+    pub fn verify_metadata(&self, metadata: &MetadataMap) -> Result<TokenClaims, Status> {
+        let token = bearer_token(metadata)
+            .ok_or_else(|| Status::unauthenticated("No bearer token was supplied."))?;
+
+        self.verify_token(token)
+    }
+}
+
+impl Interceptor for AuthInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        let identity = match self.verify_metadata(request.metadata()) {
+            Ok(claims) => Identity::Authenticated(claims),
+            Err(status) if self.requires_authentication && self.rejects_anonymous =>
+                return Err(status),
+            Err(status) => Identity::Anonymous(status.message().into()),
+        };
+
+        request.extensions_mut().insert(identity);
+
+        Ok(request)
+    }
+}
@@ -0,0 +1,778 @@
+// region: imports
+
+use crate::{
+    oauth::{ProviderConfig, ProviderKind},
+    project::ProjectMembers,
+    retention::{parse_env_var, RetentionPolicy},
+    syslog::SyslogConfig,
+    tls::TlsConfig,
+    unix_socket,
+};
+use directories::ProjectDirs;
+use dotenv::dotenv;
+use log::{info, warn};
+use serde::{de::Error as _, Deserialize, Deserializer};
+use std::{
+    collections::BTreeMap,
+    env,
+    error::Error,
+    fmt::{self, Display},
+    fs, io,
+    net::IpAddr,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use toml::{value::Table, Value};
+
+// endregion
+
+/// The environment variable that the path of the config file can be given in,
+/// when it isn't given on the command line.
+pub const CONFIG_ENV_VAR: &str = "CODECTRL_CONFIG";
+
+fn project_dirs() -> Option<ProjectDirs> {
+    ProjectDirs::from("com", "Authentura", "codectrl-server")
+}
+
+/// The server's configuration, usually read from a TOML file with
+/// [`ServerConfig::load`]. Every key is optional, so an empty file is the
+/// default config:
+///
+/// ```toml
+/// # Defaults to the platform's data directory, i.e.
+/// # ~/.local/share/codectrl-server on Linux.
+/// data_dir = "/var/lib/codectrl"
+/// # Whether usernames are taken out of file paths in logs.
+/// censor_usernames = true
+///
+/// [listen]
+/// host = "127.0.0.1"
+/// port = 3002
+/// # Whether the JSON API and the metrics are served on `http_api_port`.
+/// http_api = true
+/// http_api_port = 3003
+/// # Whether the gRPC server listens on `port`, it can be turned off when it
+/// # listens on a Unix socket instead. The HTTP API is turned off with it.
+/// tcp = true
+/// # The gRPC server also listens on this Unix socket if it's set.
+/// unix_socket = "/run/codectrl/codectrl.sock"
+/// unix_socket_mode = 0o660
+///
+/// [auth]
+/// required = false
+/// redirect_port = 8080
+/// # A random secret is generated on every start if this isn't set, which
+/// # logs everyone out when the server restarts.
+/// token_secret = "..."
+///
+/// [retention]
+/// max_logs = 100000
+/// max_age = 604800 # in seconds
+/// max_bytes = 1073741824
+///
+/// # The syslog listener is only started if this table is present.
+/// [syslog]
+/// port = 5140
+/// udp = true
+/// tcp = true
+/// project = "infrastructure"
+/// # Syslog can't send a token, so the listener refuses to start when
+/// # authentication is required unless this is set.
+/// allow_unauthenticated = false
+///
+/// # The gRPC server and the HTTP API only use TLS if this table is present.
+/// [tls]
+/// cert = "/etc/codectrl/server.pem"
+/// key = "/etc/codectrl/server.key"
+/// # Clients have to present a certificate signed by this CA if it's set.
+/// client_ca = "/etc/codectrl/clients-ca.pem"
+///
+/// # The providers that users can log in with, by name. If there is no
+/// # [oauth] table, they are read from the environment variables instead.
+/// [oauth.github]
+/// client_id = "..."
+/// client_secret = "..."
+///
+/// [oauth.keycloak]
+/// # github, gitlab, oidc or oauth2. Defaults to the name of the provider if
+/// # it is one of those, or else to oidc if base_url is set and oauth2 if not.
+/// kind = "oidc"
+/// client_id = "codectrl"
+/// # The URL of a self-hosted GitLab instance, or the issuer of an OIDC
+/// # provider.
+/// base_url = "https://keycloak.example.com/realms/main"
+/// # auth_url, token_url and user_url override the provider's endpoints, which
+/// # oauth2 providers need all of.
+/// scopes = ["openid", "profile"]
+/// subject_field = "preferred_username"
+///
+/// # The subjects of the members of each project, where "*" is a member of
+/// # every project. Anyone can log in to every project if this isn't set.
+/// [projects]
+/// project-a = ["github:alice", "gitlab:bob"]
+/// "*" = ["github:admin"]
+/// ```
+///
+/// The keys are checked when the config is parsed, while the keys that depend
+/// on each other are only checked by [`ServerConfig::validate`], once the
+/// environment variables and command line options have been applied.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub data_dir: Option<PathBuf>,
+    pub censor_usernames: bool,
+    pub listen: ListenConfig,
+    pub auth: AuthConfig,
+    pub retention: RetentionConfig,
+    pub syslog: Option<SyslogConfig>,
+    pub tls: Option<TlsConfig>,
+    /// The `[oauth]` table, see [`ServerConfig::oauth_providers`].
+    #[serde(deserialize_with = "deserialize_oauth")]
+    pub oauth: Option<Vec<ProviderConfig>>,
+    /// The `[projects]` table.
+    pub projects: ProjectMembers,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            data_dir: None,
+            censor_usernames: true,
+            listen: ListenConfig::default(),
+            auth: AuthConfig::default(),
+            retention: RetentionConfig::default(),
+            syslog: None,
+            tls: None,
+            oauth: None,
+            projects: ProjectMembers::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ListenConfig {
+    pub host: String,
+    /// The port of the `gRPC` server.
+    pub port: u16,
+    /// Whether the JSON API is served on `http_api_port`, see
+    /// [`crate::http_api`].
+    pub http_api: bool,
+    /// The port of the JSON API.
+    pub http_api_port: u16,
+    /// Whether the `gRPC` server listens on `host` and `port`. The HTTP API is
+    /// only served if this is on, as it can't listen on `unix_socket`.
+    pub tcp: bool,
+    /// A Unix socket that the `gRPC` server listens on as well, see
+    /// [`crate::unix_socket`].
+    pub unix_socket: Option<PathBuf>,
+    /// The permissions of `unix_socket`, i.e. `0o660` to only let the owner
+    /// and their group connect.
+    pub unix_socket_mode: Option<u32>,
+}
+
+impl Default for ListenConfig {
+    fn default() -> Self {
+        Self {
+            host: "127.0.0.1".into(),
+            port: 3002,
+            http_api: true,
+            http_api_port: 3003,
+            tcp: true,
+            unix_socket: None,
+            unix_socket_mode: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct AuthConfig {
+    pub required: bool,
+    /// The port that OAuth providers redirect back to after logging in.
+    pub redirect_port: u16,
+    pub token_secret: Option<String>,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            required: false,
+            redirect_port: 8080,
+            token_secret: None,
+        }
+    }
+}
+
+/// The `[retention]` table, see [`RetentionPolicy`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct RetentionConfig {
+    pub max_logs: Option<u64>,
+    /// In seconds.
+    pub max_age: Option<u64>,
+    pub max_bytes: Option<u64>,
+}
+
+/// A provider in the `[oauth]` table, which is named by its key.
+#[derive(Deserialize)]
+struct OAuthProviderTable {
+    kind: Option<String>,
+    client_id: String,
+    client_secret: Option<String>,
+    base_url: Option<String>,
+    auth_url: Option<String>,
+    token_url: Option<String>,
+    user_url: Option<String>,
+    scopes: Option<Vec<String>>,
+    subject_field: Option<String>,
+}
+
+fn deserialize_oauth<'de, D>(
+    deserializer: D,
+) -> Result<Option<Vec<ProviderConfig>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    BTreeMap::<String, OAuthProviderTable>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(name, provider)| -> Result<_, D::Error> {
+            let kind = match provider.kind {
+                Some(kind) => kind.parse::<ProviderKind>().map_err(D::Error::custom)?,
+                None => ProviderKind::default_for(&name, provider.base_url.is_some()),
+            };
+
+            Ok(ProviderConfig {
+                name: name.to_lowercase(),
+                kind,
+                client_id: provider.client_id,
+                client_secret: provider.client_secret,
+                base_url: provider.base_url,
+                auth_url: provider.auth_url,
+                token_url: provider.token_url,
+                user_url: provider.user_url,
+                scopes: provider.scopes,
+                subject_field: provider.subject_field,
+            })
+        })
+        .collect::<Result<_, _>>()
+        .map(Some)
+}
+
+impl From<RetentionConfig> for RetentionPolicy {
+    fn from(config: RetentionConfig) -> Self {
+        Self {
+            max_count: config.max_logs,
+            max_age: config.max_age.map(Duration::from_secs),
+            max_bytes: config.max_bytes,
+        }
+    }
+}
+
+/// What a key in the config file has to be.
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Table,
+    Bool,
+    String,
+    NonEmptyString,
+    IpAddress,
+    Port,
+    Count,
+    FileMode,
+    StringList,
+    LoginProvider,
+}
+
+/// Every key that the config file can have, by its dotted path. A `*` stands
+/// for any name, i.e. the name of a login provider.
+const KEYS: [(&str, Kind); 41] = [
+    ("data_dir", Kind::NonEmptyString),
+    ("censor_usernames", Kind::Bool),
+    ("listen", Kind::Table),
+    ("listen.host", Kind::IpAddress),
+    ("listen.port", Kind::Port),
+    ("listen.http_api", Kind::Bool),
+    ("listen.http_api_port", Kind::Port),
+    ("listen.tcp", Kind::Bool),
+    ("listen.unix_socket", Kind::NonEmptyString),
+    ("listen.unix_socket_mode", Kind::FileMode),
+    ("auth", Kind::Table),
+    ("auth.required", Kind::Bool),
+    ("auth.redirect_port", Kind::Port),
+    ("auth.token_secret", Kind::NonEmptyString),
+    ("retention", Kind::Table),
+    ("retention.max_logs", Kind::Count),
+    ("retention.max_age", Kind::Count),
+    ("retention.max_bytes", Kind::Count),
+    ("syslog", Kind::Table),
+    ("syslog.port", Kind::Port),
+    ("syslog.udp", Kind::Bool),
+    ("syslog.tcp", Kind::Bool),
+    ("syslog.project", Kind::String),
+    ("syslog.allow_unauthenticated", Kind::Bool),
+    ("tls", Kind::Table),
+    ("tls.cert", Kind::NonEmptyString),
+    ("tls.key", Kind::NonEmptyString),
+    ("tls.client_ca", Kind::NonEmptyString),
+    ("oauth", Kind::Table),
+    ("oauth.*", Kind::Table),
+    ("oauth.*.kind", Kind::LoginProvider),
+    ("oauth.*.client_id", Kind::NonEmptyString),
+    ("oauth.*.client_secret", Kind::NonEmptyString),
+    ("oauth.*.base_url", Kind::NonEmptyString),
+    ("oauth.*.auth_url", Kind::NonEmptyString),
+    ("oauth.*.token_url", Kind::NonEmptyString),
+    ("oauth.*.user_url", Kind::NonEmptyString),
+    ("oauth.*.scopes", Kind::StringList),
+    ("oauth.*.subject_field", Kind::NonEmptyString),
+    ("projects", Kind::Table),
+    ("projects.*", Kind::StringList),
+];
+
+impl Kind {
+    fn check(self, value: &Value) -> Result<(), String> {
+        let is_valid = match self {
+            Self::Table => value.is_table(),
+            Self::Bool => value.is_bool(),
+            Self::String => value.is_str(),
+            Self::NonEmptyString =>
+                matches!(value.as_str(), Some(value) if !value.is_empty()),
+            Self::IpAddress =>
+                matches!(value.as_str(), Some(value) if value.parse::<IpAddr>().is_ok()),
+            Self::Port => matches!(value.as_integer(), Some(1..=65535)),
+            Self::Count => matches!(value.as_integer(), Some(0..=i64::MAX)),
+            Self::FileMode => matches!(value.as_integer(), Some(0..=0o777)),
+            Self::StringList => matches!(
+                value.as_array(),
+                Some(values) if values.iter().all(Value::is_str)
+            ),
+            Self::LoginProvider => matches!(
+                value.as_str(),
+                Some(kind) if kind.parse::<ProviderKind>().is_ok()
+            ),
+        };
+
+        if is_valid {
+            return Ok(());
+        }
+
+        Err(match self {
+            Self::Table => "expected a table".into(),
+            Self::Bool => "expected true or false".into(),
+            Self::String => "expected a string".into(),
+            Self::NonEmptyString => "expected a non-empty string".into(),
+            Self::IpAddress => format!("{value} is not an IP address"),
+            Self::Port => format!("{value} is not a port, expected 1 to 65535"),
+            Self::Count => format!("{value} is not a non-negative integer"),
+            Self::FileMode =>
+                format!("{value} is not a file mode, expected 0o000 to 0o777"),
+            Self::StringList => "expected a list of strings".into(),
+            Self::LoginProvider => format!(
+                "{value} is not a kind of provider, expected github, gitlab, oidc or \
+                 oauth2"
+            ),
+        })
+    }
+}
+
+/// A key in the config file that has the wrong type or value, or isn't a key
+/// that the server knows about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigProblem {
+    /// The dotted path of the key, i.e. `listen.port`.
+    pub key: String,
+    pub message: String,
+}
+
+impl Display for ConfigProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.key, self.message)
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, io::Error),
+    Syntax(Option<PathBuf>, toml::de::Error),
+    /// Every problem with the config, rather than just the first one.
+    Invalid(Option<PathBuf>, Vec<ConfigProblem>),
+}
+
+impl ConfigError {
+    fn with_path(self, path: &Path) -> Self {
+        match self {
+            Self::Syntax(_, error) => Self::Syntax(Some(path.to_owned()), error),
+            Self::Invalid(_, problems) => Self::Invalid(Some(path.to_owned()), problems),
+            error => error,
+        }
+    }
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let describe = |path: &Option<PathBuf>| match path {
+            Some(path) => format!("config file {}", path.to_string_lossy()),
+            None => "config".into(),
+        };
+
+        match self {
+            Self::Io(path, error) => write!(
+                f,
+                "Could not read config file {}: {error}",
+                path.to_string_lossy()
+            ),
+            Self::Syntax(path, error) =>
+                write!(f, "Could not parse {}: {error}", describe(path)),
+            Self::Invalid(path, problems) => {
+                write!(f, "Invalid {}:", describe(path))?;
+
+                for problem in problems {
+                    write!(f, "\n  - {problem}")?;
+                }
+
+                Ok(())
+            },
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+/// Finds what the key at `path` has to be in [`KEYS`].
+fn kind_of(path: &[&str]) -> Option<Kind> {
+    KEYS.iter()
+        .find(|(name, _)| {
+            name.split('.').count() == path.len()
+                && name
+                    .split('.')
+                    .zip(path)
+                    .all(|(pattern, key)| pattern == "*" || pattern == *key)
+        })
+        .map(|(_, kind)| *kind)
+}
+
+fn validate_table<'a>(
+    table: &'a Table,
+    prefix: &[&'a str],
+    problems: &mut Vec<ConfigProblem>,
+) {
+    for (key, value) in table {
+        let mut path = prefix.to_vec();
+        path.push(key);
+
+        let kind = if let Some(kind) = kind_of(&path) {
+            kind
+        } else {
+            problems.push(ConfigProblem {
+                key: path.join("."),
+                message: "unknown key".into(),
+            });
+            continue;
+        };
+
+        if let Err(message) = kind.check(value) {
+            problems.push(ConfigProblem {
+                key: path.join("."),
+                message,
+            });
+        } else if let Value::Table(table) = value {
+            validate_table(table, &path, problems);
+        }
+    }
+}
+
+/// Checks every key in `config` and that the tables have the keys they can't
+/// do without.
+fn check_keys(config: &Table) -> Vec<ConfigProblem> {
+    let mut problems = vec![];
+
+    validate_table(config, &[], &mut problems);
+
+    if let Some(Value::Table(syslog)) = config.get("syslog") {
+        if !syslog.contains_key("port") {
+            problems.push(ConfigProblem {
+                key: "syslog.port".into(),
+                message: "missing, the syslog listener needs a port".into(),
+            });
+        }
+    }
+
+    if let Some(Value::Table(tls)) = config.get("tls") {
+        for key in ["cert", "key"] {
+            if !tls.contains_key(key) {
+                problems.push(ConfigProblem {
+                    key: format!("tls.{key}"),
+                    message: "missing, TLS needs a certificate and its key".into(),
+                });
+            }
+        }
+    }
+
+    if let Some(Value::Table(oauth)) = config.get("oauth") {
+        for (name, provider) in oauth {
+            if matches!(provider, Value::Table(provider) if !provider.contains_key("client_id"))
+            {
+                problems.push(ConfigProblem {
+                    key: format!("oauth.{name}.client_id"),
+                    message: "missing, every provider needs a client ID".into(),
+                });
+            }
+        }
+    }
+
+    problems
+}
+
+impl ServerConfig {
+    /// Parses a config and checks its keys, without applying any overrides.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::Syntax`] if `config` isn't valid TOML, and
+    /// [`ConfigError::Invalid`] with every bad key if it isn't a valid config.
+    pub fn from_toml(config: &str) -> Result<Self, ConfigError> {
+        let table: Table =
+            toml::from_str(config).map_err(|error| ConfigError::Syntax(None, error))?;
+
+        let problems = check_keys(&table);
+
+        if !problems.is_empty() {
+            return Err(ConfigError::Invalid(None, problems));
+        }
+
+        Value::Table(table)
+            .try_into()
+            .map_err(|error| ConfigError::Syntax(None, error))
+    }
+
+    /// Loads the config file at `path`, or if it isn't given the one in the
+    /// [`CONFIG_ENV_VAR`] environment variable, or failing that `config.toml`
+    /// in the platform's config directory if it exists. The environment
+    /// variables are then applied on top of it, see
+    /// [`ServerConfig::apply_env`]. [`ServerConfig::validate`] has yet to be
+    /// called on it, as the caller may still override it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a config file that was asked for can't be read, or
+    /// if the config file isn't valid.
+    pub fn load(path: Option<&Path>) -> Result<Self, ConfigError> {
+        dotenv().ok();
+
+        let path = path
+            .map(Path::to_owned)
+            .or_else(|| env::var_os(CONFIG_ENV_VAR).map(PathBuf::from));
+
+        let path = match path {
+            Some(path) => Some(path),
+            None => project_dirs()
+                .map(|dirs| dirs.config_dir().join("config.toml"))
+                .filter(|path| path.exists()),
+        };
+
+        let mut config = if let Some(path) = path {
+            info!("Loading config file {}", path.to_string_lossy());
+
+            let config = fs::read_to_string(&path)
+                .map_err(|error| ConfigError::Io(path.clone(), error))?;
+
+            Self::from_toml(&config).map_err(|error| error.with_path(&path))?
+        } else {
+            Self::default()
+        };
+
+        config.apply_env();
+
+        Ok(config)
+    }
+
+    /// Overrides the config with the environment variables that are set:
+    /// `HOST`, `PORT`, `HTTP_API` (`0` or `1`), `HTTP_API_PORT`, `LISTEN_TCP`
+    /// (`0` or `1`),
+    /// `UNIX_SOCKET`, `UNIX_SOCKET_MODE` (in octal), `DATA_DIR`,
+    /// `CENSOR_USERNAMES` (`0` or `1`), `REQUIRE_AUTHENTICATION` (`0` or
+    /// `1`), `REDIRECT_PORT`, `TOKEN_SECRET`, the `RETENTION_*` variables
+    /// (see [`RetentionPolicy::from_env`]), the `SYSLOG_*` variables, which
+    /// replace the `[syslog]` table (see [`SyslogConfig::from_env`]), the
+    /// `TLS_*` variables, which replace the `[tls]` table (see
+    /// [`TlsConfig::from_env`]), `OAUTH_PROVIDERS` and the variables of the
+    /// providers in it, which replace the `[oauth]` table (see
+    /// [`ProviderConfig::all_from_env`]), and `PROJECT_MEMBERS`, which
+    /// replaces the `[projects]` table (see [`ProjectMembers::parse`]).
+    /// Variables with invalid values are ignored with a warning.
+    pub fn apply_env(&mut self) {
+        if let Some(host) = parse_env_var::<IpAddr>("HOST") {
+            self.listen.host = host.to_string();
+        }
+
+        if let Some(port) = parse_env_var("PORT") {
+            self.listen.port = port;
+        }
+
+        if let Some(http_api) = parse_env_var::<u8>("HTTP_API") {
+            self.listen.http_api = http_api != 0;
+        }
+
+        if let Some(port) = parse_env_var("HTTP_API_PORT") {
+            self.listen.http_api_port = port;
+        }
+
+        if let Some(tcp) = parse_env_var::<u8>("LISTEN_TCP") {
+            self.listen.tcp = tcp != 0;
+        }
+
+        if let Some(path) = env::var_os("UNIX_SOCKET").filter(|path| !path.is_empty()) {
+            self.listen.unix_socket = Some(path.into());
+        }
+
+        if let Ok(mode) = env::var("UNIX_SOCKET_MODE") {
+            if let Some(mode) = unix_socket::parse_mode(&mode) {
+                self.listen.unix_socket_mode = Some(mode);
+            } else {
+                warn!(
+                    "UNIX_SOCKET_MODE was found but \"{mode}\" is not an octal file \
+                     mode, ignoring it!"
+                );
+            }
+        }
+
+        if let Some(data_dir) = env::var_os("DATA_DIR").filter(|dir| !dir.is_empty()) {
+            self.data_dir = Some(data_dir.into());
+        }
+
+        if let Some(censor_usernames) = parse_env_var::<u8>("CENSOR_USERNAMES") {
+            self.censor_usernames = censor_usernames != 0;
+        }
+
+        if let Some(required) = parse_env_var::<u8>("REQUIRE_AUTHENTICATION") {
+            self.auth.required = required != 0;
+        }
+
+        if let Some(port) = parse_env_var("REDIRECT_PORT") {
+            self.auth.redirect_port = port;
+        }
+
+        match env::var("TOKEN_SECRET") {
+            Ok(secret) if secret.is_empty() =>
+                warn!("TOKEN_SECRET was found but was empty, ignoring it!"),
+            Ok(secret) => self.auth.token_secret = Some(secret),
+            Err(_) => (),
+        }
+
+        let retention = RetentionPolicy::from_env();
+
+        if let Some(max_logs) = retention.max_count {
+            self.retention.max_logs = Some(max_logs);
+        }
+
+        if let Some(max_age) = retention.max_age {
+            self.retention.max_age = Some(max_age.as_secs());
+        }
+
+        if let Some(max_bytes) = retention.max_bytes {
+            self.retention.max_bytes = Some(max_bytes);
+        }
+
+        if let Some(syslog) = SyslogConfig::from_env() {
+            self.syslog = Some(syslog);
+        }
+
+        if let Some(tls) = TlsConfig::from_env() {
+            self.tls = Some(tls);
+        }
+
+        if env::var_os("OAUTH_PROVIDERS").is_some() {
+            self.oauth = Some(ProviderConfig::all_from_env());
+        }
+
+        if env::var_os("PROJECT_MEMBERS").is_some() {
+            self.projects = ProjectMembers::from_env();
+        }
+    }
+
+    /// Checks the keys that only make sense together. This has to be done
+    /// after the environment variables and command line options are applied,
+    /// as they can fill in what the config file is missing, i.e. the Unix
+    /// socket that is needed when `listen.tcp` is turned off.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::Invalid`] with every problem that was found.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut problems = vec![];
+
+        if !self.listen.tcp && self.listen.unix_socket.is_none() {
+            problems.push(ConfigProblem {
+                key: "listen.tcp".into(),
+                message: "the gRPC server needs a Unix socket to listen on if tcp is \
+                          turned off"
+                    .into(),
+            });
+        }
+
+        if matches!(&self.syslog, Some(syslog) if !syslog.udp && !syslog.tcp) {
+            problems.push(ConfigProblem {
+                key: "syslog".into(),
+                message: "neither udp nor tcp is enabled, remove the table to disable \
+                          the syslog listener"
+                    .into(),
+            });
+        }
+
+        for provider in self.oauth.iter().flatten() {
+            let mut missing = |key: &str, message: &str| {
+                problems.push(ConfigProblem {
+                    key: format!("oauth.{}.{key}", provider.name),
+                    message: format!("missing, {message}"),
+                });
+            };
+
+            match provider.kind {
+                ProviderKind::Oidc if provider.base_url.is_none() =>
+                    missing("base_url", "oidc providers need the URL of their issuer"),
+                ProviderKind::OAuth2 => {
+                    for (key, url) in [
+                        ("auth_url", &provider.auth_url),
+                        ("token_url", &provider.token_url),
+                        ("user_url", &provider.user_url),
+                    ] {
+                        if url.is_none() {
+                            missing(key, "oauth2 providers have no default endpoints");
+                        }
+                    }
+                },
+                _ => (),
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(None, problems))
+        }
+    }
+
+    /// The providers that users can log in with, from the `[oauth]` table or
+    /// `OAUTH_PROVIDERS`, or if neither is set, from the environment variables
+    /// of the `github` provider.
+    #[must_use]
+    pub fn oauth_providers(&self) -> Vec<ProviderConfig> {
+        self.oauth
+            .clone()
+            .unwrap_or_else(ProviderConfig::all_from_env)
+    }
+
+    /// Where the database is kept, which defaults to the platform's data
+    /// directory.
+    #[must_use]
+    pub fn data_dir(&self) -> PathBuf {
+        if let Some(data_dir) = &self.data_dir {
+            data_dir.clone()
+        } else if let Some(dirs) = project_dirs() {
+            dirs.data_dir().to_owned()
+        } else {
+            Path::new(".codectrl-server").to_owned()
+        }
+    }
+}
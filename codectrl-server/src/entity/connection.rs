@@ -9,7 +9,13 @@ use sea_orm::entity::prelude::*;
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
     pub uuid: String,
+    /// The UUIDs of the logs sent down the connection, as JSON. Only written by
+    /// older versions of the server, see `last_sent_log_id`.
     pub sent_logs: Option<String>,
+    /// The subject of the token that registered the connection, if any.
+    pub owner: Option<String>,
+    /// The ID of the newest log sent down the connection.
+    pub last_sent_log_id: Option<i64>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter)]
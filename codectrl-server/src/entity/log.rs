@@ -0,0 +1,134 @@
+#![allow(clippy::derive_partial_eq_without_eq)]
+
+//! The logs that the server has received, so that they outlive the server.
+
+use crate::{fields, StoredLog};
+use codectrl_protobuf_bindings::data::Log;
+use prost::Message;
+use sea_orm::{entity::prelude::*, Set};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "log")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    #[sea_orm(unique)]
+    pub uuid: String,
+    pub message: String,
+    pub message_type: String,
+    pub file_name: String,
+    pub line_number: u32,
+    pub address: String,
+    pub language: String,
+    pub stack: String,
+    pub code_snippet: String,
+    pub warnings: String,
+    pub received_at: DateTimeUtc,
+    pub size: i64,
+    pub project: Option<String>,
+    pub severity: Option<String>,
+    pub fields: Option<String>,
+    pub client_subject: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef { panic!("No RelationDef") }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+// The stack, code snippet, warnings and fields of a log don't map onto SQLite
+// columns very well, so they're stored as JSON strings instead and
+// (de)serialised when converting between the protobuf `Log` and the database
+// row.
+
+impl ActiveModel {
+    /// Creates a new row from a verified log.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stack, code snippet, warnings or fields could
+    /// not be serialised to JSON.
+    pub fn from_stored_log(stored: &StoredLog) -> Result<Self, serde_json::Error> {
+        let log = &stored.log;
+
+        Ok(Self {
+            uuid: Set(log.uuid.clone()),
+            message: Set(log.message.clone()),
+            message_type: Set(log.message_type.clone()),
+            file_name: Set(log.file_name.clone()),
+            line_number: Set(log.line_number),
+            address: Set(log.address.clone()),
+            language: Set(log.language.clone()),
+            stack: Set(serde_json::to_string(&log.stack)?),
+            code_snippet: Set(serde_json::to_string(&log.code_snippet)?),
+            warnings: Set(serde_json::to_string(&log.warnings)?),
+            received_at: Set(stored.received_at),
+            size: Set(i64::try_from(log.encoded_len()).unwrap_or(i64::MAX)),
+            project: Set(stored.project.clone()),
+            severity: Set(Some(stored.severity.to_string())),
+            fields: Set(Some(serde_json::to_string(&stored.fields)?)),
+            client_subject: Set(stored.client_subject.clone()),
+            ..Default::default()
+        })
+    }
+}
+
+impl TryFrom<Model> for Log {
+    type Error = serde_json::Error;
+
+    fn try_from(model: Model) -> Result<Self, Self::Error> {
+        Ok(Self {
+            uuid: model.uuid,
+            message: model.message,
+            message_type: model.message_type,
+            file_name: model.file_name,
+            line_number: model.line_number,
+            address: model.address,
+            language: model.language,
+            stack: serde_json::from_str(&model.stack)?,
+            code_snippet: serde_json::from_str(&model.code_snippet)?,
+            warnings: serde_json::from_str(&model.warnings)?,
+            ..Default::default()
+        })
+    }
+}
+
+impl TryFrom<Model> for StoredLog {
+    type Error = serde_json::Error;
+
+    fn try_from(model: Model) -> Result<Self, Self::Error> {
+        let id = model.id;
+        let received_at = model.received_at;
+        let project = model.project.clone();
+        // Logs stored before severities were added count as `info`.
+        let severity = model
+            .severity
+            .as_deref()
+            .and_then(|severity| severity.parse().ok())
+            .unwrap_or_default();
+        let fields = model.fields.clone();
+        let client_subject = model.client_subject.clone();
+        let log = Log::try_from(model)?;
+
+        // Logs stored before fields were added only have the fields of their
+        // message.
+        let fields = match fields {
+            Some(fields) => serde_json::from_str(&fields)?,
+            None => fields::from_message(&log.message).unwrap_or_default(),
+        };
+
+        Ok(Self {
+            id,
+            log,
+            project,
+            severity,
+            fields,
+            client_subject,
+            received_at,
+        })
+    }
+}
@@ -5,3 +5,5 @@
 pub mod prelude;
 
 pub mod connection;
+pub mod log;
+pub mod revoked_token;
@@ -2,4 +2,7 @@
 
 //! SeaORM Entity. Generated by sea-orm-codegen 0.9.1
 
-pub use super::connection::Entity as Connection;
+pub use super::{
+    connection::Entity as Connection, log::Entity as Log,
+    revoked_token::Entity as RevokedToken,
+};
@@ -0,0 +1,23 @@
+#![allow(clippy::derive_partial_eq_without_eq)]
+
+//! The tokens that were revoked before they expired, see `Service::revoke`.
+
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "revoked_token")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: String,
+    pub subject: String,
+    pub expires_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef { panic!("No RelationDef") }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,38 @@
+// region: imports
+
+use codectrl_protobuf_bindings::data::Log;
+use serde_json::Value;
+use tonic::{metadata::MetadataMap, Status};
+
+// endregion
+
+pub use codectrl_common::fields::{
+    from_message, lookup, value_to_string, FieldFilter, Fields, FIELDS_HEADER,
+};
+
+/// Collects the fields of a received `log`, from its message and the
+/// `x-fields` header. The header wins when both have the same field.
+///
+/// # Errors
+///
+/// Returns `Status::invalid_argument` if the header isn't a JSON object.
+pub fn of(log: &Log, metadata: &MetadataMap) -> Result<Fields, Status> {
+    let mut fields = from_message(&log.message).unwrap_or_default();
+
+    if let Some(header) = metadata.get(FIELDS_HEADER) {
+        let header = header
+            .to_str()
+            .ok()
+            .and_then(|header| serde_json::from_str::<Value>(header).ok());
+
+        match header {
+            Some(Value::Object(header)) => fields.extend(header),
+            _ =>
+                return Err(Status::invalid_argument(format!(
+                    "The {FIELDS_HEADER} header is not a JSON object"
+                ))),
+        }
+    }
+
+    Ok(fields)
+}
@@ -0,0 +1,417 @@
+// region: imports
+
+use crate::{
+    metrics::{LogSource, METRICS},
+    tls::{certificate_subject, TlsConfig},
+    Service,
+};
+use anyhow::Context;
+use codectrl_protobuf_bindings::data::{Log, LogData};
+use futures::{future::BoxFuture, FutureExt};
+use hyper::{
+    server::conn::Http,
+    service::{service_fn, Service as HyperService},
+    Body,
+};
+use log::{error, info, warn};
+use prometheus::TEXT_FORMAT;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+    net::{SocketAddr, TcpListener as StdTcpListener},
+    sync::Arc,
+};
+use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
+use tonic::{metadata::MetadataMap, Code, Status};
+use warp::{
+    body::BodyDeserializeError,
+    http::{HeaderMap, Request as HttpRequest, Response as HttpResponse, StatusCode},
+    reject::{MethodNotAllowed, PayloadTooLarge},
+    reply::{self, Json, WithStatus},
+    Filter, Rejection,
+};
+
+// endregion
+
+/// The largest request body the HTTP API accepts.
+pub const MAX_BODY_SIZE: u64 = 4 * 1024 * 1024;
+
+/// A stack frame of a [`JsonLog`], mirroring `LogData`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct JsonLogData {
+    pub file_path: String,
+    pub line_number: u32,
+    pub column_number: u32,
+    pub code: String,
+}
+
+impl From<JsonLogData> for LogData {
+    fn from(data: JsonLogData) -> Self {
+        Self {
+            file_path: data.file_path,
+            line_number: data.line_number,
+            column_number: data.column_number,
+            code: data.code,
+            ..Default::default()
+        }
+    }
+}
+
+/// A log as sent to `POST /api/logs`, mirroring the `Log` message. Every field
+/// is optional so that loggers only have to send what they know, the server
+/// fills in or warns about the rest like it does for `gRPC` loggers.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct JsonLog {
+    pub message: String,
+    pub message_type: String,
+    pub file_name: String,
+    pub line_number: u32,
+    pub language: String,
+    pub stack: Vec<JsonLogData>,
+    pub code_snippet: BTreeMap<u32, String>,
+    pub warnings: Vec<String>,
+}
+
+impl From<JsonLog> for Log {
+    fn from(log: JsonLog) -> Self {
+        Self {
+            message: log.message,
+            message_type: log.message_type,
+            file_name: log.file_name,
+            line_number: log.line_number,
+            language: log.language,
+            stack: log.stack.into_iter().map(LogData::from).collect(),
+            code_snippet: log.code_snippet.into_iter().collect(),
+            warnings: log.warnings,
+            ..Default::default()
+        }
+    }
+}
+
+/// The body of `POST /api/logs`: either a single log, or an array of logs that
+/// are stored like `send_logs` would.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum JsonLogs {
+    One(JsonLog),
+    Many(Vec<JsonLog>),
+}
+
+impl JsonLogs {
+    #[must_use]
+    pub fn into_logs(self) -> Vec<Log> {
+        match self {
+            Self::One(log) => vec![log.into()],
+            Self::Many(logs) => logs.into_iter().map(Log::from).collect(),
+        }
+    }
+}
+
+/// The connection that a request to the HTTP API came in over. Attached to the
+/// request's extensions when the HTTP API is served over TLS, as warp only
+/// knows the remote address of connections it accepted itself.
+#[derive(Debug, Clone, Default)]
+struct Connection {
+    remote_addr: Option<SocketAddr>,
+    /// The subject of the client's certificate under mutual TLS, see
+    /// [`certificate_subject`].
+    client_subject: Option<String>,
+}
+
+impl Connection {
+    /// Gets the connection that the TLS server attached to the request, or
+    /// the remote address from warp if there is none.
+    fn filter() -> impl Filter<Extract = (Self,), Error = Infallible> + Clone {
+        warp::addr::remote().and(warp::ext::optional::<Self>()).map(
+            |remote_addr, connection: Option<Self>| {
+                connection.unwrap_or(Self {
+                    remote_addr,
+                    client_subject: None,
+                })
+            },
+        )
+    }
+}
+
+/// The JSON form of a `RequestResult`.
+#[derive(Debug, Clone, Serialize)]
+struct JsonRequestResult {
+    message: String,
+    status: &'static str,
+}
+
+/// Maps the `gRPC` status codes that sending a log can fail with onto their
+/// closest HTTP equivalent.
+#[must_use]
+pub fn status_code(status: &Status) -> StatusCode {
+    match status.code() {
+        Code::Ok => StatusCode::OK,
+        Code::InvalidArgument | Code::FailedPrecondition | Code::OutOfRange =>
+            StatusCode::BAD_REQUEST,
+        Code::Unauthenticated => StatusCode::UNAUTHORIZED,
+        Code::PermissionDenied => StatusCode::FORBIDDEN,
+        Code::NotFound => StatusCode::NOT_FOUND,
+        Code::AlreadyExists => StatusCode::CONFLICT,
+        Code::ResourceExhausted => StatusCode::PAYLOAD_TOO_LARGE,
+        Code::Unimplemented => StatusCode::NOT_IMPLEMENTED,
+        Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        Code::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+fn result_reply(result: Result<String, Status>) -> WithStatus<Json> {
+    let (status, body) = match result {
+        Ok(message) => (
+            StatusCode::OK,
+            JsonRequestResult {
+                message,
+                status: "CONFIRMED",
+            },
+        ),
+        Err(status) => (
+            status_code(&status),
+            JsonRequestResult {
+                message: status.message().into(),
+                status: "ERROR",
+            },
+        ),
+    };
+
+    reply::with_status(reply::json(&body), status)
+}
+
+/// Replies to requests that warp rejected (i.e. invalid JSON) in the same
+/// format as every other reply.
+#[allow(clippy::unused_async)]
+async fn handle_rejection(rejection: Rejection) -> Result<WithStatus<Json>, Infallible> {
+    let status = if rejection.is_not_found() {
+        Status::not_found("Not found")
+    } else if let Some(error) = rejection.find::<BodyDeserializeError>() {
+        Status::invalid_argument(format!("Invalid log: {error}"))
+    } else if rejection.find::<PayloadTooLarge>().is_some() {
+        Status::resource_exhausted(format!("Request body exceeds {MAX_BODY_SIZE} bytes"))
+    } else if rejection.find::<MethodNotAllowed>().is_some() {
+        Status::unimplemented("Logs can only be sent using POST")
+    } else {
+        Status::invalid_argument(format!("{rejection:?}"))
+    };
+
+    Ok(result_reply(Err(status)))
+}
+
+impl Service {
+    /// Verifies and stores logs sent to the HTTP API, the same way as
+    /// `send_log` and `send_logs` do for `gRPC` loggers. A batch is only stored
+    /// if every log in it is valid, and if storing fails partway the error says
+    /// how many of the logs were stored.
+    async fn receive_json_logs(
+        &self,
+        logs: JsonLogs,
+        connection: Connection,
+        headers: HeaderMap,
+    ) -> Result<String, Status> {
+        let remote_addr = connection.remote_addr;
+        let metadata = MetadataMap::from_headers(headers);
+
+        // Does what the interceptor on the client service does for `gRPC`.
+        let claims = match self.auth_interceptor(true).verify_metadata(&metadata) {
+            Ok(claims) => Some(claims),
+            Err(status) if self.requires_authentication => return Err(status),
+            Err(_) => None,
+        };
+
+        let logs = logs.into_logs();
+        let amount = logs.len();
+
+        // Every log is checked before any is stored, so that an invalid log
+        // rejects the whole batch and retrying it doesn't store the rest twice.
+        let logs = logs
+            .into_iter()
+            .enumerate()
+            .map(|(index, log)| {
+                let mut log =
+                    Self::verify_log(log, remote_addr, &metadata, claims.as_ref())
+                        .map_err(|status| {
+                            Status::new(
+                                status.code(),
+                                format!("Log {index}: {}", status.message()),
+                            )
+                        })?;
+                log.client_subject = connection.client_subject.clone();
+
+                Ok(log)
+            })
+            .collect::<Result<Vec<_>, Status>>()?;
+
+        for (stored, log) in logs.into_iter().enumerate() {
+            self.store_log(log, LogSource::HttpApi)
+                .await
+                .map_err(|status| {
+                    Status::new(
+                        status.code(),
+                        format!(
+                            "Only the first {stored} of {amount} log(s) were stored: {}",
+                            status.message()
+                        ),
+                    )
+                })?;
+        }
+
+        info!(target: "codectrl_server - http api", "{amount} log(s) received from {}", remote_addr.map_or_else(|| "Unknown".into(), |addr| addr.to_string()));
+
+        Ok(if amount == 1 {
+            "Log added!".into()
+        } else {
+            format!("{amount} logs added!")
+        })
+    }
+
+    /// The metrics in the Prometheus text format, see [`crate::metrics`].
+    async fn render_metrics(&self) -> String {
+        METRICS.render(self.connections.read().await.len(), self.log_sender.len())
+    }
+
+    /// Binds the HTTP API to `addr` and returns the future that serves it. It
+    /// is served over TLS with the same certificate as the `gRPC` server if
+    /// `tls` is given, and clients have to present a certificate signed by
+    /// its client CA if it has one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `addr` could not be bound to, or if the TLS files
+    /// can't be read.
+    pub fn http_api(
+        &self,
+        addr: SocketAddr,
+        tls: Option<&TlsConfig>,
+    ) -> anyhow::Result<(SocketAddr, BoxFuture<'static, ()>)> {
+        let service = self.clone();
+
+        let send_logs = warp::path!("api" / "logs")
+            .and(warp::post())
+            .and(warp::body::content_length_limit(MAX_BODY_SIZE))
+            .and(warp::body::json::<JsonLogs>())
+            .and(Connection::filter())
+            .and(warp::header::headers_cloned())
+            .and_then(move |logs, connection, headers| {
+                let service = service.clone();
+
+                async move {
+                    let result = service
+                        .receive_json_logs(logs, connection, headers)
+                        .await;
+
+                    if let Err(status) = &result {
+                        error!(target: "codectrl_server - http api", "Could not store log(s): {}", status.message());
+                    }
+
+                    Ok::<_, Infallible>(result_reply(result))
+                }
+            });
+
+        let service = self.clone();
+
+        // Unauthenticated like the server details, so that Prometheus can scrape
+        // it without a token.
+        let metrics = warp::path!("metrics").and(warp::get()).and_then(move || {
+            let service = service.clone();
+
+            async move {
+                Ok::<_, Infallible>(reply::with_header(
+                    service.render_metrics().await,
+                    "content-type",
+                    TEXT_FORMAT,
+                ))
+            }
+        });
+
+        let routes = send_logs.or(metrics).recover(handle_rejection);
+
+        let (addr, server) = if let Some(tls) = tls {
+            // warp panics rather than returning an error if it can't use the TLS
+            // files or bind to `addr`, and can't tell who the client is under
+            // mutual TLS, so TLS connections are accepted here instead.
+            let acceptor = TlsAcceptor::from(Arc::new(tls.server_rustls_config()?));
+            let listener = StdTcpListener::bind(addr)
+                .and_then(|listener| {
+                    listener.set_nonblocking(true)?;
+                    TcpListener::from_std(listener)
+                })
+                .with_context(|| format!("Could not bind the HTTP API to {addr}"))?;
+            let addr = listener.local_addr()?;
+
+            (
+                addr,
+                serve_tls(listener, acceptor, warp::service(routes)).boxed(),
+            )
+        } else {
+            let (addr, server) = warp::serve(routes).try_bind_ephemeral(addr)?;
+
+            (addr, server.boxed())
+        };
+
+        info!(target: "codectrl_server - http api", "Listening for JSON logs on {addr}");
+
+        Ok((addr, server))
+    }
+}
+
+/// Accepts TLS connections on `listener` and serves `service` on each of them,
+/// with the [`Connection`] attached to every request.
+async fn serve_tls<S>(listener: TcpListener, acceptor: TlsAcceptor, service: S)
+where
+    S: HyperService<HttpRequest<Body>, Response = HttpResponse<Body>, Error = Infallible>
+        + Clone
+        + Send
+        + 'static,
+    S::Future: Send,
+{
+    loop {
+        let (stream, remote_addr) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(error) => {
+                error!(target: "codectrl_server - http api", "Could not accept a connection: {error}");
+                continue;
+            },
+        };
+
+        let acceptor = acceptor.clone();
+        let mut service = service.clone();
+
+        tokio::spawn(async move {
+            let stream = match acceptor.accept(stream).await {
+                Ok(stream) => stream,
+                Err(error) => {
+                    warn!(target: "codectrl_server - http api", "TLS handshake with {remote_addr} failed: {error}");
+                    return;
+                },
+            };
+
+            let connection = Connection {
+                remote_addr: Some(remote_addr),
+                // The first certificate is the client's own, the rest are the
+                // chain that signed it.
+                client_subject: stream
+                    .get_ref()
+                    .1
+                    .peer_certificates()
+                    .and_then(<[_]>::first)
+                    .and_then(|cert| certificate_subject(&cert.0)),
+            };
+
+            let service = service_fn(move |mut request| {
+                request.extensions_mut().insert(connection.clone());
+                service.call(request)
+            });
+
+            if let Err(error) = Http::new().serve_connection(stream, service).await {
+                warn!(target: "codectrl_server - http api", "Error while serving {remote_addr}: {error}");
+            }
+        });
+    }
+}
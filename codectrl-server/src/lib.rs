@@ -1,10 +1,31 @@
 #![warn(clippy::pedantic)]
 
+pub mod admin;
+pub mod auth;
+pub mod config;
 mod entity;
+pub mod fields;
+pub mod http_api;
+pub mod metrics;
+pub mod oauth;
+pub mod otlp;
+pub mod project;
+pub mod proto;
+pub mod query;
 pub mod redirect_handler;
+pub mod retention;
+pub mod severity;
+pub mod syslog;
+pub mod tls;
+pub mod unix_socket;
 
 // region: imports
 
+use auth::{
+    AuthInterceptor, Identity, TokenClaims, TokenSecret, ANONYMOUS_SUBJECT,
+    DEFAULT_TOKEN_LIFETIME,
+};
+use chrono::{DateTime, TimeZone, Utc};
 use codectrl_protobuf_bindings::{
     auth_service::{
         authentication_server::{Authentication, AuthenticationServer},
@@ -17,44 +38,76 @@ use codectrl_protobuf_bindings::{
         RequestResult, RequestStatus, ServerDetails,
     },
 };
+use config::ServerConfig;
 use dashmap::{DashMap, DashSet};
-use directories::ProjectDirs;
 use dotenv::dotenv;
-use entity::connection::{ActiveModel, Entity};
-use futures::StreamExt;
-use log::{error, info, trace, warn};
-use oauth2::{
-    basic::BasicClient, AuthUrl, ClientId, ClientSecret, CsrfToken, RedirectUrl, Scope,
-    TokenUrl,
+use entity::{
+    connection::{ActiveModel, Entity},
+    log::{ActiveModel as LogActiveModel, Column as LogColumn, Entity as LogEntity},
+    revoked_token::{
+        ActiveModel as RevokedTokenActiveModel, Column as RevokedTokenColumn,
+        Entity as RevokedTokenEntity,
+    },
+};
+use fields::Fields;
+use futures::{
+    future::{self, BoxFuture},
+    FutureExt, StreamExt,
 };
+use log::{error, info, trace, warn};
+use metrics::{LogSource, LogWarning, METRICS};
+use oauth::{ProviderConfig, ProviderKind};
 use once_cell::{race::OnceBool, sync::OnceCell};
+use project::ProjectScope;
+use prost::Message;
+use proto::{
+    log_search::{
+        log_search_server::{LogSearch, LogSearchServer},
+        LogPage, LogQuery, QueriedLog,
+    },
+    login_providers::{
+        login_providers_server::{LoginProviders, LoginProvidersServer},
+        CollectTokenRequest, CollectTokenResult, LoginRequest, Provider, ProviderList,
+        ProviderLoginUrl,
+    },
+    opentelemetry::proto::collector::logs::v1::logs_service_server::LogsServiceServer,
+};
+use query::LogMatcher;
 use rand::{
     distributions::{Alphanumeric, DistString},
     thread_rng,
 };
+use redirect_handler::RedirectHandler;
 use regex::Regex;
+use retention::{RetentionPolicy, RetentionRow, RetentionTotals, RETENTION_PAGE_SIZE};
 use sea_orm::{
-    ActiveModelTrait, ActiveValue::NotSet, ConnectionTrait, Database, DatabaseConnection,
-    EntityTrait, Schema, Set,
+    ActiveModelTrait, ActiveValue::NotSet, ColumnTrait, Condition, ConnectionTrait,
+    Database, DatabaseConnection, DbErr, EntityTrait, FromQueryResult, QueryFilter,
+    QueryOrder, QuerySelect, Schema, Set, Statement,
 };
-use serde::{Deserialize, Serialize};
+use severity::Severity;
 use std::{
     borrow::Cow,
     collections::VecDeque,
-    env,
-    fs::{self, File},
+    fs,
     net::SocketAddr,
     path::Path,
     sync::Arc,
     time::{Duration, Instant},
 };
 use tokio::{
-    sync::{mpsc, RwLock},
+    runtime::Handle,
+    sync::{
+        broadcast::{self, error::RecvError},
+        mpsc, Mutex, RwLock,
+    },
     time::sleep_until,
 };
 use tokio_stream::wrappers::ReceiverStream;
 use tonic::{
-    metadata::MetadataMap, transport::Server, Code, Request, Response, Status, Streaming,
+    metadata::MetadataMap,
+    transport::{server::Router, Error as TransportError, Server},
+    Code, Request, Response, Status, Streaming,
 };
 use uuid::Uuid;
 
@@ -66,42 +119,88 @@ static CENSOR_USERNAMES: OnceBool = OnceBool::new();
 // the server, but "caching" them inside a lazy initialised static should
 // definitely be faster.
 static USERNAME_REGEXES: OnceCell<[Result<Regex, regex::Error>; 4]> = OnceCell::new();
-static REDIRECT_HANDLER_PORT: OnceCell<u16> = OnceCell::new();
 
 // region: ConnectionState
 #[derive(Debug, Clone)]
 pub struct ConnectionState {
     last_update: Instant,
-    sent_log_ids: DashSet<String>,
+    /// The ID of the newest log sent down the connection. Logs are always sent
+    /// oldest first, so every log up to it has been sent.
+    last_sent_log_id: i64,
+    owner: Option<String>,
 }
 
 impl Default for ConnectionState {
     fn default() -> Self {
         Self {
             last_update: Instant::now(),
-            sent_log_ids: DashSet::new(),
+            last_sent_log_id: 0,
+            owner: None,
         }
     }
 }
 
 impl ConnectionState {
-    pub fn add_log(&mut self, log: &Log) {
-        self.sent_log_ids.insert(log.uuid.clone());
+    pub fn add_log(&mut self, id: i64) {
+        self.last_sent_log_id = self.last_sent_log_id.max(id);
         self.last_update = Instant::now();
     }
 }
 // endregion
 
+/// A log along with the project it belongs to, its severity and its structured
+/// fields, which aren't part of the `Log` message itself.
+#[derive(Debug, Clone)]
+pub struct StoredLog {
+    /// The ID of the log's row in the database, set once the log is stored.
+    pub id: i64,
+    pub log: Log,
+    pub project: Option<String>,
+    pub severity: Severity,
+    pub fields: Fields,
+    /// The subject of the client certificate that the log was sent with, if
+    /// the server requires one, see [`tls::client_subject`].
+    pub client_subject: Option<String>,
+    pub received_at: DateTime<Utc>,
+}
+
+impl From<StoredLog> for QueriedLog {
+    fn from(stored: StoredLog) -> Self {
+        Self {
+            log: Some(stored.log),
+            received_at: stored.received_at.timestamp_millis(),
+            project: stored.project.unwrap_or_default(),
+            severity: stored.severity.to_string(),
+            fields: serde_json::Value::Object(stored.fields).to_string(),
+            client_subject: stored.client_subject.unwrap_or_default(),
+        }
+    }
+}
+
+/// Describes where `request` came from for the server's own logs, as requests
+/// over the Unix socket have no remote address.
+fn peer_of<T>(request: &Request<T>) -> String {
+    request
+        .remote_addr()
+        .map_or_else(|| "the Unix socket".into(), |addr| addr.to_string())
+}
+
 // region: Service
 #[derive(Debug, Clone)]
 pub struct Service {
-    logs: Arc<RwLock<VecDeque<Log>>>,
     connections: Arc<RwLock<DashMap<String, ConnectionState>>>,
     host: String,
     port: u32,
     uptime: Instant,
     db_connection: Arc<DatabaseConnection>,
     requires_authentication: bool,
+    log_sender: broadcast::Sender<StoredLog>,
+    /// Held while a log is stored and broadcast, see [`Service::store_log`].
+    store_lock: Arc<Mutex<()>>,
+    token_secret: TokenSecret,
+    revoked_tokens: Arc<DashSet<String>>,
+    redirect_handler: Option<Arc<RedirectHandler>>,
+    oauth_providers: Arc<Vec<ProviderConfig>>,
 }
 
 impl Service {
@@ -118,20 +217,18 @@ impl Service {
 
                 for mut connection in connections.write().await.iter_mut() {
                     if connection.last_update.elapsed() >= Duration::new(5, 0) {
-                        let sent_logs = if let Ok(sent_log_ids) =
-                            serde_json::to_string(&connection.sent_log_ids)
-                        {
-                            Set(Some(sent_log_ids))
-                        } else {
-                            Set(None)
-                        };
-
                         let model = ActiveModel {
                             uuid: Set(connection.key().clone()),
-                            sent_logs,
+                            sent_logs: NotSet,
+                            owner: NotSet,
+                            last_sent_log_id: Set(Some(connection.last_sent_log_id)),
                         };
 
-                        if let Err(error) = model.update(db_connection.as_ref()).await {
+                        let started = Instant::now();
+                        let result = model.update(db_connection.as_ref()).await;
+                        METRICS.backup_written(started.elapsed(), result.is_err());
+
+                        if let Err(error) = result {
                             error!(target: "codectrl_server - background backup thread", "Error occurred while updating DB: {error}");
                         } else {
                             trace!(target: "codectrl_server - background backup thread", "Updated DB");
@@ -145,6 +242,176 @@ impl Service {
         info!("... Done!");
     }
 
+    /// Starts the thread that evicts logs according to `policy` and prunes
+    /// revoked tokens once they've expired.
+    pub fn start_retention_thread(&self, policy: RetentionPolicy) {
+        if policy.is_unbounded() {
+            info!("No log retention limits set, logs will be kept indefinitely");
+        }
+
+        let db_connection = Arc::clone(&self.db_connection);
+        let revoked_tokens = Arc::clone(&self.revoked_tokens);
+
+        info!("Starting background retention thread with {policy:?}...");
+
+        tokio::spawn(async move {
+            info!(target: "codectrl_server - background retention thread", "Running every 30 seconds");
+            loop {
+                if !policy.is_unbounded() {
+                    Self::evict_logs(&db_connection, &policy).await;
+                }
+
+                Self::prune_revoked_tokens(&db_connection, &revoked_tokens).await;
+
+                sleep_until(tokio::time::Instant::now() + Duration::new(30, 0)).await;
+            }
+        });
+
+        info!("... Done!");
+    }
+
+    /// Evicts the oldest logs until the rest satisfy `policy`.
+    async fn evict_logs(db_connection: &DatabaseConnection, policy: &RetentionPolicy) {
+        let mut totals = match RetentionTotals::find_by_statement(
+            RetentionTotals::statement(db_connection.get_database_backend()),
+        )
+        .one(db_connection)
+        .await
+        {
+            Ok(totals) => totals.unwrap_or_default(),
+            Err(error) => {
+                error!(target: "codectrl_server - background retention thread", "Error occurred while reading DB: {error}");
+                RetentionTotals::default()
+            },
+        };
+
+        let mut evicted_count = 0;
+
+        // Only the oldest logs are read, a page at a time, until the rest of the
+        // logs satisfy the policy.
+        loop {
+            let rows = match LogEntity::find()
+                .select_only()
+                .column(LogColumn::Id)
+                .column(LogColumn::Uuid)
+                .column(LogColumn::ReceivedAt)
+                .column(LogColumn::Size)
+                .order_by_asc(LogColumn::Id)
+                .limit(RETENTION_PAGE_SIZE)
+                .into_model::<RetentionRow>()
+                .all(db_connection)
+                .await
+            {
+                Ok(rows) => rows,
+                Err(error) => {
+                    error!(target: "codectrl_server - background retention thread", "Error occurred while reading DB: {error}");
+                    break;
+                },
+            };
+
+            let evicted = &rows[..policy.evict_count(&rows, totals, Utc::now())];
+
+            // Logs are always evicted oldest first, so everything up to and
+            // including the last evicted ID can be deleted in one go.
+            let last = if let Some(last) = evicted.last() {
+                last
+            } else {
+                break;
+            };
+
+            if let Err(error) = LogEntity::delete_many()
+                .filter(LogColumn::Id.lte(last.id))
+                .exec(db_connection)
+                .await
+            {
+                error!(target: "codectrl_server - background retention thread", "Error occurred while evicting logs: {error}");
+                break;
+            }
+
+            totals.evict(evicted);
+            evicted_count += evicted.len();
+
+            if evicted.len() < rows.len() {
+                break;
+            }
+        }
+
+        if evicted_count > 0 {
+            info!(target: "codectrl_server - background retention thread", "Evicted {evicted_count} log(s)");
+        }
+    }
+
+    /// Forgets the revoked tokens and sessions that have expired, as every
+    /// token they apply to would be rejected as expired anyway.
+    async fn prune_revoked_tokens(
+        db_connection: &DatabaseConnection,
+        revoked_tokens: &DashSet<String>,
+    ) {
+        let now = Utc::now();
+
+        let expired = match RevokedTokenEntity::find()
+            .filter(RevokedTokenColumn::ExpiresAt.lte(now))
+            .all(db_connection)
+            .await
+        {
+            Ok(expired) => expired,
+            Err(error) => {
+                error!(target: "codectrl_server - background retention thread", "Error occurred while reading DB: {error}");
+                return;
+            },
+        };
+
+        if expired.is_empty() {
+            return;
+        }
+
+        if let Err(error) = RevokedTokenEntity::delete_many()
+            .filter(RevokedTokenColumn::ExpiresAt.lte(now))
+            .exec(db_connection)
+            .await
+        {
+            error!(target: "codectrl_server - background retention thread", "Error occurred while pruning revoked tokens: {error}");
+            return;
+        }
+
+        for revoked_token in &expired {
+            revoked_tokens.remove(&revoked_token.id);
+        }
+
+        info!(target: "codectrl_server - background retention thread", "Pruned {} expired revoked token(s)", expired.len());
+    }
+
+    /// Routes every `gRPC` service to this service, with the interceptors that
+    /// check the caller's identity.
+    fn grpc_router(&self, server: &mut Server) -> Router {
+        // Anyone may ask for the server details (to find out whether they need
+        // to log in), so the log server service checks the caller's identity
+        // per method while the client service rejects anonymous loggers
+        // outright.
+        let server_service = LogServerService::with_interceptor(
+            self.clone(),
+            self.auth_interceptor(false),
+        );
+        let client_service =
+            LogClientService::with_interceptor(self.clone(), self.auth_interceptor(true));
+        let log_search_service =
+            LogSearchServer::with_interceptor(self.clone(), self.auth_interceptor(true));
+        let otlp_logs_service = LogsServiceServer::with_interceptor(
+            self.clone(),
+            self.auth_interceptor(true),
+        );
+        let login_providers_service = LoginProvidersServer::new(self.clone());
+        let auth_service = AuthenticationServer::new(self.clone());
+
+        server
+            .add_service(tonic_web::enable(server_service))
+            .add_service(tonic_web::enable(client_service))
+            .add_service(tonic_web::enable(auth_service))
+            .add_service(tonic_web::enable(login_providers_service))
+            .add_service(tonic_web::enable(log_search_service))
+            .add_service(tonic_web::enable(otlp_logs_service))
+    }
+
     fn strip_username_from_path(path: &str) -> Cow<str> {
         let path: Cow<str> = path.into();
 
@@ -181,33 +448,52 @@ impl Service {
             .into()
     }
 
+    /// Fills in and censors the details of a received `log`, works out which
+    /// project it belongs to from the sender's token or the `x-project` header,
+    /// and how severe it is from the `x-severity` header or its message type.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sender may not send logs for the project in the
+    /// `x-project` header, or has to give one but didn't, or if the
+    /// `x-severity` header is invalid.
     #[allow(clippy::missing_panics_doc)]
     pub fn verify_log(
-        log: &mut Log,
+        mut log: Log,
         remote_addr: Option<SocketAddr>,
         metadata: &MetadataMap,
-    ) {
+        claims: Option<&TokenClaims>,
+    ) -> Result<StoredLog, Status> {
+        let project = ProjectScope::of(claims, metadata)?.log_project()?;
+        let severity = severity::of(&log, metadata)?;
+        let fields = fields::of(&log, metadata)?;
+
         log.uuid = Uuid::new_v4().hyphenated().to_string();
 
         if log.message.len() > 1000 {
             log.warnings.push("Message exceeds 1000 characters".into());
+            METRICS.log_warning(LogWarning::MessageTooLong);
         }
 
         if log.message.is_empty() {
             log.warnings.push("No message was given".into());
+            METRICS.log_warning(LogWarning::NoMessage);
             log.message = "<None>".into();
         }
 
         if log.message_type.is_empty() {
             log.warnings.push("Message type was not supplied".into());
+            METRICS.log_warning(LogWarning::NoMessageType);
         }
 
         if log.stack.is_empty() {
             log.warnings.push("Stacktrace is empty".into());
+            METRICS.log_warning(LogWarning::EmptyStack);
         }
 
         if log.file_name.is_empty() {
             log.warnings.push("No file name found".into());
+            METRICS.log_warning(LogWarning::NoFileName);
             log.file_name = "<None>".into();
         }
 
@@ -239,11 +525,244 @@ impl Service {
 
             None => log.address = "Unknown".into(),
         }
+
+        Ok(StoredLog {
+            id: 0,
+            log,
+            project,
+            severity,
+            fields,
+            client_subject: None,
+            received_at: Utc::now(),
+        })
     }
 
     pub fn requires_authentication(&mut self, requires_authentication: bool) {
         self.requires_authentication = requires_authentication;
     }
+
+    async fn store_log(
+        &self,
+        mut log: StoredLog,
+        source: LogSource,
+    ) -> Result<(), Status> {
+        let model = LogActiveModel::from_stored_log(&log)
+            .map_err(|error| Status::internal(error.to_string()))?;
+
+        // Log streams skip every log up to the newest one they've sent, so logs
+        // have to be broadcast in the order of their IDs.
+        let _store_guard = self.store_lock.lock().await;
+
+        match model.insert(self.db_connection.as_ref()).await {
+            Ok(model) => log.id = model.id,
+            Err(error) => {
+                error!("Error occurred while storing log {}: {error}", log.log.uuid);
+                return Err(Status::aborted(error.to_string()));
+            },
+        }
+
+        METRICS.log_received(source);
+        METRICS.bytes_ingested(log.log.encoded_len());
+
+        // There being no subscribers isn't an error, the log is already stored.
+        _ = self.log_sender.send(log);
+
+        Ok(())
+    }
+
+    /// Checks that `connection_uuid` is registered, and that it was registered
+    /// by whoever is using it now.
+    async fn check_connection(
+        &self,
+        connection_uuid: &str,
+        claims: Option<&TokenClaims>,
+    ) -> Result<(), Status> {
+        if Uuid::try_parse(connection_uuid).is_err() {
+            return Err(Status::unauthenticated("No valid Connection was supplied."));
+        }
+
+        let connections = self.connections.read().await;
+
+        let state = connections.get(connection_uuid).ok_or_else(|| {
+            Status::unauthenticated("Invalid connection, please register.")
+        })?;
+
+        match (&state.owner, claims) {
+            (Some(owner), Some(claims)) if owner == &claims.subject => Ok(()),
+            (Some(_), _) => Err(Status::permission_denied(
+                "This connection was registered by someone else.",
+            )),
+            (None, _) => Ok(()),
+        }
+    }
+
+    async fn last_sent_log_id(&self, connection_uuid: &str) -> i64 {
+        self.connections
+            .read()
+            .await
+            .get(connection_uuid)
+            .map_or(0, |state| state.last_sent_log_id)
+    }
+
+    /// Sends a stored log down a connection's log stream, unless the
+    /// connection has already received it. Returns `false` if the connection or
+    /// its stream no longer exists.
+    async fn send_to_connection<T>(
+        &self,
+        connection_uuid: &str,
+        stored: StoredLog,
+        tx: &mpsc::Sender<Result<T, Status>>,
+        into: fn(StoredLog) -> T,
+    ) -> bool {
+        let id = stored.id;
+
+        let is_sent = match self.connections.read().await.get(connection_uuid) {
+            Some(state) => id <= state.last_sent_log_id,
+            None => return false,
+        };
+
+        if is_sent {
+            return true;
+        }
+
+        if let Err(e) = tx.send(Ok(into(stored))).await {
+            error!("Occurred when writing to channel: {e}");
+            return false;
+        }
+
+        if let Some(mut state) = self.connections.read().await.get_mut(connection_uuid) {
+            state.add_log(id);
+        }
+
+        true
+    }
+
+    /// Fetches the stored logs within `scope` that are newer than the log with
+    /// the ID `after`, oldest first. At most `limit` logs are returned if a
+    /// limit is given.
+    async fn unsent_logs(
+        &self,
+        after: i64,
+        scope: &ProjectScope,
+        limit: Option<u64>,
+    ) -> Result<VecDeque<StoredLog>, Status> {
+        let mut query = LogEntity::find()
+            .filter(LogColumn::Id.gt(after))
+            .order_by_asc(LogColumn::Id);
+
+        if let ProjectScope::Projects(projects) = scope {
+            query = query.filter(LogColumn::Project.is_in(projects.clone()));
+        }
+
+        if let Some(limit) = limit {
+            query = query.limit(limit);
+        }
+
+        let rows = query
+            .all(self.db_connection.as_ref())
+            .await
+            .map_err(|error| Status::aborted(error.to_string()))?;
+
+        rows.into_iter()
+            .map(|row| {
+                StoredLog::try_from(row)
+                    .map_err(|error| Status::internal(error.to_string()))
+            })
+            .collect()
+    }
+
+    /// Streams the stored logs that a connection hasn't received yet, then
+    /// the new logs as they arrive, each turned into what the stream sends
+    /// with `into`. Backs both `GetLogs` and `StreamLogs`.
+    async fn log_stream<T: Send + 'static>(
+        &self,
+        connection: Request<Connection>,
+        into: fn(StoredLog) -> T,
+    ) -> Result<ReceiverStream<Result<T, Status>>, Status> {
+        let claims = Identity::require(&connection, self.requires_authentication)?;
+        let scope = ProjectScope::of(claims, connection.metadata())?;
+
+        self.check_connection(&connection.get_ref().uuid, claims)
+            .await?;
+
+        let remote_addr = peer_of(&connection);
+        let (tx, rx) = mpsc::channel(1024);
+        let connection = connection.into_inner();
+
+        let after = self.last_sent_log_id(&connection.uuid).await;
+
+        // Subscribe before fetching the stored logs so that no log received in
+        // the meantime is missed, any duplicates are skipped by
+        // `send_to_connection`.
+        let mut receiver = self.log_sender.subscribe();
+        let mut logs = self.unsent_logs(after, &scope, None).await?;
+
+        let log_amount = logs.len();
+        let service = self.clone();
+
+        // The stream stays open after the stored logs have been sent and pushes
+        // new logs as they arrive, until the client goes away.
+        tokio::spawn(async move {
+            let uuid = connection.uuid;
+
+            loop {
+                while let Some(stored) = logs.pop_front() {
+                    if !service.send_to_connection(&uuid, stored, &tx, into).await {
+                        return;
+                    }
+                }
+
+                tokio::select! {
+                    () = tx.closed() => break,
+                    received = receiver.recv() => match received {
+                        Ok(stored) if scope.allows(stored.project.as_deref()) =>
+                            logs.push_back(stored),
+                        Ok(_) => (),
+                        Err(RecvError::Lagged(amount)) => {
+                            warn!("Log stream for {uuid} lagged behind by {amount} log(s), catching up from the database");
+
+                            let after = service.last_sent_log_id(&uuid).await;
+
+                            match service.unsent_logs(after, &scope, None).await {
+                                Ok(unsent) => logs = unsent,
+                                Err(error) => {
+                                    error!("Could not catch up log stream for {uuid}: {error}");
+                                    break;
+                                },
+                            }
+                        },
+                        Err(RecvError::Closed) => break,
+                    },
+                }
+            }
+
+            trace!("Log stream for {uuid} closed");
+        });
+
+        trace!(
+            "{} subscribed to the log stream and will recieve {} stored log(s)",
+            remote_addr,
+            log_amount
+        );
+
+        Ok(ReceiverStream::new(rx))
+    }
+
+    /// Finds where a connection that was registered by an older version of the
+    /// server, which kept the UUIDs of every log sent down it, got up to.
+    async fn legacy_last_sent_log_id(&self, sent_logs: &str) -> Result<i64, Status> {
+        let sent_log_ids: Vec<String> = serde_json::from_str(sent_logs)
+            .map_err(|error| Status::internal(error.to_string()))?;
+
+        let newest = LogEntity::find()
+            .filter(LogColumn::Uuid.is_in(sent_log_ids))
+            .order_by_desc(LogColumn::Id)
+            .one(self.db_connection.as_ref())
+            .await
+            .map_err(|error| Status::aborted(error.to_string()))?;
+
+        Ok(newest.map_or(0, |row| row.id))
+    }
 }
 
 // region: server-to-client implementation
@@ -254,16 +773,24 @@ impl LogServerTrait for Service {
         &self,
         req: Request<()>,
     ) -> Result<Response<Connection>, Status> {
+        let owner = Identity::require(&req, self.requires_authentication)?
+            .map(|claims| claims.subject.clone());
+
         let connection = Connection::new();
 
-        self.connections
-            .write()
-            .await
-            .insert(connection.uuid.clone(), ConnectionState::default());
+        self.connections.write().await.insert(
+            connection.uuid.clone(),
+            ConnectionState {
+                owner: owner.clone(),
+                ..ConnectionState::default()
+            },
+        );
 
         let model = ActiveModel {
             uuid: Set(connection.uuid.clone()),
             sent_logs: NotSet,
+            owner: Set(owner),
+            last_sent_log_id: NotSet,
         };
 
         if let Err(error) = model.insert(self.db_connection.as_ref()).await {
@@ -273,7 +800,7 @@ impl LogServerTrait for Service {
         info!(
             "Registered new connection: {} to {}",
             &connection.uuid,
-            req.remote_addr().unwrap()
+            peer_of(&req)
         );
 
         Ok(Response::new(connection))
@@ -283,7 +810,10 @@ impl LogServerTrait for Service {
         &self,
         connection: Request<Connection>,
     ) -> Result<Response<RequestResult>, Status> {
-        let remote_addr = connection.remote_addr().unwrap();
+        let subject = Identity::require(&connection, self.requires_authentication)?
+            .map(|claims| claims.subject.clone());
+
+        let remote_addr = peer_of(&connection);
         let connection = connection.into_inner();
 
         let connections = match Entity::find_by_id(connection.uuid)
@@ -302,21 +832,28 @@ impl LogServerTrait for Service {
 
         let connection = connections[0].clone();
 
-        if let Some(sent_log_ids) = connection.sent_logs.as_ref() {
-            let sent_log_ids: DashSet<String> = match serde_json::from_str(sent_log_ids) {
-                Ok(ids) => ids,
-                Err(error) => return Err(Status::internal(error.to_string())),
-            };
-
-            self.connections.write().await.insert(
-                connection.uuid.clone(),
-                ConnectionState {
-                    last_update: Instant::now(),
-                    sent_log_ids,
-                },
-            );
+        if connection.owner.is_some() && connection.owner != subject {
+            return Err(Status::permission_denied(
+                "This connection was registered by someone else.",
+            ));
         }
 
+        let last_sent_log_id = match (connection.last_sent_log_id, &connection.sent_logs)
+        {
+            (Some(id), _) => id,
+            (None, Some(sent_logs)) => self.legacy_last_sent_log_id(sent_logs).await?,
+            (None, None) => 0,
+        };
+
+        self.connections.write().await.insert(
+            connection.uuid.clone(),
+            ConnectionState {
+                last_update: Instant::now(),
+                last_sent_log_id,
+                owner: connection.owner.clone(),
+            },
+        );
+
         let req_result = RequestResult {
             message: "Re-registration succeeded!".to_string(),
             status: RequestStatus::Confirmed.into(),
@@ -344,7 +881,7 @@ impl LogServerTrait for Service {
             requires_authentication: self.requires_authentication,
         });
 
-        trace!("{} requested server details", req.remote_addr().unwrap());
+        trace!("{} requested server details", peer_of(&req));
 
         Ok(response)
     }
@@ -353,52 +890,29 @@ impl LogServerTrait for Service {
         &self,
         connection: Request<Connection>,
     ) -> Result<Response<Log>, Status> {
-        let remote_addr = connection.remote_addr().unwrap();
-        let connection = connection.into_inner();
+        let claims = Identity::require(&connection, self.requires_authentication)?;
+        let scope = ProjectScope::of(claims, connection.metadata())?;
 
-        if Uuid::try_parse(&connection.uuid).is_err() {
-            return Err(Status::unauthenticated("No valid Connection was supplied."));
-        }
+        self.check_connection(&connection.get_ref().uuid, claims)
+            .await?;
 
-        if !self.connections.read().await.contains_key(&connection.uuid) {
-            return Err(Status::unauthenticated(
-                "Invalid connection, please register.",
-            ));
-        }
-
-        let mut ignore = DashSet::new();
-
-        if self.connections.read().await.contains_key(&connection.uuid) {
-            ignore = self
-                .connections
-                .read()
-                .await
-                .get(&connection.uuid)
-                .unwrap()
-                .clone()
-                .sent_log_ids;
-        }
+        let remote_addr = peer_of(&connection);
+        let connection = connection.into_inner();
 
-        let logs = self.logs.read().await.clone();
-        let mut logs = logs
-            .iter()
-            .cloned()
-            .filter(|log| !ignore.contains(&log.uuid))
-            .collect::<VecDeque<_>>();
+        let after = self.last_sent_log_id(&connection.uuid).await;
+        let mut logs = self.unsent_logs(after, &scope, Some(1)).await?;
 
-        if let Some(log) = logs.pop_front() {
-            if !ignore.contains(&log.uuid) {
-                let key = self.connections.write().await;
-                let key = key.get_mut(&connection.uuid);
+        if let Some(stored) = logs.pop_front() {
+            let key = self.connections.write().await;
+            let key = key.get_mut(&connection.uuid);
 
-                if let Some(mut key) = key {
-                    key.add_log(&log);
-                }
+            if let Some(mut key) = key {
+                key.add_log(stored.id);
+            }
 
-                trace!("{} requested one log and received new log", remote_addr);
+            trace!("{} requested one log and received new log", remote_addr);
 
-                return Ok(Response::new(log));
-            }
+            return Ok(Response::new(stored.log));
         }
 
         Err(Status::new(Code::ResourceExhausted, "No more logs"))
@@ -410,65 +924,9 @@ impl LogServerTrait for Service {
         &self,
         connection: Request<Connection>,
     ) -> Result<Response<Self::GetLogsStream>, Status> {
-        let remote_addr = connection.remote_addr().unwrap();
-        let (tx, rx) = mpsc::channel(1024);
-        let connection = connection.into_inner();
-
-        if Uuid::try_parse(&connection.uuid).is_err() {
-            return Err(Status::unauthenticated("No valid Connection was supplied."));
-        }
-
-        if !self.connections.read().await.contains_key(&connection.uuid) {
-            return Err(Status::unauthenticated(
-                "Invalid connection, please register.",
-            ));
-        }
-
-        let connections = Arc::clone(&self.connections);
-
-        let mut ignore = DashSet::new();
-
-        if connections.read().await.contains_key(&connection.uuid) {
-            ignore = connections
-                .read()
-                .await
-                .get(&connection.uuid)
-                .unwrap()
-                .clone()
-                .sent_log_ids;
-        }
-
-        let logs = self.logs.read().await.clone();
-        let mut logs = logs
-            .iter()
-            .cloned()
-            .filter(|log| !ignore.contains(&log.uuid))
-            .collect::<VecDeque<_>>();
-
-        let log_amount = logs.len();
-
-        tokio::spawn(async move {
-            let key = connections.write().await;
-            let mut key = key.get_mut(&connection.uuid);
-
-            while let Some(log) = logs.pop_front() {
-                if !ignore.contains(&log.uuid) {
-                    if let Err(e) = tx.send(Ok(log.clone())).await {
-                        error!("Occurred when writing to channel: {e:?}");
-                    } else if let Some(key) = key.as_mut() {
-                        key.add_log(&log);
-                    }
-                }
-            }
-        });
-
-        trace!(
-            "{} requested log stream and will recieve new {} log(s)",
-            remote_addr,
-            log_amount
-        );
-
-        Ok(Response::new(ReceiverStream::new(rx)))
+        self.log_stream(connection, |stored| stored.log)
+            .await
+            .map(Response::new)
     }
 }
 
@@ -482,14 +940,23 @@ impl LogClientTrait for Service {
         request: Request<Log>,
     ) -> Result<Response<RequestResult>, Status> {
         let remote_addr = request.remote_addr();
+        let peer = peer_of(&request);
         let metadata = request.metadata().clone();
-        let mut log = request.into_inner();
-
-        Self::verify_log(&mut log, remote_addr, &metadata);
+        let client_subject = tls::client_subject(&request);
+        // The interceptor has already rejected anonymous loggers if they have to
+        // log in.
+        let claims = Identity::require(&request, false)?.cloned();
+        let mut log = Self::verify_log(
+            request.into_inner(),
+            remote_addr,
+            &metadata,
+            claims.as_ref(),
+        )?;
+        log.client_subject = client_subject;
 
-        self.logs.write().await.push_back(log);
+        self.store_log(log, LogSource::SendLog).await?;
 
-        info!("Log received from {}", remote_addr.unwrap());
+        info!("Log received from {peer}");
 
         Ok(Response::new(RequestResult {
             message: "Log added!".into(),
@@ -503,22 +970,25 @@ impl LogClientTrait for Service {
         request: Request<Streaming<Log>>,
     ) -> Result<Response<RequestResult>, Status> {
         let remote_addr = request.remote_addr();
+        let peer = peer_of(&request);
         let metadata = request.metadata().clone();
+        let client_subject = tls::client_subject(&request);
+        // The interceptor has already rejected anonymous loggers if they have to
+        // log in.
+        let claims = Identity::require(&request, false)?.cloned();
         let mut stream = request.into_inner();
 
-        let mut lock = self.logs.write().await;
-
         let mut amount = 0;
         while let Some(log) = stream.next().await {
-            let mut log = log?;
-
-            Self::verify_log(&mut log, remote_addr, &metadata);
-            lock.push_back(log);
+            let mut log =
+                Self::verify_log(log?, remote_addr, &metadata, claims.as_ref())?;
+            log.client_subject = client_subject.clone();
+            self.store_log(log, LogSource::SendLogs).await?;
 
             amount += 1;
         }
 
-        info!("{amount} log(s) received from {}", remote_addr.unwrap());
+        info!("{amount} log(s) received from {peer}");
 
         Ok(Response::new(RequestResult {
             message: format!("{amount} logs added!"),
@@ -528,95 +998,411 @@ impl LogClientTrait for Service {
     }
 }
 
+// endregion
+// region: log search implementation
+
+#[tonic::async_trait]
+impl LogSearch for Service {
+    async fn query_logs(
+        &self,
+        request: Request<LogQuery>,
+    ) -> Result<Response<LogPage>, Status> {
+        let claims = Identity::require(&request, self.requires_authentication)?;
+        let scope = ProjectScope::of(claims, request.metadata())?;
+        let query = request.into_inner();
+
+        let matcher = LogMatcher::new(&query).map_err(|error| {
+            Status::invalid_argument(format!("Invalid regular expression: {error}"))
+        })?;
+        let page_size = query::page_size(&query);
+
+        let cursor = if query.page_token.is_empty() {
+            None
+        } else {
+            Some(
+                query
+                    .page_token
+                    .parse::<i64>()
+                    .map_err(|_| Status::invalid_argument("Invalid page token"))?,
+            )
+        };
+
+        let timestamp = |millis: i64| {
+            Utc.timestamp_millis_opt(millis)
+                .single()
+                .ok_or_else(|| Status::invalid_argument("Invalid timestamp"))
+        };
+
+        // Everything but the string filters can be done by SQLite, the rest is
+        // matched while streaming the rows.
+        let mut select = LogEntity::find();
+
+        if let ProjectScope::Projects(projects) = &scope {
+            select = select.filter(LogColumn::Project.is_in(projects.clone()));
+        }
+
+        if let Some(line_number) = query.line_number {
+            select = select.filter(LogColumn::LineNumber.eq(line_number));
+        }
+
+        if let Some(received_after) = query.received_after {
+            select = select.filter(LogColumn::ReceivedAt.gte(timestamp(received_after)?));
+        }
+
+        if let Some(received_before) = query.received_before {
+            select = select.filter(LogColumn::ReceivedAt.lt(timestamp(received_before)?));
+        }
+
+        if let Some(min_severity) = &query.min_severity {
+            let min_severity = min_severity
+                .parse::<Severity>()
+                .map_err(Status::invalid_argument)?;
+            let severities = min_severity
+                .and_above()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+
+            // Logs stored before severities were added count as `info`.
+            select = if min_severity <= Severity::Info {
+                select.filter(
+                    Condition::any()
+                        .add(LogColumn::Severity.is_in(severities))
+                        .add(LogColumn::Severity.is_null()),
+                )
+            } else {
+                select.filter(LogColumn::Severity.is_in(severities))
+            };
+        }
+
+        // Warnings are stored as a JSON array.
+        match query.has_warnings {
+            Some(true) => select = select.filter(LogColumn::Warnings.ne("[]")),
+            Some(false) => select = select.filter(LogColumn::Warnings.eq("[]")),
+            None => (),
+        }
+
+        select = match (query.newest_first, cursor) {
+            (true, Some(cursor)) => select
+                .filter(LogColumn::Id.lt(cursor))
+                .order_by_desc(LogColumn::Id),
+            (true, None) => select.order_by_desc(LogColumn::Id),
+            (false, Some(cursor)) => select
+                .filter(LogColumn::Id.gt(cursor))
+                .order_by_asc(LogColumn::Id),
+            (false, None) => select.order_by_asc(LogColumn::Id),
+        };
+
+        let mut rows = select
+            .stream(self.db_connection.as_ref())
+            .await
+            .map_err(|error| Status::aborted(error.to_string()))?;
+
+        let mut logs = Vec::new();
+        let mut last_id = None;
+        let mut has_more = false;
+
+        while let Some(row) = rows.next().await {
+            let row = row.map_err(|error| Status::aborted(error.to_string()))?;
+
+            let stored = StoredLog::try_from(row)
+                .map_err(|error| Status::internal(error.to_string()))?;
+
+            if !matcher.matches(&stored.log) || !matcher.matches_fields(&stored.fields) {
+                continue;
+            }
+
+            if logs.len() == page_size {
+                has_more = true;
+                break;
+            }
+
+            last_id = Some(stored.id);
+            logs.push(QueriedLog::from(stored));
+        }
+
+        let next_page_token = match last_id {
+            Some(last_id) if has_more => last_id.to_string(),
+            _ => String::new(),
+        };
+
+        Ok(Response::new(LogPage {
+            logs,
+            next_page_token,
+        }))
+    }
+
+    type StreamLogsStream = ReceiverStream<Result<QueriedLog, Status>>;
+
+    async fn stream_logs(
+        &self,
+        connection: Request<Connection>,
+    ) -> Result<Response<Self::StreamLogsStream>, Status> {
+        self.log_stream(connection, QueriedLog::from)
+            .await
+            .map(Response::new)
+    }
+}
+
 // endregion
 // region: oauth implementation
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-struct TokenClaims {
-    #[serde(rename = "iat")]
-    issued_at: usize,
-    #[serde(rename = "iss")]
-    issuer: String,
-    #[serde(rename = "exp")]
-    expire: usize,
-    #[serde(rename = "sub")]
-    subject: String,
-    #[serde(rename = "nbf")]
-    not_before: usize,
+impl Service {
+    #[must_use]
+    pub fn auth_interceptor(&self, rejects_anonymous: bool) -> AuthInterceptor {
+        AuthInterceptor {
+            requires_authentication: self.requires_authentication,
+            rejects_anonymous,
+            token_secret: self.token_secret.clone(),
+            revoked_tokens: Arc::clone(&self.revoked_tokens),
+        }
+    }
+
+    fn sign_token(&self, claims: &TokenClaims) -> Result<Token, Status> {
+        let token = self
+            .token_secret
+            .sign(claims)
+            .map_err(|error| Status::internal(error.to_string()))?;
+
+        info!("Issued token {} for {}", claims.id, claims.subject);
+
+        Ok(Token {
+            token,
+            ..Default::default()
+        })
+    }
+
+    /// Revokes the token or session `id` of `subject`, both in memory (so the
+    /// interceptors reject it from now on) and in the database (so it stays
+    /// revoked after a restart). The revocation is kept until `expires_at`,
+    /// after which every token it applies to has expired anyway.
+    async fn revoke(
+        &self,
+        id: &str,
+        subject: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), Status> {
+        let model = RevokedTokenActiveModel {
+            id: Set(id.into()),
+            subject: Set(subject.into()),
+            expires_at: Set(expires_at),
+        };
+
+        if let Err(error) = model.insert(self.db_connection.as_ref()).await {
+            return Err(Status::aborted(error.to_string()));
+        }
+
+        self.revoked_tokens.insert(id.into());
+
+        info!("Revoked {id} for {subject}");
+
+        Ok(())
+    }
+
+    /// Starts a login with `provider` and returns the URL the user has to
+    /// visit, along with the state to collect the token with. The redirect
+    /// handler is started if it isn't already, to receive the provider
+    /// redirecting back to us.
+    async fn start_login(
+        &self,
+        provider: &ProviderConfig,
+    ) -> Result<(String, String), Status> {
+        let redirect_handler = self.redirect_handler.as_ref().ok_or_else(|| {
+            Status::failed_precondition("This server does not require authentication")
+        })?;
+
+        let provider = provider
+            .build(redirect_handler.port())
+            .await
+            .map_err(|error| Status::unavailable(format!("{error:#}")))?;
+
+        // The CSRF token comes back as the `state` of the redirect, which is how
+        // the redirect handler knows which login is being completed.
+        let (authorize_url, csrf_state) = provider.authorize_url();
+        let state = csrf_state.secret().clone();
+        redirect_handler.add_pending_login(state.clone(), provider);
+
+        if !redirect_handler.is_started() {
+            redirect_handler.start(Handle::current()).await;
+        }
+
+        Ok((authorize_url.to_string(), state))
+    }
 }
 
 #[tonic::async_trait]
 impl Authentication for Service {
     async fn verify_token(
         &self,
-        _request: Request<VerifyTokenRequest>,
+        request: Request<VerifyTokenRequest>,
     ) -> Result<Response<VerifyTokenRequestResult>, Status> {
-        todo!()
+        let token = request.into_inner().token.unwrap_or_default();
+        let result = self
+            .auth_interceptor(false)
+            .verify_token(&token.token)
+            .is_ok();
+
+        Ok(Response::new(VerifyTokenRequestResult {
+            result,
+            ..Default::default()
+        }))
     }
 
     async fn generate_token(
         &self,
-        _request: Request<GenerateTokenRequest>,
+        request: Request<GenerateTokenRequest>,
     ) -> Result<Response<GenerateTokenRequestResult>, Status> {
-        todo!()
+        // Only someone who is already logged in can hand out new tokens (i.e. for
+        // loggers), otherwise authentication would be pointless. The new token
+        // can't see more projects than the token it was generated with, and acts
+        // as whoever generated it, the requested name is only a label.
+        let claims = if self.requires_authentication {
+            Some(
+                self.auth_interceptor(true)
+                    .verify_metadata(request.metadata())?,
+            )
+        } else {
+            None
+        };
+        let projects =
+            ProjectScope::of(claims.as_ref(), request.metadata())?.into_token_projects();
+
+        let request = request.into_inner();
+        let claims = claims
+            .map_or_else(
+                || TokenClaims::new(ANONYMOUS_SUBJECT, DEFAULT_TOKEN_LIFETIME),
+                |claims| claims.child(DEFAULT_TOKEN_LIFETIME),
+            )
+            .with_label(&request.name)
+            .with_projects(projects);
+        let token = self.sign_token(&claims)?;
+
+        Ok(Response::new(GenerateTokenRequestResult {
+            token: Some(token),
+            ..Default::default()
+        }))
     }
 
     async fn revoke_token(
         &self,
-        _request: Request<Token>,
+        request: Request<Token>,
     ) -> Result<Response<RevokeTokenRequestResult>, Status> {
-        todo!()
+        let token = request.into_inner();
+
+        // Holding a validly signed token is enough to revoke it, even if it has
+        // already been revoked.
+        let claims = self.token_secret.verify(&token.token).map_err(|error| {
+            Status::invalid_argument(format!("Invalid token: {error}"))
+        })?;
+
+        // The whole session is revoked, along with every token generated with
+        // it. None of those can be refreshed any more, so they have all expired
+        // once a token issued right now would have.
+        let result = if self.revoked_tokens.contains(&claims.session) {
+            false
+        } else {
+            let expires_at = Utc::now()
+                + chrono::Duration::from_std(DEFAULT_TOKEN_LIFETIME)
+                    .unwrap_or_else(|_| chrono::Duration::zero());
+
+            self.revoke(&claims.session, &claims.subject, expires_at)
+                .await?;
+            true
+        };
+
+        Ok(Response::new(RevokeTokenRequestResult {
+            result,
+            ..Default::default()
+        }))
     }
 
     async fn refresh_token(
         &self,
-        _request: Request<Token>,
+        request: Request<Token>,
     ) -> Result<Response<Token>, Status> {
-        todo!()
+        let token = request.into_inner();
+        let claims = self.auth_interceptor(true).verify_token(&token.token)?;
+
+        // Only the old token is revoked, not its session, so that the tokens
+        // generated with it stay valid.
+        let token = self.sign_token(&claims.refreshed(DEFAULT_TOKEN_LIFETIME))?;
+        let expires_at = i64::try_from(claims.expire)
+            .ok()
+            .and_then(|expire| Utc.timestamp_opt(expire, 0).single())
+            .unwrap_or_else(Utc::now);
+        self.revoke(&claims.id, &claims.subject, expires_at).await?;
+
+        Ok(Response::new(token))
     }
 
     async fn github_login(&self, _: Request<()>) -> Result<Response<LoginUrl>, Status> {
-        Ok(Response::new(LoginUrl {
-            url: generate_github_login_url(),
-        }))
+        let provider = self
+            .oauth_providers
+            .iter()
+            .find(|provider| provider.kind == ProviderKind::GitHub)
+            .ok_or_else(|| Status::unavailable("GitHub login is not configured"))?;
+
+        let (url, _) = self.start_login(provider).await?;
+
+        Ok(Response::new(LoginUrl { url }))
     }
 }
 
-fn generate_github_login_url() -> String {
-    let github_client_id = ClientId::new(
-        env::var("GITHUB_CLIENT_ID")
-            .expect("Missing the GITHUB_CLIENT_ID environment variable."),
-    );
-    let github_client_secret = ClientSecret::new(
-        env::var("GITHUB_CLIENT_SECRET")
-            .expect("Missing the GITHUB_CLIENT_SECRET environment variable."),
-    );
-    let auth_url = AuthUrl::new("https://github.com/login/oauth/authorize".to_string())
-        .expect("Invalid authorization endpoint URL");
-    let token_url =
-        TokenUrl::new("https://github.com/login/oauth/access_token".to_string())
-            .expect("Invalid token endpoint URL");
-
-    let client = BasicClient::new(
-        github_client_id,
-        Some(github_client_secret),
-        auth_url,
-        Some(token_url),
-    )
-    .set_redirect_uri(
-        RedirectUrl::new(format!(
-            "https://localhost:{}",
-            REDIRECT_HANDLER_PORT.get_or_init(|| 8080)
-        ))
-        .expect("Invalid redirect URL"),
-    );
+#[tonic::async_trait]
+impl LoginProviders for Service {
+    async fn list_providers(
+        &self,
+        _: Request<()>,
+    ) -> Result<Response<ProviderList>, Status> {
+        let providers = self
+            .oauth_providers
+            .iter()
+            .map(|provider| Provider {
+                name: provider.name.clone(),
+                kind: provider.kind.to_string(),
+            })
+            .collect();
+
+        Ok(Response::new(ProviderList { providers }))
+    }
+
+    async fn login(
+        &self,
+        request: Request<LoginRequest>,
+    ) -> Result<Response<ProviderLoginUrl>, Status> {
+        let name = request.into_inner().provider;
+
+        let provider = self
+            .oauth_providers
+            .iter()
+            .find(|provider| provider.name == name.to_lowercase())
+            .ok_or_else(|| {
+                Status::not_found(format!("No login provider called \"{name}\""))
+            })?;
+
+        let (url, state) = self.start_login(provider).await?;
+
+        Ok(Response::new(ProviderLoginUrl {
+            provider: provider.name.clone(),
+            url,
+            state,
+        }))
+    }
+
+    async fn collect_token(
+        &self,
+        request: Request<CollectTokenRequest>,
+    ) -> Result<Response<CollectTokenResult>, Status> {
+        let redirect_handler = self.redirect_handler.as_ref().ok_or_else(|| {
+            Status::failed_precondition("This server does not require authentication")
+        })?;
 
-    let (authorize_url, _csrf_state) = client
-        .authorize_url(CsrfToken::new_random)
-        .add_scope(Scope::new("user:email".to_string()))
-        .url();
+        let token = redirect_handler
+            .collect_token(&request.into_inner().state)?
+            .unwrap_or_default();
 
-    authorize_url.to_string()
+        Ok(Response::new(CollectTokenResult { token }))
+    }
 }
 
 fn generate_token() -> String {
@@ -632,141 +1418,259 @@ fn generate_token() -> String {
 
 // endregion
 
-/// Runs the `gRPC` server to be used by the GUI or the standalone binary.
-///
-/// # Errors
-///
-/// This function could error under the following circumstances:
-///
-/// 1. Supplied host was taken or invalid.
-/// 2. Supplied port was taken or invalid.
-/// 3. The inner tonic server returns an error during runtime.
-#[allow(clippy::missing_panics_doc)]
-pub async fn run_server(
-    host: Option<String>,
-    port: Option<u32>,
-    requires_authentication: Option<bool>,
-    redirect_handler_port: Option<u16>,
-) -> anyhow::Result<()> {
-    dotenv().ok();
-    env_logger::try_init().ok();
-
-    if let Ok(value) = env::var("CENSOR_USERNAMES") {
-        if let Ok(value) = value.parse::<u8>() {
-            if value == 0 {
-                CENSOR_USERNAMES.get_or_init(|| false);
-            }
-        }
-        CENSOR_USERNAMES.get_or_init(|| true);
-    } else {
-        CENSOR_USERNAMES.get_or_init(|| true);
-    }
-
-    let _token_secret = if let Ok(secret) = env::var("TOKEN_SECRET") {
-        if secret.is_empty() {
-            warn!("TOKEN_SECRET was found but was empty!");
-            generate_token()
-        } else {
-            warn!("TOKEN_SECRET wasn't found in the environment variables!");
-            secret
-        }
-    } else {
-        generate_token()
-    };
-
-    let data_dir = if let Some(data_directory) =
-        ProjectDirs::from("com", "Authentura", "codectrl-server")
-    {
-        data_directory.data_dir().to_owned()
-    } else {
-        Path::new(".codectrl-server").to_owned()
-    };
+/// Adds `column` to `table` if the database was created by an older version of
+/// the server that didn't have it yet.
+async fn add_missing_column(
+    db_connection: &DatabaseConnection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), DbErr> {
+    let backend = db_connection.get_database_backend();
+
+    let has_column = db_connection
+        .query_all(Statement::from_string(
+            backend,
+            format!("PRAGMA table_info({table})"),
+        ))
+        .await?
+        .iter()
+        .any(
+            |row| matches!(row.try_get::<String>("", "name"), Ok(name) if name == column),
+        );
 
-    let requires_authentication =
-        if let Some(requires_authentication) = requires_authentication {
-            requires_authentication
-        } else {
-            false
-        };
+    if !has_column {
+        info!("Adding the missing {column} column to the {table} table");
 
-    let handler_port;
-    if requires_authentication {
-        handler_port = if let Some(port) = redirect_handler_port {
-            port
-        } else {
-            8080
-        };
+        db_connection
+            .execute(Statement::from_string(
+                backend,
+                format!("ALTER TABLE {table} ADD COLUMN {column} {definition}"),
+            ))
+            .await?;
+    }
 
-        REDIRECT_HANDLER_PORT.get_or_init(|| handler_port);
-    };
+    Ok(())
+}
 
+/// Opens the database in `data_dir`, creating it if it doesn't exist yet and
+/// adding any tables and columns that are missing from databases created by
+/// older versions of the server.
+///
+/// # Errors
+///
+/// Returns an error if the data directory can't be created or the database
+/// can't be opened or migrated.
+pub async fn open_database(data_dir: &Path) -> anyhow::Result<DatabaseConnection> {
     info!(
         "Data directory for CodeCTRL: {}",
         data_dir.to_string_lossy()
     );
 
     if !data_dir.exists() {
-        fs::create_dir_all(&data_dir)?;
+        fs::create_dir_all(data_dir)?;
         info!("Created {}", data_dir.to_string_lossy());
     }
 
-    let data_dir = data_dir.to_string_lossy().to_string();
-    let db_file = format!("{data_dir}/db.sqlite");
+    let db_file = data_dir.join("db.sqlite");
+    let db_file = db_file.to_string_lossy();
 
-    // If the DB file does not exist or is completely empty, then create and create
-    // the necessary table.
-    if !Path::new(&db_file).exists() || File::open(&db_file)?.metadata()?.len() == 0 {
-        File::create(&db_file)?;
+    // `mode=rwc` creates the DB file if it does not exist yet, and the tables are
+    // only created if they're missing so that databases created by older
+    // versions of the server gain the `log` table.
+    let db_connection = Database::connect(format!("sqlite:{db_file}?mode=rwc")).await?;
 
-        let db_connection = Database::connect(format!("sqlite:{db_file}")).await?;
+    let backend = db_connection.get_database_backend();
+    let schema = Schema::new(backend);
 
-        let backend = db_connection.get_database_backend();
-        let schema = Schema::new(backend);
-        let statement = backend.build(&schema.create_table_from_entity(Entity));
+    info!("Creating missing SQLite database tables");
 
-        info!("Creating initial SQLite database");
+    db_connection
+        .execute(backend.build(schema.create_table_from_entity(Entity).if_not_exists()))
+        .await?;
+    db_connection
+        .execute(
+            backend.build(schema.create_table_from_entity(LogEntity).if_not_exists()),
+        )
+        .await?;
+    db_connection
+        .execute(
+            backend.build(
+                schema
+                    .create_table_from_entity(RevokedTokenEntity)
+                    .if_not_exists(),
+            ),
+        )
+        .await?;
 
-        db_connection.execute(statement).await?;
-    }
+    add_missing_column(&db_connection, "connection", "owner", "TEXT").await?;
+    add_missing_column(&db_connection, "connection", "last_sent_log_id", "INTEGER")
+        .await?;
+    // Logs stored before the size was recorded count as empty towards
+    // `retention.max_bytes`.
+    add_missing_column(&db_connection, "log", "size", "INTEGER NOT NULL DEFAULT 0")
+        .await?;
+    add_missing_column(&db_connection, "log", "project", "TEXT").await?;
+    add_missing_column(&db_connection, "log", "severity", "TEXT").await?;
+    add_missing_column(&db_connection, "log", "fields", "TEXT").await?;
+    add_missing_column(&db_connection, "log", "client_subject", "TEXT").await?;
+
+    Ok(db_connection)
+}
+
+/// Runs the `gRPC` server to be used by the GUI or the standalone binary.
+///
+/// # Errors
+///
+/// This function could error under the following circumstances:
+///
+/// 1. The config is invalid, see [`ServerConfig::validate`].
+/// 2. Supplied host was taken or invalid.
+/// 3. Supplied port was taken or invalid.
+/// 4. The Unix socket in `listen.unix_socket` could not be bound.
+/// 5. The inner tonic server returns an error during runtime.
+///
+/// Besides the CodeCTRL services, the `gRPC` server accepts logs exported by
+/// OpenTelemetry SDKs and collectors (OTLP/`gRPC`).
+///
+/// Logs can also be sent as JSON to `POST /api/logs` on `listen.http_api_port`
+/// (3003 by default), for loggers that can't speak `gRPC`, unless
+/// `listen.http_api` or `listen.tcp` is turned off. Prometheus metrics are
+/// served on `GET /metrics` of the same port, see [`metrics`].
+///
+/// The `config` is usually loaded with [`ServerConfig::load`], which applies
+/// the environment variables on top of the config file, and is checked with
+/// [`ServerConfig::validate`] before anything is started.
+#[allow(clippy::missing_panics_doc)]
+pub async fn run_server(config: ServerConfig) -> anyhow::Result<()> {
+    dotenv().ok();
+    env_logger::try_init().ok();
 
-    let db_connection = Database::connect(format!("sqlite:{db_file}")).await?;
+    config.validate()?;
 
-    let host = if host.is_some() {
-        host.unwrap()
+    CENSOR_USERNAMES.get_or_init(|| config.censor_usernames);
+
+    let token_secret = if let Some(secret) = config.auth.token_secret.clone() {
+        secret
     } else {
-        String::from("127.0.0.1")
+        warn!("No token secret was configured, generating one!");
+        generate_token()
     };
-    let port = if port.is_some() { port.unwrap() } else { 3002 };
 
-    let logs = Arc::new(RwLock::new(VecDeque::new()));
+    let data_dir = config.data_dir();
+    let requires_authentication = config.auth.required;
+    let redirect_handler_port = config.auth.redirect_port;
+    let http_api_port = config.listen.http_api_port;
+
+    let db_connection = open_database(&data_dir).await?;
+
+    // Tokens that have expired since being revoked would be rejected anyway.
+    let revoked_tokens = RevokedTokenEntity::find()
+        .filter(RevokedTokenColumn::ExpiresAt.gt(Utc::now()))
+        .all(&db_connection)
+        .await?
+        .into_iter()
+        .map(|revoked_token| revoked_token.id)
+        .collect::<DashSet<_>>();
+
+    let host = config.listen.host.clone();
+    let port = u32::from(config.listen.port);
+
+    let (log_sender, _) = broadcast::channel(1024);
+    let token_secret = TokenSecret::new(token_secret);
 
     let logs_service = Service {
         host: host.clone(),
         port,
         uptime: Instant::now(),
-        logs: Arc::clone(&logs),
         connections: Arc::new(RwLock::new(DashMap::new())),
         db_connection: Arc::new(db_connection),
         requires_authentication,
+        log_sender,
+        store_lock: Arc::new(Mutex::new(())),
+        redirect_handler: if requires_authentication {
+            Some(RedirectHandler::new(
+                redirect_handler_port,
+                token_secret.clone(),
+                config.projects.clone(),
+            ))
+        } else {
+            None
+        },
+        token_secret,
+        revoked_tokens: Arc::new(revoked_tokens),
+        oauth_providers: Arc::new(
+            if requires_authentication {
+                config.oauth_providers()
+            } else {
+                Vec::new()
+            },
+        ),
     };
 
     logs_service.start_backup_thread();
+    logs_service.start_retention_thread(RetentionPolicy::from(config.retention));
+
+    if config.listen.http_api && !config.listen.tcp {
+        info!("Not serving the HTTP API, as listen.tcp is turned off");
+    } else if config.listen.http_api {
+        let http_api_addr = format!("{host}:{http_api_port}").parse()?;
+        let (_, http_api) = logs_service.http_api(http_api_addr, config.tls.as_ref())?;
+        tokio::spawn(http_api);
+    }
 
-    let server_service = LogServerService::new(logs_service.clone());
-    let client_service = LogClientService::new(logs_service.clone());
-    let auth_service = AuthenticationServer::new(logs_service);
+    if let Some(syslog) = config.syslog {
+        logs_service.start_syslog_listener(&host, syslog).await?;
+    }
 
-    let grpc_addr = format!("{host}:{port}").parse()?;
+    let mut server = Server::builder().accept_http1(true);
 
-    info!("Starting gPRC server on {grpc_addr}...");
+    if let Some(tls) = &config.tls {
+        server = server.tls_config(tls.server_tls_config()?)?;
 
-    Server::builder()
-        .accept_http1(true)
-        .add_service(tonic_web::enable(server_service))
-        .add_service(tonic_web::enable(client_service))
-        .add_service(tonic_web::enable(auth_service))
-        .serve(grpc_addr)
-        .await?;
+        if tls.client_ca.is_some() {
+            info!("Clients have to present a certificate signed by the client CA");
+        }
+    }
+
+    let mut servers: Vec<BoxFuture<'static, Result<(), TransportError>>> = Vec::new();
+
+    if config.listen.tcp {
+        let grpc_addr = format!("{host}:{port}").parse()?;
+
+        info!("Starting gPRC server on {grpc_addr}...");
+
+        servers.push(
+            logs_service
+                .grpc_router(&mut server)
+                .serve(grpc_addr)
+                .boxed(),
+        );
+    }
+
+    if let Some(path) = &config.listen.unix_socket {
+        #[cfg(unix)]
+        {
+            let incoming = unix_socket::bind(path, config.listen.unix_socket_mode)?;
+
+            info!("Starting gPRC server on {}...", path.to_string_lossy());
+
+            servers.push(
+                logs_service
+                    .grpc_router(&mut server)
+                    .serve_with_incoming(incoming)
+                    .boxed(),
+            );
+        }
+
+        #[cfg(not(unix))]
+        anyhow::bail!(
+            "Cannot listen on {}: Unix sockets are not supported on this platform",
+            path.to_string_lossy()
+        );
+    }
+
+    future::try_join_all(servers).await?;
 
     Ok(())
 }
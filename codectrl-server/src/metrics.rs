@@ -0,0 +1,193 @@
+// region: imports
+
+use log::error;
+use once_cell::sync::Lazy;
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, IntCounter, IntCounterVec, IntGauge, Opts,
+    Registry, TextEncoder,
+};
+use std::time::Duration;
+
+// endregion
+
+/// The metrics of the server, served in the Prometheus text format on `GET
+/// /metrics` of the HTTP API.
+pub static METRICS: Lazy<Metrics> = Lazy::new(Metrics::new);
+
+/// Where a log was received from, the `source` label of
+/// `codectrl_logs_received_total`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogSource {
+    SendLog,
+    SendLogs,
+    Otlp,
+    HttpApi,
+    Syslog,
+}
+
+impl LogSource {
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::SendLog => "send_log",
+            Self::SendLogs => "send_logs",
+            Self::Otlp => "otlp",
+            Self::HttpApi => "http_api",
+            Self::Syslog => "syslog",
+        }
+    }
+}
+
+/// A warning that `Service::verify_log` adds to a log that is missing
+/// something, the `kind` label of `codectrl_log_warnings_total`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogWarning {
+    MessageTooLong,
+    NoMessage,
+    NoMessageType,
+    EmptyStack,
+    NoFileName,
+}
+
+impl LogWarning {
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::MessageTooLong => "message_too_long",
+            Self::NoMessage => "no_message",
+            Self::NoMessageType => "no_message_type",
+            Self::EmptyStack => "empty_stack",
+            Self::NoFileName => "no_file_name",
+        }
+    }
+}
+
+/// Counters and gauges of what the server is doing, there is only the one in
+/// [`METRICS`].
+pub struct Metrics {
+    registry: Registry,
+    logs_received: IntCounterVec,
+    log_warnings: IntCounterVec,
+    ingested_bytes: IntCounter,
+    backup_write_duration: Histogram,
+    backup_write_errors: IntCounter,
+    /// Set when the metrics are scraped, see [`Metrics::render`].
+    connections: IntGauge,
+    /// Set when the metrics are scraped, see [`Metrics::render`].
+    log_queue_depth: IntGauge,
+}
+
+impl Metrics {
+    // The metrics are all valid and registered once, so registering them can't
+    // fail.
+    fn new() -> Self {
+        let registry = Registry::new();
+
+        let logs_received = IntCounterVec::new(
+            Opts::new("codectrl_logs_received_total", "Logs received and stored"),
+            &["source"],
+        )
+        .unwrap();
+        let log_warnings = IntCounterVec::new(
+            Opts::new(
+                "codectrl_log_warnings_total",
+                "Warnings added to received logs that were missing something",
+            ),
+            &["kind"],
+        )
+        .unwrap();
+        let ingested_bytes = IntCounter::new(
+            "codectrl_ingested_bytes_total",
+            "Protobuf encoded size of the stored logs",
+        )
+        .unwrap();
+        let backup_write_duration = Histogram::with_opts(HistogramOpts::new(
+            "codectrl_backup_write_duration_seconds",
+            "How long the backup thread takes to write a connection to the database",
+        ))
+        .unwrap();
+        let backup_write_errors = IntCounter::new(
+            "codectrl_backup_write_errors_total",
+            "Connections that the backup thread could not write to the database",
+        )
+        .unwrap();
+        let connections =
+            IntGauge::new("codectrl_connections", "Registered connections").unwrap();
+        let log_queue_depth = IntGauge::new(
+            "codectrl_log_queue_depth",
+            "Stored logs that the slowest log stream has yet to send",
+        )
+        .unwrap();
+
+        registry.register(Box::new(logs_received.clone())).unwrap();
+        registry.register(Box::new(log_warnings.clone())).unwrap();
+        registry.register(Box::new(ingested_bytes.clone())).unwrap();
+        registry
+            .register(Box::new(backup_write_duration.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(backup_write_errors.clone()))
+            .unwrap();
+        registry.register(Box::new(connections.clone())).unwrap();
+        registry
+            .register(Box::new(log_queue_depth.clone()))
+            .unwrap();
+
+        Self {
+            registry,
+            logs_received,
+            log_warnings,
+            ingested_bytes,
+            backup_write_duration,
+            backup_write_errors,
+            connections,
+            log_queue_depth,
+        }
+    }
+
+    pub fn log_received(&self, source: LogSource) {
+        self.logs_received
+            .with_label_values(&[source.label()])
+            .inc();
+    }
+
+    pub fn log_warning(&self, warning: LogWarning) {
+        self.log_warnings
+            .with_label_values(&[warning.label()])
+            .inc();
+    }
+
+    pub fn bytes_ingested(&self, bytes: usize) {
+        self.ingested_bytes
+            .inc_by(u64::try_from(bytes).unwrap_or(u64::MAX));
+    }
+
+    pub fn backup_written(&self, duration: Duration, is_error: bool) {
+        self.backup_write_duration.observe(duration.as_secs_f64());
+
+        if is_error {
+            self.backup_write_errors.inc();
+        }
+    }
+
+    /// Encodes every metric in the Prometheus text format, after setting the
+    /// gauges that are only known by the service to `connections` and
+    /// `log_queue_depth`.
+    #[must_use]
+    pub fn render(&self, connections: usize, log_queue_depth: usize) -> String {
+        self.connections
+            .set(i64::try_from(connections).unwrap_or(i64::MAX));
+        self.log_queue_depth
+            .set(i64::try_from(log_queue_depth).unwrap_or(i64::MAX));
+
+        let mut buffer = Vec::new();
+
+        if let Err(error) =
+            TextEncoder::new().encode(&self.registry.gather(), &mut buffer)
+        {
+            error!("Could not encode the metrics: {error}");
+        }
+
+        String::from_utf8_lossy(&buffer).into_owned()
+    }
+}
@@ -0,0 +1,447 @@
+// region: imports
+
+use anyhow::{bail, Context};
+use log::warn;
+use oauth2::{
+    basic::BasicClient, reqwest::async_http_client, url::Url, AuthUrl, AuthorizationCode,
+    ClientId, ClientSecret, CsrfToken, RedirectUrl, Scope, TokenResponse, TokenUrl,
+};
+use reqwest::header::{ACCEPT, USER_AGENT};
+use serde::Deserialize;
+use serde_json::Value;
+use std::{env, fmt, str::FromStr, sync::Arc};
+
+// endregion
+
+/// Something users can log in with. The redirect handler hands the code that
+/// the provider redirects back with to [`OAuthProvider::identify`] to find out
+/// who logged in.
+#[tonic::async_trait]
+pub trait OAuthProvider: fmt::Debug + Send + Sync {
+    /// The name the provider was configured with, which is also the last
+    /// segment of its redirect URL.
+    fn name(&self) -> &str;
+
+    /// Generates the URL the user has to visit to log in, along with the CSRF
+    /// token that the provider will pass back to the redirect handler as the
+    /// `state`.
+    fn authorize_url(&self) -> (Url, CsrfToken);
+
+    /// Exchanges the authorization `code` for an access token and uses it to
+    /// find out who logged in. Returns the subject to issue a CodeCTRL token
+    /// for.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the code could not be exchanged or the user could
+    /// not be fetched.
+    async fn identify(&self, code: String) -> anyhow::Result<String>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProviderKind {
+    GitHub,
+    GitLab,
+    /// Any OpenID Connect provider (i.e. Keycloak), whose endpoints are found
+    /// through the issuer's discovery document.
+    Oidc,
+    /// Any other OAuth 2 provider, whose endpoints all have to be configured.
+    OAuth2,
+}
+
+impl FromStr for ProviderKind {
+    type Err = anyhow::Error;
+
+    fn from_str(kind: &str) -> Result<Self, Self::Err> {
+        match kind.to_lowercase().as_str() {
+            "github" => Ok(Self::GitHub),
+            "gitlab" => Ok(Self::GitLab),
+            "oidc" => Ok(Self::Oidc),
+            "oauth2" => Ok(Self::OAuth2),
+            _ => bail!("Unknown provider kind \"{kind}\""),
+        }
+    }
+}
+
+impl ProviderKind {
+    /// The kind of a provider that wasn't given one: providers named after a
+    /// kind (i.e. `github` or `gitlab`) are of that kind, providers with a base
+    /// URL are OIDC providers and any other provider is a plain OAuth 2
+    /// provider.
+    #[must_use]
+    pub fn default_for(name: &str, has_base_url: bool) -> Self {
+        match name.parse() {
+            Ok(kind) => kind,
+            Err(_) if has_base_url => Self::Oidc,
+            Err(_) => Self::OAuth2,
+        }
+    }
+}
+
+impl fmt::Display for ProviderKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            Self::GitHub => "github",
+            Self::GitLab => "gitlab",
+            Self::Oidc => "oidc",
+            Self::OAuth2 => "oauth2",
+        };
+        write!(f, "{kind}")
+    }
+}
+
+/// The configuration of a single provider. Any endpoint that is set overrides
+/// the default (or discovered) one, i.e. to point a provider at a mock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderConfig {
+    pub name: String,
+    pub kind: ProviderKind,
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    /// The URL of a self-hosted GitLab instance, or the issuer URL of an OIDC
+    /// provider.
+    pub base_url: Option<String>,
+    pub auth_url: Option<String>,
+    pub token_url: Option<String>,
+    pub user_url: Option<String>,
+    pub scopes: Option<Vec<String>>,
+    /// The field of the user info response that identifies the user.
+    pub subject_field: Option<String>,
+}
+
+impl ProviderConfig {
+    /// Reads the names of the providers to configure from the comma separated
+    /// `OAUTH_PROVIDERS` environment variable, defaulting to just `github`.
+    #[must_use]
+    pub fn names_from_env() -> Vec<String> {
+        env::var("OAUTH_PROVIDERS")
+            .unwrap_or_else(|_| "github".to_string())
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    }
+
+    /// Reads every provider named in `OAUTH_PROVIDERS`, skipping (and warning
+    /// about) any that are misconfigured.
+    #[must_use]
+    pub fn all_from_env() -> Vec<Self> {
+        Self::names_from_env()
+            .iter()
+            .filter_map(|name| match Self::from_env(name) {
+                Ok(config) => Some(config),
+                Err(error) => {
+                    warn!("Not enabling the \"{name}\" login provider: {error:#}");
+                    None
+                },
+            })
+            .collect()
+    }
+
+    /// Reads the provider called `name` from the `<NAME>_CLIENT_ID`,
+    /// `<NAME>_CLIENT_SECRET`, `<NAME>_KIND`, `<NAME>_BASE_URL`,
+    /// `<NAME>_AUTH_URL`, `<NAME>_TOKEN_URL`, `<NAME>_USER_URL`,
+    /// `<NAME>_SCOPES` (space separated) and `<NAME>_SUBJECT_FIELD`
+    /// environment variables, where `<NAME>` is `name` in upper case.
+    ///
+    /// If `<NAME>_KIND` is missing, the kind is worked out from the name, see
+    /// [`ProviderKind::default_for`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the client ID is missing or the kind is unknown.
+    pub fn from_env(name: &str) -> anyhow::Result<Self> {
+        let prefix = name.to_uppercase().replace('-', "_");
+        let var = |suffix: &str| {
+            env::var(format!("{prefix}_{suffix}"))
+                .ok()
+                .filter(|value| !value.is_empty())
+        };
+
+        let client_id = var("CLIENT_ID").with_context(|| {
+            format!("Missing the {prefix}_CLIENT_ID environment variable.")
+        })?;
+        let base_url = var("BASE_URL");
+
+        let kind = match var("KIND") {
+            Some(kind) => kind.parse()?,
+            None => ProviderKind::default_for(name, base_url.is_some()),
+        };
+
+        Ok(Self {
+            name: name.to_lowercase(),
+            kind,
+            client_id,
+            client_secret: var("CLIENT_SECRET"),
+            base_url,
+            auth_url: var("AUTH_URL"),
+            token_url: var("TOKEN_URL"),
+            user_url: var("USER_URL"),
+            scopes: var("SCOPES")
+                .map(|scopes| scopes.split_whitespace().map(String::from).collect()),
+            subject_field: var("SUBJECT_FIELD"),
+        })
+    }
+
+    /// Sets up the provider, redirecting back to the redirect handler on
+    /// `redirect_port`. OIDC providers fetch their discovery document here.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an endpoint is missing or invalid, or if the
+    /// discovery document could not be fetched.
+    pub async fn build(
+        &self,
+        redirect_port: u16,
+    ) -> anyhow::Result<Arc<dyn OAuthProvider>> {
+        let provider: Arc<dyn OAuthProvider> = match self.kind {
+            ProviderKind::GitHub => Arc::new(EndpointProvider::new(
+                self,
+                Endpoints {
+                    auth_url: "https://github.com/login/oauth/authorize".into(),
+                    token_url: "https://github.com/login/oauth/access_token".into(),
+                    user_url: "https://api.github.com/user".into(),
+                },
+                &["user:email"],
+                "login",
+                redirect_port,
+            )?),
+            ProviderKind::GitLab => {
+                let base_url = self
+                    .base_url
+                    .as_deref()
+                    .unwrap_or("https://gitlab.com")
+                    .trim_end_matches('/');
+
+                Arc::new(EndpointProvider::new(
+                    self,
+                    Endpoints {
+                        auth_url: format!("{base_url}/oauth/authorize"),
+                        token_url: format!("{base_url}/oauth/token"),
+                        user_url: format!("{base_url}/api/v4/user"),
+                    },
+                    &["read_user"],
+                    "username",
+                    redirect_port,
+                )?)
+            },
+            ProviderKind::Oidc =>
+                Arc::new(OidcProvider::discover(self, redirect_port).await?),
+            ProviderKind::OAuth2 => {
+                let missing = |endpoint: &str| {
+                    format!(
+                        "The {} provider has no {endpoint} URL configured.",
+                        self.name
+                    )
+                };
+
+                Arc::new(EndpointProvider::new(
+                    self,
+                    Endpoints {
+                        auth_url: self
+                            .auth_url
+                            .clone()
+                            .with_context(|| missing("auth"))?,
+                        token_url: self
+                            .token_url
+                            .clone()
+                            .with_context(|| missing("token"))?,
+                        user_url: self
+                            .user_url
+                            .clone()
+                            .with_context(|| missing("user"))?,
+                    },
+                    &[],
+                    "sub",
+                    redirect_port,
+                )?)
+            },
+        };
+
+        Ok(provider)
+    }
+}
+
+/// The default endpoints of a provider, before the configured overrides are
+/// applied.
+#[derive(Debug, Clone)]
+pub struct Endpoints {
+    pub auth_url: String,
+    pub token_url: String,
+    pub user_url: String,
+}
+
+/// A provider that exchanges the code at its token endpoint and identifies the
+/// user with a field of its user info endpoint's response, which covers GitHub,
+/// GitLab and most other OAuth 2 providers.
+#[derive(Debug, Clone)]
+pub struct EndpointProvider {
+    name: String,
+    client: BasicClient,
+    user_url: String,
+    scopes: Vec<String>,
+    subject_field: String,
+}
+
+impl EndpointProvider {
+    /// # Errors
+    ///
+    /// Returns an error if any of the endpoint URLs are invalid.
+    pub fn new(
+        config: &ProviderConfig,
+        defaults: Endpoints,
+        default_scopes: &[&str],
+        default_subject_field: &str,
+        redirect_port: u16,
+    ) -> anyhow::Result<Self> {
+        let auth_url = AuthUrl::new(config.auth_url.clone().unwrap_or(defaults.auth_url))
+            .context("Invalid authorization endpoint URL")?;
+        let token_url =
+            TokenUrl::new(config.token_url.clone().unwrap_or(defaults.token_url))
+                .context("Invalid token endpoint URL")?;
+
+        let client = BasicClient::new(
+            ClientId::new(config.client_id.clone()),
+            config.client_secret.clone().map(ClientSecret::new),
+            auth_url,
+            Some(token_url),
+        )
+        .set_redirect_uri(
+            RedirectUrl::new(format!(
+                "http://localhost:{redirect_port}/oauth/register/{}",
+                config.name
+            ))
+            .context("Invalid redirect URL")?,
+        );
+
+        Ok(Self {
+            name: config.name.clone(),
+            client,
+            user_url: config.user_url.clone().unwrap_or(defaults.user_url),
+            scopes: config.scopes.clone().unwrap_or_else(|| {
+                default_scopes.iter().map(ToString::to_string).collect()
+            }),
+            subject_field: config
+                .subject_field
+                .clone()
+                .unwrap_or_else(|| default_subject_field.to_string()),
+        })
+    }
+}
+
+#[tonic::async_trait]
+impl OAuthProvider for EndpointProvider {
+    fn name(&self) -> &str { &self.name }
+
+    fn authorize_url(&self) -> (Url, CsrfToken) {
+        self.client
+            .authorize_url(CsrfToken::new_random)
+            .add_scopes(self.scopes.iter().cloned().map(Scope::new))
+            .url()
+    }
+
+    async fn identify(&self, code: String) -> anyhow::Result<String> {
+        let token = self
+            .client
+            .exchange_code(AuthorizationCode::new(code))
+            .request_async(async_http_client)
+            .await
+            .context("Could not exchange the authorization code")?;
+
+        let user: Value = reqwest::Client::new()
+            .get(&self.user_url)
+            .bearer_auth(token.access_token().secret())
+            .header(USER_AGENT, "codectrl-server")
+            .header(ACCEPT, "application/json")
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let subject = match user.get(&self.subject_field) {
+            Some(Value::String(subject)) => subject.clone(),
+            Some(subject @ Value::Number(_)) => subject.to_string(),
+            _ => bail!("The user did not have a \"{}\" field", self.subject_field),
+        };
+
+        Ok(format!("{}:{subject}", self.name))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct DiscoveryDocument {
+    authorization_endpoint: String,
+    token_endpoint: String,
+    userinfo_endpoint: String,
+}
+
+/// An OpenID Connect provider (i.e. Keycloak), whose endpoints are read from
+/// `<issuer>/.well-known/openid-configuration`.
+#[derive(Debug, Clone)]
+pub struct OidcProvider {
+    issuer: String,
+    endpoints: EndpointProvider,
+}
+
+impl OidcProvider {
+    /// Fetches the issuer's discovery document and sets up the provider with
+    /// the endpoints in it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no issuer is configured, or the discovery document
+    /// could not be fetched or contains invalid endpoints.
+    pub async fn discover(
+        config: &ProviderConfig,
+        redirect_port: u16,
+    ) -> anyhow::Result<Self> {
+        let issuer = config
+            .base_url
+            .as_deref()
+            .with_context(|| {
+                format!("The {} provider has no issuer URL configured.", config.name)
+            })?
+            .trim_end_matches('/')
+            .to_string();
+
+        let discovery: DiscoveryDocument = reqwest::Client::new()
+            .get(format!("{issuer}/.well-known/openid-configuration"))
+            .header(USER_AGENT, "codectrl-server")
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+            .context("Could not read the OIDC discovery document")?;
+
+        let endpoints = EndpointProvider::new(
+            config,
+            Endpoints {
+                auth_url: discovery.authorization_endpoint,
+                token_url: discovery.token_endpoint,
+                user_url: discovery.userinfo_endpoint,
+            },
+            &["openid", "profile", "email"],
+            "preferred_username",
+            redirect_port,
+        )?;
+
+        Ok(Self { issuer, endpoints })
+    }
+
+    #[must_use]
+    pub fn issuer(&self) -> &str { &self.issuer }
+}
+
+#[tonic::async_trait]
+impl OAuthProvider for OidcProvider {
+    fn name(&self) -> &str { self.endpoints.name() }
+
+    fn authorize_url(&self) -> (Url, CsrfToken) { self.endpoints.authorize_url() }
+
+    async fn identify(&self, code: String) -> anyhow::Result<String> {
+        self.endpoints.identify(code).await
+    }
+}
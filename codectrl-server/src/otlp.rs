@@ -0,0 +1,373 @@
+// region: imports
+
+use crate::{
+    auth::Identity,
+    metrics::LogSource,
+    proto::opentelemetry::proto::{
+        collector::logs::v1::{
+            logs_service_server::LogsService, ExportLogsPartialSuccess,
+            ExportLogsServiceRequest, ExportLogsServiceResponse,
+        },
+        common::v1::{any_value::Value, AnyValue, KeyValue},
+        logs::v1::LogRecord,
+    },
+    severity::Severity,
+    tls, Service,
+};
+use codectrl_protobuf_bindings::data::{Log, LogData};
+use log::{info, warn};
+use once_cell::sync::OnceCell;
+use regex::Regex;
+use tonic::{Request, Response, Status};
+
+// endregion
+
+// The OpenTelemetry semantic conventions for the attributes that have a place
+// in a `Log`.
+pub const CODE_FILEPATH: &str = "code.filepath";
+pub const CODE_LINENO: &str = "code.lineno";
+pub const CODE_FUNCTION: &str = "code.function";
+pub const EXCEPTION_MESSAGE: &str = "exception.message";
+pub const EXCEPTION_STACKTRACE: &str = "exception.stacktrace";
+pub const EXCEPTION_TYPE: &str = "exception.type";
+pub const HOST_NAME: &str = "host.name";
+pub const SDK_LANGUAGE: &str = "telemetry.sdk.language";
+
+// Matches a single frame of the stack traces of the most common languages:
+// Python, C#, Java and JavaScript, then anything else that looks like
+// `file:line[:column]` (i.e. Rust, Go).
+static FRAME_REGEXES: OnceCell<[Result<Regex, regex::Error>; 4]> = OnceCell::new();
+
+fn frame_regexes() -> impl Iterator<Item = &'static Regex> {
+    FRAME_REGEXES
+        .get_or_init(|| {
+            [
+                Regex::new(
+                    r#"^File "(?P<file>[^"]+)", line (?P<line>\d+)(?:, in (?P<function>.+))?$"#,
+                ),
+                Regex::new(r"^at (?P<function>.+?) in (?P<file>.+):line (?P<line>\d+)$"),
+                Regex::new(
+                    r"^at (?:(?P<function>.+?) ?\()?(?P<file>[^()]+?):(?P<line>\d+)(?::(?P<column>\d+))?\)?$",
+                ),
+                Regex::new(
+                    r"(?P<file>[^\s():]+\.[A-Za-z0-9]+):(?P<line>\d+)(?::(?P<column>\d+))?",
+                ),
+            ]
+        })
+        .iter()
+        .filter_map(|regex| regex.as_ref().ok())
+}
+
+/// Formats an attribute or body value the way it would be written in most
+/// languages.
+#[must_use]
+pub fn any_value_to_string(value: &AnyValue) -> String {
+    match &value.value {
+        Some(Value::StringValue(string)) => string.clone(),
+        Some(Value::BoolValue(bool)) => bool.to_string(),
+        Some(Value::IntValue(int)) => int.to_string(),
+        Some(Value::DoubleValue(double)) => double.to_string(),
+        Some(Value::ArrayValue(array)) => format!(
+            "[{}]",
+            array
+                .values
+                .iter()
+                .map(any_value_to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Some(Value::KvlistValue(list)) => format!(
+            "{{{}}}",
+            list.values
+                .iter()
+                .map(|pair| format!(
+                    "{}: {}",
+                    pair.key,
+                    pair.value
+                        .as_ref()
+                        .map(any_value_to_string)
+                        .unwrap_or_default()
+                ))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Some(Value::BytesValue(bytes)) =>
+            bytes.iter().map(|byte| format!("{byte:02x}")).collect(),
+        None => String::new(),
+    }
+}
+
+fn type_name(value: &AnyValue) -> &'static str {
+    match &value.value {
+        Some(Value::StringValue(_)) => "String",
+        Some(Value::BoolValue(_)) => "Bool",
+        Some(Value::IntValue(_)) => "Int",
+        Some(Value::DoubleValue(_)) => "Double",
+        Some(Value::ArrayValue(_)) => "Array",
+        Some(Value::KvlistValue(_)) => "Map",
+        Some(Value::BytesValue(_)) => "Bytes",
+        None => "",
+    }
+}
+
+fn attribute<'a>(attributes: &'a [KeyValue], key: &str) -> Option<&'a AnyValue> {
+    attributes
+        .iter()
+        .find(|attribute| attribute.key == key)
+        .and_then(|attribute| attribute.value.as_ref())
+}
+
+fn string_attribute(attributes: &[KeyValue], key: &str) -> Option<String> {
+    attribute(attributes, key)
+        .map(any_value_to_string)
+        .filter(|value| !value.is_empty())
+}
+
+/// Maps the `telemetry.sdk.language` of an SDK onto the name of its syntax in
+/// the GUI.
+fn language_name(sdk_language: &str) -> &str {
+    match sdk_language {
+        "cpp" => "C++",
+        "dotnet" => "C#",
+        "erlang" => "Erlang",
+        "go" => "Go",
+        "java" => "Java",
+        "nodejs" | "webjs" => "JavaScript",
+        "php" => "PHP",
+        "python" => "Python",
+        "ruby" => "Ruby",
+        "rust" => "Rust",
+        "swift" => "Swift",
+        language => language,
+    }
+}
+
+/// Parses the frames out of an `exception.stacktrace`. Lines that aren't
+/// frames are skipped, except for the line of code that Python prints under
+/// each frame. The frames are returned outermost call first, like the stack of
+/// a `Log`.
+#[must_use]
+pub fn parse_stacktrace(stacktrace: &str) -> Vec<LogData> {
+    let mut frames: Vec<LogData> = Vec::new();
+    let mut is_most_recent_last = false;
+    let mut expects_code = false;
+
+    for line in stacktrace
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+    {
+        let frame = frame_regexes().enumerate().find_map(|(index, regex)| {
+            let captures = regex.captures(line)?;
+
+            Some((
+                index,
+                LogData {
+                    file_path: captures.name("file")?.as_str().into(),
+                    line_number: captures.name("line")?.as_str().parse().ok()?,
+                    column_number: captures
+                        .name("column")
+                        .and_then(|column| column.as_str().parse().ok())
+                        .unwrap_or(0),
+                    code: captures
+                        .name("function")
+                        .map(|function| function.as_str().to_string())
+                        .unwrap_or_default(),
+                    ..Default::default()
+                },
+            ))
+        });
+
+        match frame {
+            // Only Python prints the most recent call last, along with the line
+            // of code of each frame under it.
+            Some((0, frame)) => {
+                is_most_recent_last = true;
+                expects_code = true;
+                frames.push(frame);
+            },
+            _ if expects_code => {
+                if let Some(frame) = frames.last_mut() {
+                    frame.code = line.to_string();
+                }
+
+                expects_code = false;
+            },
+            Some((_, frame)) => frames.push(frame),
+            None => (),
+        }
+    }
+
+    if !is_most_recent_last {
+        frames.reverse();
+    }
+
+    frames
+}
+
+/// Maps an OTLP `SeverityNumber` onto a [`Severity`], each severity covers four
+/// numbers.
+#[must_use]
+pub fn severity_of_number(severity_number: i32) -> Option<Severity> {
+    match severity_number {
+        1..=4 => Some(Severity::Trace),
+        5..=8 => Some(Severity::Debug),
+        9..=12 => Some(Severity::Info),
+        13..=16 => Some(Severity::Warn),
+        17..=20 => Some(Severity::Error),
+        21..=24 => Some(Severity::Fatal),
+        _ => None,
+    }
+}
+
+/// Converts an OTLP log record into a `Log`, using the `code.*` and
+/// `exception.*` attributes of the record and the `telemetry.sdk.language` of
+/// the `resource` it came from. The severity of the record becomes the message
+/// type, as the type of the body is nearly always a string. The address is
+/// left for [`Service::verify_log`] to fill in.
+#[must_use]
+pub fn record_to_log(record: &LogRecord, resource: &[KeyValue]) -> Log {
+    let attributes = record.attributes.as_slice();
+
+    let mut message = record
+        .body
+        .as_ref()
+        .map(any_value_to_string)
+        .unwrap_or_default();
+    let mut message_type = record.body.as_ref().map(type_name).unwrap_or_default();
+
+    // Exceptions are often recorded without a body.
+    if message.is_empty() {
+        if let Some(exception) = string_attribute(attributes, EXCEPTION_MESSAGE) {
+            message = match string_attribute(attributes, EXCEPTION_TYPE) {
+                Some(exception_type) => format!("{exception_type}: {exception}"),
+                None => exception,
+            };
+            message_type = "String";
+        }
+    }
+
+    let file_name = string_attribute(attributes, CODE_FILEPATH).unwrap_or_default();
+    let line_number = attribute(attributes, CODE_LINENO)
+        .and_then(|line_number| match &line_number.value {
+            Some(Value::IntValue(line_number)) => u32::try_from(*line_number).ok(),
+            Some(Value::StringValue(line_number)) => line_number.parse().ok(),
+            _ => None,
+        })
+        .unwrap_or(0);
+
+    let mut stack = string_attribute(attributes, EXCEPTION_STACKTRACE)
+        .map(|stacktrace| parse_stacktrace(&stacktrace))
+        .unwrap_or_default();
+
+    if stack.is_empty() && !file_name.is_empty() {
+        stack.push(LogData {
+            file_path: file_name.clone(),
+            line_number,
+            code: string_attribute(attributes, CODE_FUNCTION).unwrap_or_default(),
+            ..Default::default()
+        });
+    }
+
+    let message_type = if record.severity_text.is_empty() {
+        severity_of_number(record.severity_number)
+            .map_or_else(|| message_type.to_string(), |severity| severity.to_string())
+    } else {
+        record.severity_text.clone()
+    };
+
+    Log {
+        message,
+        message_type,
+        file_name,
+        line_number,
+        language: string_attribute(resource, SDK_LANGUAGE)
+            .map(|language| language_name(&language).to_string())
+            .unwrap_or_default(),
+        stack,
+        ..Default::default()
+    }
+}
+
+#[tonic::async_trait]
+impl LogsService for Service {
+    async fn export(
+        &self,
+        request: Request<ExportLogsServiceRequest>,
+    ) -> Result<Response<ExportLogsServiceResponse>, Status> {
+        let remote_addr = request.remote_addr();
+        let metadata = request.metadata().clone();
+        let client_subject = tls::client_subject(&request);
+        // The interceptor has already rejected anonymous exporters if they have
+        // to log in.
+        let claims = Identity::require(&request, false)?.cloned();
+        let request = request.into_inner();
+
+        let mut amount = 0;
+        // Records that can't be stored are rejected one by one rather than
+        // failing the whole export, as the exporter would retry the records that
+        // were already stored as well.
+        let mut rejected = 0;
+        let mut error_message = None;
+
+        for resource_logs in request.resource_logs {
+            let resource = resource_logs
+                .resource
+                .map(|resource| resource.attributes)
+                .unwrap_or_default();
+
+            // The host the resource runs on is more useful than the address of
+            // the collector that forwarded it to us.
+            let mut metadata = metadata.clone();
+            let remote_addr = match string_attribute(&resource, HOST_NAME)
+                .and_then(|host| host.parse().ok())
+            {
+                Some(host) => {
+                    metadata.insert("x-host", host);
+                    None
+                },
+                None => remote_addr,
+            };
+
+            for record in resource_logs
+                .scope_logs
+                .iter()
+                .flat_map(|scope_logs| &scope_logs.log_records)
+            {
+                let result = match Self::verify_log(
+                    record_to_log(record, &resource),
+                    remote_addr,
+                    &metadata,
+                    claims.as_ref(),
+                ) {
+                    Ok(mut log) => {
+                        log.client_subject = client_subject.clone();
+                        self.store_log(log, LogSource::Otlp).await
+                    },
+                    Err(status) => Err(status),
+                };
+
+                match result {
+                    Ok(()) => amount += 1,
+                    Err(status) => {
+                        rejected += 1;
+                        error_message.get_or_insert_with(|| status.message().to_string());
+                    },
+                }
+            }
+        }
+
+        info!(target: "codectrl_server - otlp receiver", "{amount} log(s) exported to us");
+
+        let partial_success = error_message.map(|error_message| {
+            warn!(target: "codectrl_server - otlp receiver", "Rejected {rejected} log(s): {error_message}");
+
+            ExportLogsPartialSuccess {
+                rejected_log_records: rejected,
+                error_message,
+            }
+        });
+
+        Ok(Response::new(ExportLogsServiceResponse { partial_success }))
+    }
+}
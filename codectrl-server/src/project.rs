@@ -0,0 +1,196 @@
+// region: imports
+
+use crate::auth::TokenClaims;
+use serde::Deserialize;
+use std::{collections::HashMap, env};
+use tonic::{metadata::MetadataMap, Status};
+
+// endregion
+
+pub use codectrl_common::project::PROJECT_HEADER;
+
+/// Which projects' logs a request may send or see, based on the projects in the
+/// caller's token and the project asked for in the `x-project` header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProjectScope {
+    /// Every log, including the ones that don't belong to a project.
+    All,
+    /// Only the logs that belong to one of these projects.
+    Projects(Vec<String>),
+}
+
+impl ProjectScope {
+    /// Works out the scope of a request made with `claims`. A token that is
+    /// limited to some projects can only narrow its scope down to one of those
+    /// projects with the `x-project` header, while an unlimited token (or no
+    /// token at all) can ask for any project.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Status::permission_denied` if the header asks for a project
+    /// that the token isn't allowed to access, or `Status::invalid_argument` if
+    /// the header isn't valid.
+    pub fn of(
+        claims: Option<&TokenClaims>,
+        metadata: &MetadataMap,
+    ) -> Result<Self, Status> {
+        let requested = match metadata.get(PROJECT_HEADER) {
+            Some(project) => Some(project.to_str().map_err(|_| {
+                Status::invalid_argument(format!(
+                    "The {PROJECT_HEADER} header is not valid"
+                ))
+            })?)
+            .map(str::trim)
+            .filter(|project| !project.is_empty()),
+            None => None,
+        };
+
+        let allowed = claims
+            .map(|claims| claims.projects.as_slice())
+            .filter(|projects| !projects.is_empty());
+
+        match (allowed, requested) {
+            (None, None) => Ok(Self::All),
+            (None, Some(requested)) => Ok(Self::Projects(vec![requested.into()])),
+            (Some(allowed), Some(requested))
+                if allowed.iter().any(|project| project == requested) =>
+                Ok(Self::Projects(vec![requested.into()])),
+            (Some(_), Some(requested)) => Err(Status::permission_denied(format!(
+                "Not a member of the \"{requested}\" project"
+            ))),
+            (Some(allowed), None) => Ok(Self::Projects(allowed.to_vec())),
+        }
+    }
+
+    /// Whether a log that belongs to `project` is within this scope.
+    #[must_use]
+    pub fn allows(&self, project: Option<&str>) -> bool {
+        match self {
+            Self::All => true,
+            Self::Projects(projects) => matches!(
+                project,
+                Some(project) if projects.iter().any(|allowed| allowed == project)
+            ),
+        }
+    }
+
+    /// The project that a log sent within this scope belongs to.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Status::invalid_argument` if the scope covers several projects,
+    /// as then the `x-project` header has to say which one the log is for.
+    pub fn log_project(&self) -> Result<Option<String>, Status> {
+        match self {
+            Self::All => Ok(None),
+            Self::Projects(projects) => match projects.as_slice() {
+                [project] => Ok(Some(project.clone())),
+                _ => Err(Status::invalid_argument(format!(
+                    "The token belongs to several projects, so the {PROJECT_HEADER} \
+                     header has to say which project the log belongs to"
+                ))),
+            },
+        }
+    }
+
+    /// The projects to put in a token issued within this scope.
+    #[must_use]
+    pub fn into_token_projects(self) -> Vec<String> {
+        match self {
+            Self::All => Vec::new(),
+            Self::Projects(projects) => projects,
+        }
+    }
+}
+
+/// Which projects the users that log in through an OAuth provider belong to.
+/// In the `[projects]` table of the config file, each project lists the
+/// subjects of its members:
+///
+/// ```toml
+/// [projects]
+/// project-a = ["github:alice", "gitlab:bob"]
+/// "*" = ["github:admin"]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(from = "HashMap<String, Vec<String>>")]
+pub struct ProjectMembers(HashMap<String, Vec<String>>);
+
+impl<P, S> FromIterator<(P, S)> for ProjectMembers
+where
+    P: AsRef<str>,
+    S: IntoIterator,
+    S::Item: AsRef<str>,
+{
+    fn from_iter<I: IntoIterator<Item = (P, S)>>(projects: I) -> Self {
+        let mut projects_of = HashMap::<String, Vec<String>>::new();
+
+        for (project, subjects) in projects {
+            for subject in subjects {
+                let subject = subject.as_ref().trim();
+
+                if subject.is_empty() {
+                    continue;
+                }
+
+                projects_of
+                    .entry(subject.to_string())
+                    .or_default()
+                    .push(project.as_ref().trim().to_string());
+            }
+        }
+
+        Self(projects_of)
+    }
+}
+
+impl From<HashMap<String, Vec<String>>> for ProjectMembers {
+    fn from(projects: HashMap<String, Vec<String>>) -> Self {
+        projects.into_iter().collect()
+    }
+}
+
+impl ProjectMembers {
+    /// The project that grants access to every project.
+    pub const ALL_PROJECTS: &'static str = "*";
+
+    /// Parses a list of projects in the form
+    /// `project-a=github:alice,gitlab:bob;project-b=gitlab:carol`, where each
+    /// member is the subject of their tokens.
+    #[must_use]
+    pub fn parse(members: &str) -> Self {
+        members
+            .split(';')
+            .filter_map(|project| project.split_once('='))
+            .map(|(project, subjects)| (project, subjects.split(',')))
+            .collect()
+    }
+
+    /// Reads the members from the `PROJECT_MEMBERS` environment variable, see
+    /// [`ProjectMembers::parse`].
+    #[must_use]
+    pub fn from_env() -> Self {
+        env::var("PROJECT_MEMBERS")
+            .map(|members| Self::parse(&members))
+            .unwrap_or_default()
+    }
+
+    /// The projects to put in the token of `subject`, where an empty list
+    /// means every project. Returns `None` if projects are configured but
+    /// `subject` isn't a member of any of them, in which case they shouldn't be
+    /// able to log in at all.
+    #[must_use]
+    pub fn projects_of(&self, subject: &str) -> Option<Vec<String>> {
+        if self.0.is_empty() {
+            return Some(Vec::new());
+        }
+
+        let projects = self.0.get(subject)?;
+
+        if projects.iter().any(|project| project == Self::ALL_PROJECTS) {
+            Some(Vec::new())
+        } else {
+            Some(projects.clone())
+        }
+    }
+}
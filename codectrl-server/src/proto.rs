@@ -0,0 +1,43 @@
+//! gRPC services that only this server provides, which are not (yet) part of
+//! `codectrl-protobuf-bindings`. The ones that clients use too live in
+//! `codectrl-common`.
+
+#![allow(clippy::pedantic, clippy::derive_partial_eq_without_eq)]
+
+pub mod login_providers {
+    tonic::include_proto!("codectrl.server.login_providers");
+}
+
+pub use codectrl_common::proto::log_search;
+
+/// The parts of the OpenTelemetry protocol (OTLP) that are needed to receive
+/// logs, nested like their packages so that they can refer to each other.
+pub mod opentelemetry {
+    pub mod proto {
+        pub mod common {
+            pub mod v1 {
+                tonic::include_proto!("opentelemetry.proto.common.v1");
+            }
+        }
+
+        pub mod resource {
+            pub mod v1 {
+                tonic::include_proto!("opentelemetry.proto.resource.v1");
+            }
+        }
+
+        pub mod logs {
+            pub mod v1 {
+                tonic::include_proto!("opentelemetry.proto.logs.v1");
+            }
+        }
+
+        pub mod collector {
+            pub mod logs {
+                pub mod v1 {
+                    tonic::include_proto!("opentelemetry.proto.collector.logs.v1");
+                }
+            }
+        }
+    }
+}
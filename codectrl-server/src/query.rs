@@ -0,0 +1,21 @@
+// region: imports
+
+use crate::proto::log_search::LogQuery;
+
+// endregion
+
+pub use codectrl_common::query::LogMatcher;
+
+/// The default amount of logs in a page of query results.
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+/// The most logs that a page of query results can hold.
+pub const MAX_PAGE_SIZE: usize = 1000;
+
+/// How many logs a page of results for `query` holds.
+#[must_use]
+pub fn page_size(query: &LogQuery) -> usize {
+    match usize::try_from(query.page_size) {
+        Ok(0) | Err(_) => DEFAULT_PAGE_SIZE,
+        Ok(page_size) => page_size.min(MAX_PAGE_SIZE),
+    }
+}
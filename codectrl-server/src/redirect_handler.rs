@@ -1,15 +1,24 @@
 // region: imports
 
+use crate::{
+    auth::{TokenClaims, TokenSecret, DEFAULT_TOKEN_LIFETIME},
+    oauth::OAuthProvider,
+    project::ProjectMembers,
+};
+use dashmap::DashMap;
 use futures::Future;
-use log::{debug, info, trace};
+use log::{debug, error, info, trace};
 use parking_lot::Mutex;
 use serde_json::json;
 use std::{
+    collections::HashMap,
+    convert::Infallible,
     fmt,
     sync::{
         atomic::{AtomicU16, Ordering},
         Arc,
     },
+    time::Instant,
 };
 use tokio::{
     runtime::Handle,
@@ -20,34 +29,17 @@ use tokio::{
     task::JoinHandle,
     time::{sleep, Duration},
 };
-use warp::Filter;
+use tonic::Status;
+use warp::{
+    http::StatusCode,
+    reply::{self, Json, WithStatus},
+    Filter,
+};
 
 // endregion
 
-#[derive(Debug, Clone, Copy)]
-enum OAuthProvider {
-    Unknown,
-    GitHub,
-}
-
-impl From<String> for OAuthProvider {
-    fn from(provider: String) -> Self {
-        match provider.to_lowercase().as_str() {
-            "github" => Self::GitHub,
-            _ => Self::Unknown,
-        }
-    }
-}
-
-impl fmt::Display for OAuthProvider {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let provider = match self {
-            Self::GitHub => "github",
-            Self::Unknown => "unknown",
-        };
-        write!(f, "{provider}")
-    }
-}
+/// How long a started login may take before it is forgotten about.
+const PENDING_LOGIN_TIMEOUT: Duration = Duration::from_secs(60 * 10);
 
 struct ThreadHandle<T: Send + Sync + 'static> {
     is_closed_rx: Mutex<Option<WatchReceiver<bool>>>,
@@ -66,11 +58,17 @@ impl<T: Send + Sync + 'static> ThreadHandle<T> {
     where
         F: Future<Output = T> + Send + 'static,
     {
-        *self.is_closed_rx.lock() = Some(is_closed_rx);
+        // `inner` stays locked until the server is spawned, so that two starts
+        // can't both spawn one.
         let mut lock = self.inner.lock();
 
-        if lock.is_none() {
+        // A handle that has finished belongs to a server that was closed, so it
+        // can be replaced by a new one. `is_closed_rx` is only replaced along
+        // with it, as the sender of a receiver passed to a start that doesn't
+        // spawn is dropped without ever saying that the server was closed.
+        if lock.as_ref().map_or(true, JoinHandle::is_finished) {
             info!(target: "codectrl_server - redirect handler", "Handle not spawned. Spawning now...");
+            *self.is_closed_rx.lock() = Some(is_closed_rx);
             *lock = Some(handle.spawn(f));
         } else {
             debug!(target: "codectrl_server - redirect handler", "Handle already spawned");
@@ -102,26 +100,186 @@ impl<T: Send + Sync + 'static> ThreadHandle<T> {
     }
 }
 
+struct PendingLogin {
+    provider: Arc<dyn OAuthProvider>,
+    started: Instant,
+    token: Option<String>,
+}
+
+fn json_reply(status: StatusCode, message: &str) -> WithStatus<Json> {
+    reply::with_status(reply::json(&json!({ "message": message })), status)
+}
+
 pub struct RedirectHandler {
     port: u16,
     inner_handle: ThreadHandle<()>,
     count: AtomicU16,
+    token_secret: TokenSecret,
+    project_members: ProjectMembers,
+    pending_logins: DashMap<String, PendingLogin>,
+}
+
+impl fmt::Debug for RedirectHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RedirectHandler")
+            .field("port", &self.port)
+            .field("count", &self.count)
+            .finish_non_exhaustive()
+    }
 }
 
 impl RedirectHandler {
     #[must_use]
-    pub fn new(port: u16) -> Arc<Self> {
+    pub fn new(
+        port: u16,
+        token_secret: TokenSecret,
+        project_members: ProjectMembers,
+    ) -> Arc<Self> {
         Arc::new(Self {
             port,
             inner_handle: ThreadHandle::none(),
             count: AtomicU16::new(0),
+            token_secret,
+            project_members,
+            pending_logins: DashMap::new(),
         })
     }
 
-    pub fn start(self: &Arc<Self>, runtime_handle: Handle) {
+    #[must_use]
+    pub fn port(&self) -> u16 { self.port }
+
+    /// Keeps track of a login that was started with the given CSRF `state`. The
+    /// handler stays open until the resulting token has been collected, or the
+    /// login times out.
+    pub fn add_pending_login(&self, state: String, provider: Arc<dyn OAuthProvider>) {
+        self.pending_logins.insert(
+            state,
+            PendingLogin {
+                provider,
+                started: Instant::now(),
+                token: None,
+            },
+        );
+        self.register();
+    }
+
+    fn remove_expired_logins(&self) {
+        let before = self.pending_logins.len();
+
+        self.pending_logins
+            .retain(|_, login| login.started.elapsed() < PENDING_LOGIN_TIMEOUT);
+
+        for _ in self.pending_logins.len()..before {
+            self.unregister();
+        }
+    }
+
+    /// Handles the provider redirecting back to us after the user logged in:
+    /// validates the `state`, exchanges the `code` and issues a CodeCTRL token
+    /// for whoever logged in.
+    async fn complete_login(
+        self: Arc<Self>,
+        provider: String,
+        query: HashMap<String, String>,
+    ) -> Result<WithStatus<Json>, Infallible> {
+        let (code, state) = match (query.get("code"), query.get("state")) {
+            (Some(code), Some(state)) => (code.clone(), state.clone()),
+            _ =>
+                return Ok(json_reply(
+                    StatusCode::BAD_REQUEST,
+                    "Missing the code or state parameters",
+                )),
+        };
+
+        let login_provider = match self.pending_logins.get(&state) {
+            Some(login)
+                if login.token.is_none()
+                    && login.provider.name() == provider.to_lowercase() =>
+                Arc::clone(&login.provider),
+            _ =>
+                return Ok(json_reply(
+                    StatusCode::BAD_REQUEST,
+                    "Unknown or expired login state",
+                )),
+        };
+
+        let token = match login_provider.identify(code).await {
+            Ok(subject) => match self.project_members.projects_of(&subject) {
+                Some(projects) => self
+                    .token_secret
+                    .sign(
+                        &TokenClaims::new(&subject, DEFAULT_TOKEN_LIFETIME)
+                            .with_projects(projects),
+                    )
+                    .map(|token| (subject, token))
+                    .map_err(anyhow::Error::from),
+                None => Err(anyhow::anyhow!(
+                    "{subject} is not a member of any project on this server"
+                )),
+            },
+            Err(error) => Err(error),
+        };
+
+        match token {
+            Ok((subject, token)) => {
+                if let Some(mut login) = self.pending_logins.get_mut(&state) {
+                    login.token = Some(token);
+                }
+
+                info!(target: "codectrl_server - redirect handler", "{subject} logged in using {provider}");
+
+                Ok(json_reply(
+                    StatusCode::OK,
+                    &format!(
+                        "Logged in as {subject}, you can now close this window and \
+                         return to CodeCTRL."
+                    ),
+                ))
+            },
+            Err(error) => {
+                error!(target: "codectrl_server - redirect handler", "Could not log in using {provider}: {error:#}");
+
+                if self.pending_logins.remove(&state).is_some() {
+                    self.unregister();
+                }
+
+                Ok(json_reply(
+                    StatusCode::BAD_GATEWAY,
+                    &format!("Could not log in: {error}"),
+                ))
+            },
+        }
+    }
+
+    /// Hands the token of a completed login over to whoever started it (i.e.
+    /// the GUI, which polls this through the `CollectToken` RPC until the user
+    /// has logged in). Returns `None` while the user has yet to log in.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Status::not_found` if the login is unknown, has expired or
+    /// could not be completed.
+    pub fn collect_token(&self, state: &str) -> Result<Option<String>, Status> {
+        let token = self
+            .pending_logins
+            .get(state)
+            .map(|login| login.token.clone())
+            .ok_or_else(|| Status::not_found("Unknown or expired login state"))?;
+
+        if token.is_some() && self.pending_logins.remove(state).is_some() {
+            self.unregister();
+        }
+
+        Ok(token)
+    }
+
+    /// Starts the temporary Warp server if it isn't running yet, and waits
+    /// until it is accepting requests.
+    pub async fn start(self: &Arc<Self>, runtime_handle: Handle) {
         let arc_self = Arc::clone(self);
 
         let (is_closed_sender, is_closed_receiver) = watch::channel(false);
+        let (started_sender, started_receiver) = oneshot::channel();
 
         self.inner_handle.init(&runtime_handle.clone(), is_closed_receiver, async move {
             let arc_self_clone = Arc::clone(&arc_self);
@@ -133,26 +291,31 @@ impl RedirectHandler {
                sleep(Duration::new(5, 0)).await;
 
                 loop {
+                    arc_self_clone.remove_expired_logins();
+
                     if arc_self_clone.count.load(Ordering::SeqCst) == 0 {
                         info!(target: "codectrl_server - redirect handler", "Closing temporary Warp server...");
                         arc_self_clone.close(close_signal_sender);
                         break;
                     }
+
+                    sleep(Duration::from_millis(100)).await;
                 }
             });
 
-            let register = warp::path!("oauth" / "register" / String).map(|provider: String| {
-                // TODO: Properly handle response from OAuth providers here.
-
-                let provider: OAuthProvider = provider.into();
-                warp::reply::json(&json!({ "message" : format!("Hello {provider}") }))
-            });
+            let register_handler = Arc::clone(&arc_self);
+            let register = warp::path!("oauth" / "register" / String)
+                .and(warp::query::<HashMap<String, String>>())
+                .and_then(move |provider: String, query| {
+                    Arc::clone(&register_handler).complete_login(provider, query)
+                });
 
             info!(target: "codectrl_server - redirect handler", "Spinning up temporary Warp server on 127.0.0.1:{}.", arc_self.port);
 
             let (_, server) = warp::serve(register)
                 .bind_with_graceful_shutdown(([127, 0, 0, 1], arc_self.port), async move {
                     _ = is_closed_sender.send(false);
+                    _ = started_sender.send(());
                     close_signal_receiver.await.ok();
                     info!(target: "codectrl_server - redirect handler", "Closed temporary Warp server.");
                     _ = is_closed_sender.send(true);
@@ -161,11 +324,9 @@ impl RedirectHandler {
             server.await;
         });
 
-        loop {
-            if self.is_started() {
-                break;
-            }
-        }
+        // The sender is dropped without sending if the server was already running
+        // or couldn't be bound, either way there is nothing left to wait for.
+        _ = started_receiver.await;
     }
 
     pub fn register(&self) { _ = self.count.fetch_add(1, Ordering::SeqCst); }
@@ -0,0 +1,130 @@
+// region: imports
+
+use chrono::{DateTime, Utc};
+use log::warn;
+use sea_orm::{DbBackend, FromQueryResult, Statement};
+use std::{env, str::FromStr, time::Duration};
+
+// endregion
+
+/// How many of the oldest logs the retention thread reads at a time.
+pub const RETENTION_PAGE_SIZE: u64 = 1000;
+
+/// Limits on how many logs the server keeps around. Any limit that is `None`
+/// is not enforced, so the default policy keeps every log forever.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// The maximum amount of logs to keep.
+    pub max_count: Option<u64>,
+    /// The maximum age of a log, from when it was received.
+    pub max_age: Option<Duration>,
+    /// The maximum amount of bytes (protobuf encoded size) of all logs
+    /// combined.
+    pub max_bytes: Option<u64>,
+}
+
+pub(crate) fn parse_env_var<T: FromStr>(name: &str) -> Option<T> {
+    let value = env::var(name).ok()?;
+
+    if let Ok(value) = value.parse() {
+        Some(value)
+    } else {
+        warn!("{name} was found but \"{value}\" is not a valid value, ignoring it!");
+        None
+    }
+}
+
+impl RetentionPolicy {
+    /// Reads the policy from the `RETENTION_MAX_LOGS`, `RETENTION_MAX_AGE` (in
+    /// seconds) and `RETENTION_MAX_BYTES` environment variables.
+    #[must_use]
+    pub fn from_env() -> Self {
+        Self {
+            max_count: parse_env_var("RETENTION_MAX_LOGS"),
+            max_age: parse_env_var("RETENTION_MAX_AGE").map(Duration::from_secs),
+            max_bytes: parse_env_var("RETENTION_MAX_BYTES"),
+        }
+    }
+
+    #[must_use]
+    pub fn is_unbounded(&self) -> bool {
+        self.max_count.is_none() && self.max_age.is_none() && self.max_bytes.is_none()
+    }
+
+    /// Given the oldest stored logs, ordered from oldest to newest, and the
+    /// totals of every stored log, returns how many of `rows` have to be
+    /// evicted to satisfy this policy. If every row in `rows` is evicted,
+    /// the next oldest logs may have to be evicted too.
+    #[must_use]
+    pub fn evict_count(
+        &self,
+        rows: &[RetentionRow],
+        totals: RetentionTotals,
+        now: DateTime<Utc>,
+    ) -> usize {
+        let cutoff = self
+            .max_age
+            .and_then(|max_age| chrono::Duration::from_std(max_age).ok())
+            .and_then(|max_age| now.checked_sub_signed(max_age));
+
+        let mut remaining_count = u64::try_from(totals.count).unwrap_or(0);
+        let mut remaining_bytes = u64::try_from(totals.bytes).unwrap_or(0);
+
+        let mut evicted = 0;
+
+        for row in rows {
+            let is_too_old = matches!(cutoff, Some(cutoff) if row.received_at < cutoff);
+            let is_too_many =
+                matches!(self.max_count, Some(max) if remaining_count > max);
+            let is_too_big = matches!(self.max_bytes, Some(max) if remaining_bytes > max);
+
+            if !is_too_old && !is_too_many && !is_too_big {
+                break;
+            }
+
+            evicted += 1;
+            remaining_count = remaining_count.saturating_sub(1);
+            remaining_bytes =
+                remaining_bytes.saturating_sub(u64::try_from(row.size).unwrap_or(0));
+        }
+
+        evicted
+    }
+}
+
+/// The subset of a stored log's columns needed to enforce a
+/// [`RetentionPolicy`].
+#[derive(Debug, Clone, FromQueryResult)]
+pub struct RetentionRow {
+    pub id: i64,
+    pub uuid: String,
+    pub received_at: DateTime<Utc>,
+    pub size: i64,
+}
+
+/// How many logs are stored and how many bytes they take up combined, so that
+/// the count and size limits of a [`RetentionPolicy`] can be checked without
+/// reading every log.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, FromQueryResult)]
+pub struct RetentionTotals {
+    pub count: i64,
+    pub bytes: i64,
+}
+
+impl RetentionTotals {
+    /// The statement that counts the stored logs and adds up their sizes.
+    #[must_use]
+    pub fn statement(backend: DbBackend) -> Statement {
+        Statement::from_string(
+            backend,
+            "SELECT COUNT(*) AS count, COALESCE(SUM(size), 0) AS bytes FROM log"
+                .to_owned(),
+        )
+    }
+
+    /// Takes the `evicted` rows out of the totals.
+    pub fn evict(&mut self, evicted: &[RetentionRow]) {
+        self.count -= i64::try_from(evicted.len()).unwrap_or(i64::MAX);
+        self.bytes -= evicted.iter().map(|row| row.size).sum::<i64>();
+    }
+}
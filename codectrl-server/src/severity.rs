@@ -0,0 +1,29 @@
+// region: imports
+
+use codectrl_protobuf_bindings::data::Log;
+use tonic::{metadata::MetadataMap, Status};
+
+// endregion
+
+pub use codectrl_common::severity::{Severity, SEVERITY_HEADER};
+
+/// Works out the severity of a received `log`, preferring the `x-severity`
+/// header over the message type. Logs are `Info` unless either says otherwise.
+///
+/// # Errors
+///
+/// Returns `Status::invalid_argument` if the header isn't a severity.
+pub fn of(log: &Log, metadata: &MetadataMap) -> Result<Severity, Status> {
+    match metadata.get(SEVERITY_HEADER) {
+        Some(severity) => severity
+            .to_str()
+            .ok()
+            .and_then(|severity| severity.parse().ok())
+            .ok_or_else(|| {
+                Status::invalid_argument(format!(
+                    "The {SEVERITY_HEADER} header is not a valid severity"
+                ))
+            }),
+        None => Ok(Severity::from_message_type(&log.message_type).unwrap_or_default()),
+    }
+}
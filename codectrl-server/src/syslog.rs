@@ -0,0 +1,627 @@
+// region: imports
+
+use crate::{
+    fields::Fields, metrics::LogSource, project::PROJECT_HEADER,
+    retention::parse_env_var, Service,
+};
+use codectrl_protobuf_bindings::data::Log;
+use log::{error, info, trace, warn};
+use serde::Deserialize;
+use serde_json::Value;
+use std::{env, io, net::SocketAddr};
+use tokio::{
+    io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, BufReader},
+    net::{TcpListener, TcpStream, UdpSocket},
+};
+use tonic::{metadata::MetadataMap, Status};
+
+// endregion
+
+/// The largest syslog record that is accepted, anything longer is truncated
+/// (UDP) or closes the connection (TCP).
+pub const MAX_RECORD_SIZE: usize = 64 * 1024;
+
+/// Reads the next record of a TCP syslog stream, which is either octet counted
+/// (`<length> <record>`, see RFC 6587) or ends with a newline. Returns `None`
+/// once the stream has ended.
+///
+/// # Errors
+///
+/// Returns an `InvalidData` error if the record is longer than
+/// [`MAX_RECORD_SIZE`] or its length is invalid, as the rest of the stream
+/// can't be framed after that, along with any error reading the stream.
+pub async fn read_record<R>(reader: &mut R) -> io::Result<Option<Vec<u8>>>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let is_octet_counted = match reader.fill_buf().await?.first() {
+        Some(byte) => byte.is_ascii_digit(),
+        None => return Ok(None),
+    };
+
+    let mut record = Vec::new();
+
+    if is_octet_counted {
+        let mut length = Vec::new();
+        (&mut *reader).take(8).read_until(b' ', &mut length).await?;
+
+        let length = std::str::from_utf8(&length)
+            .ok()
+            .and_then(|length| length.trim().parse::<usize>().ok())
+            .filter(|length| *length <= MAX_RECORD_SIZE)
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "Invalid record length")
+            })?;
+
+        record.resize(length, 0);
+        reader.read_exact(&mut record).await?;
+    } else {
+        (&mut *reader)
+            .take(MAX_RECORD_SIZE as u64)
+            .read_until(b'\n', &mut record)
+            .await?;
+
+        // Stopping short of the newline would leave the rest of the record to
+        // be read as the next one.
+        if record.len() == MAX_RECORD_SIZE && record.last() != Some(&b'\n') {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("The record is longer than {MAX_RECORD_SIZE} bytes"),
+            ));
+        }
+    }
+
+    Ok(Some(record))
+}
+
+/// The names of the syslog severities, indexed by severity.
+pub const SEVERITIES: [&str; 8] = [
+    "Emergency",
+    "Alert",
+    "Critical",
+    "Error",
+    "Warning",
+    "Notice",
+    "Informational",
+    "Debug",
+];
+
+/// The names of the syslog facilities, indexed by facility.
+pub const FACILITIES: [&str; 24] = [
+    "kern",
+    "user",
+    "mail",
+    "daemon",
+    "auth",
+    "syslog",
+    "lpr",
+    "news",
+    "uucp",
+    "cron",
+    "authpriv",
+    "ftp",
+    "ntp",
+    "security",
+    "console",
+    "solaris-cron",
+    "local0",
+    "local1",
+    "local2",
+    "local3",
+    "local4",
+    "local5",
+    "local6",
+    "local7",
+];
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+fn protocol_default() -> bool { true }
+
+/// Where and how to listen for syslog records, either from the `[syslog]`
+/// table of the config file or from the environment, see
+/// [`SyslogConfig::from_env`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct SyslogConfig {
+    pub port: u16,
+    #[serde(default = "protocol_default")]
+    pub udp: bool,
+    #[serde(default = "protocol_default")]
+    pub tcp: bool,
+    /// The project that every received record belongs to, as syslog has no
+    /// way of sending a token or `x-project` header.
+    #[serde(default)]
+    pub project: Option<String>,
+    /// Whether to listen even though the server requires authentication, which
+    /// lets anyone who can reach the port store logs.
+    #[serde(default)]
+    pub allow_unauthenticated: bool,
+}
+
+impl SyslogConfig {
+    /// Reads the config from the `SYSLOG_PORT`, `SYSLOG_PROTOCOLS` (`udp`,
+    /// `tcp` or `udp,tcp`, the default), `SYSLOG_PROJECT` and
+    /// `SYSLOG_ALLOW_UNAUTHENTICATED` (`0` or `1`) environment variables.
+    /// Returns `None` if `SYSLOG_PORT` isn't set, as the listener is disabled
+    /// by default.
+    #[must_use]
+    pub fn from_env() -> Option<Self> {
+        let port = parse_env_var("SYSLOG_PORT")?;
+        let protocols = env::var("SYSLOG_PROTOCOLS")
+            .map(|protocols| protocols.to_lowercase())
+            .unwrap_or_else(|_| "udp,tcp".into());
+
+        let has_protocol =
+            |name: &str| protocols.split(',').any(|protocol| protocol.trim() == name);
+
+        let config = Self {
+            port,
+            udp: has_protocol("udp"),
+            tcp: has_protocol("tcp"),
+            project: env::var("SYSLOG_PROJECT")
+                .ok()
+                .filter(|project| !project.is_empty()),
+            allow_unauthenticated: parse_env_var::<u8>("SYSLOG_ALLOW_UNAUTHENTICATED")
+                .map_or(false, |allow| allow != 0),
+        };
+
+        if !config.udp && !config.tcp {
+            warn!(
+                "SYSLOG_PROTOCOLS was found but \"{protocols}\" has neither udp nor \
+                 tcp, not listening for syslog!"
+            );
+            return None;
+        }
+
+        Some(config)
+    }
+}
+
+/// A syslog record in either the RFC 5424 or the older RFC 3164 (BSD) format.
+/// Fields that are missing or nil (`-`) are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyslogRecord {
+    pub facility: u8,
+    pub severity: u8,
+    pub timestamp: Option<String>,
+    pub hostname: Option<String>,
+    pub app_name: Option<String>,
+    pub proc_id: Option<String>,
+    pub msg_id: Option<String>,
+    pub structured_data: Option<String>,
+    pub message: String,
+}
+
+fn nil(field: &str) -> Option<String> {
+    (!field.is_empty() && field != "-").then(|| field.to_string())
+}
+
+fn parse_pri(record: &str) -> Option<(u8, &str)> {
+    let rest = record.strip_prefix('<')?;
+    let end = rest.find('>')?;
+
+    if end == 0 || end > 3 {
+        return None;
+    }
+
+    match rest[..end].parse::<u8>() {
+        Ok(pri) if pri <= 191 => Some((pri, &rest[end + 1..])),
+        _ => None,
+    }
+}
+
+/// Splits the structured data off the start of `rest`, returning it along
+/// with the message that follows it.
+fn split_structured_data(rest: &str) -> Option<(Option<String>, &str)> {
+    if rest == "-" {
+        return Some((None, ""));
+    }
+
+    if let Some(message) = rest.strip_prefix("- ") {
+        return Some((None, message));
+    }
+
+    if !rest.starts_with('[') {
+        return None;
+    }
+
+    let mut in_quotes = false;
+    let mut is_escaped = false;
+
+    for (i, c) in rest.char_indices() {
+        if is_escaped {
+            is_escaped = false;
+            continue;
+        }
+
+        match c {
+            '\\' if in_quotes => is_escaped = true,
+            '"' => in_quotes = !in_quotes,
+            // Several elements follow each other without any separator.
+            ']' if !in_quotes && !rest[i + 1..].starts_with('[') => {
+                let message = &rest[i + 1..];
+
+                return Some((
+                    Some(rest[..=i].to_string()),
+                    message.strip_prefix(' ').unwrap_or(message),
+                ));
+            },
+            _ => (),
+        }
+    }
+
+    None
+}
+
+/// Parses structured data, i.e. `[id@1 key="value"][id@2]`, into the
+/// parameters of each element by its ID. Returns `None` if it isn't valid.
+fn parse_structured_data(structured_data: &str) -> Option<Fields> {
+    let mut elements = Fields::new();
+    let mut rest = structured_data;
+
+    while let Some(element) = rest.strip_prefix('[') {
+        let (id, mut element) = element.split_at(element.find([' ', ']'])?);
+        let mut params = Fields::new();
+
+        loop {
+            element = element.trim_start_matches(' ');
+
+            if let Some(after) = element.strip_prefix(']') {
+                rest = after;
+                break;
+            }
+
+            let (name, quoted) = element.split_once("=\"")?;
+            let mut value = String::new();
+            let mut chars = quoted.char_indices();
+
+            // Only `"`, `\` and `]` are escaped, any other backslash is kept.
+            let end = loop {
+                match chars.next()? {
+                    (_, '\\') => {
+                        let (_, escaped) = chars.next()?;
+
+                        if !matches!(escaped, '"' | '\\' | ']') {
+                            value.push('\\');
+                        }
+
+                        value.push(escaped);
+                    },
+                    (i, '"') => break i,
+                    (_, c) => value.push(c),
+                }
+            };
+
+            params.insert(name.into(), Value::String(value));
+            element = &quoted[end + 1..];
+        }
+
+        elements.insert(id.into(), Value::Object(params));
+    }
+
+    rest.is_empty().then_some(elements)
+}
+
+fn parse_rfc5424(rest: &str) -> Option<SyslogRecord> {
+    let mut fields = rest.strip_prefix("1 ")?.splitn(6, ' ');
+
+    let timestamp = nil(fields.next()?);
+    let hostname = nil(fields.next()?);
+    let app_name = nil(fields.next()?);
+    let proc_id = nil(fields.next()?);
+    let msg_id = nil(fields.next()?);
+    let (structured_data, message) = split_structured_data(fields.next()?)?;
+
+    Some(SyslogRecord {
+        timestamp,
+        hostname,
+        app_name,
+        proc_id,
+        msg_id,
+        structured_data,
+        message: message.trim_start_matches('\u{feff}').to_string(),
+        ..SyslogRecord::default()
+    })
+}
+
+/// Whether `timestamp` looks like `Mmm dd hh:mm:ss`, where the day is padded
+/// with a space.
+fn is_bsd_timestamp(timestamp: &str) -> bool {
+    let bytes = timestamp.as_bytes();
+    let is_time = bytes.len() == 15
+        && bytes[7..].iter().enumerate().all(|(i, byte)| {
+            if i % 3 == 2 {
+                *byte == b':'
+            } else {
+                byte.is_ascii_digit()
+            }
+        });
+
+    is_time
+        && timestamp
+            .get(..3)
+            .map_or(false, |month| MONTHS.contains(&month))
+        && bytes[3] == b' '
+        && (bytes[4] == b' ' || bytes[4].is_ascii_digit())
+        && bytes[5].is_ascii_digit()
+        && bytes[6] == b' '
+}
+
+/// Parses the part of an RFC 3164 record after the priority. The format was
+/// never enforced, so anything that doesn't fit it becomes the message.
+fn parse_rfc3164(rest: &str) -> SyslogRecord {
+    let (timestamp, rest) = match rest.get(..15) {
+        Some(timestamp) if is_bsd_timestamp(timestamp) && rest[15..].starts_with(' ') =>
+            (Some(timestamp.to_string()), &rest[16..]),
+        _ => (None, rest),
+    };
+
+    // Some senders leave the hostname out and go straight to the tag.
+    let (hostname, rest) = match rest.split_once(' ') {
+        Some((hostname, rest))
+            if timestamp.is_some()
+                && !hostname.ends_with(':')
+                && !hostname.contains('[') =>
+            (nil(hostname), rest),
+        _ => (None, rest),
+    };
+
+    let (tag, message) = rest.split_once(' ').unwrap_or((rest, ""));
+
+    let (app_name, proc_id, message) = match tag.strip_suffix(':') {
+        Some(tag) if !tag.is_empty() =>
+            match tag.strip_suffix(']').and_then(|tag| tag.split_once('[')) {
+                Some((app_name, proc_id)) => (nil(app_name), nil(proc_id), message),
+                None => (nil(tag), None, message),
+            },
+        _ => (None, None, rest),
+    };
+
+    SyslogRecord {
+        timestamp,
+        hostname,
+        app_name,
+        proc_id,
+        message: message.to_string(),
+        ..SyslogRecord::default()
+    }
+}
+
+impl SyslogRecord {
+    /// Parses a single syslog record. Records without a valid priority are
+    /// treated as `user.notice`, as RFC 3164 recommends, and anything that
+    /// isn't valid RFC 5424 is parsed as RFC 3164.
+    #[must_use]
+    pub fn parse(record: &str) -> Self {
+        let record = record.trim_end_matches(['\r', '\n', '\0']);
+        let (pri, rest) = parse_pri(record).unwrap_or((13, record));
+
+        Self {
+            facility: pri / 8,
+            severity: pri % 8,
+            ..parse_rfc5424(rest).unwrap_or_else(|| parse_rfc3164(rest))
+        }
+    }
+
+    #[must_use]
+    pub fn severity_name(&self) -> &'static str {
+        SEVERITIES
+            .get(usize::from(self.severity))
+            .copied()
+            .unwrap_or("Unknown")
+    }
+
+    #[must_use]
+    pub fn facility_name(&self) -> &'static str {
+        FACILITIES
+            .get(usize::from(self.facility))
+            .copied()
+            .unwrap_or("Unknown")
+    }
+
+    /// The details of the record that `Log` has no place for, as fields under
+    /// `syslog`: the facility, timestamp, process ID, message ID and the
+    /// structured data, parsed into its elements if it is valid.
+    #[must_use]
+    pub fn fields(&self) -> Fields {
+        let mut syslog = Fields::new();
+
+        syslog.insert("facility".into(), self.facility_name().into());
+
+        for (key, value) in [
+            ("timestamp", &self.timestamp),
+            ("proc_id", &self.proc_id),
+            ("msg_id", &self.msg_id),
+        ] {
+            if let Some(value) = value {
+                syslog.insert(key.into(), value.as_str().into());
+            }
+        }
+
+        if let Some(structured_data) = &self.structured_data {
+            syslog.insert(
+                "structured_data".into(),
+                parse_structured_data(structured_data)
+                    .map_or_else(|| structured_data.as_str().into(), Value::Object),
+            );
+        }
+
+        let mut fields = Fields::new();
+        fields.insert("syslog".into(), Value::Object(syslog));
+
+        fields
+    }
+
+    /// Converts the record into a `Log`. The severity becomes the message type
+    /// and the app name the file name, while the other details are left for
+    /// [`SyslogRecord::fields`]. The hostname is left for
+    /// [`Service::verify_log`] to fill in as the address.
+    #[must_use]
+    pub fn into_log(self) -> Log {
+        Log {
+            message_type: self.severity_name().into(),
+            file_name: self.app_name.unwrap_or_default(),
+            message: self.message,
+            ..Default::default()
+        }
+    }
+}
+
+impl Service {
+    /// Verifies and stores a syslog record received from `peer`.
+    async fn receive_syslog(
+        &self,
+        record: &str,
+        peer: SocketAddr,
+        project: Option<&str>,
+    ) -> Result<(), Status> {
+        let record = SyslogRecord::parse(record);
+        let mut metadata = MetadataMap::new();
+
+        if let Some(project) = project.and_then(|project| project.parse().ok()) {
+            metadata.insert(PROJECT_HEADER, project);
+        }
+
+        // The hostname in the record is more useful than the address of
+        // whichever relay forwarded it to us.
+        let remote_addr = match record
+            .hostname
+            .as_deref()
+            .and_then(|host| host.parse().ok())
+        {
+            Some(host) => {
+                metadata.insert("x-host", host);
+                None
+            },
+            None => Some(peer),
+        };
+
+        let fields = record.fields();
+        let mut log = Self::verify_log(record.into_log(), remote_addr, &metadata, None)?;
+        log.fields.extend(fields);
+
+        self.store_log(log, LogSource::Syslog).await
+    }
+
+    async fn receive_syslog_bytes(
+        &self,
+        record: &[u8],
+        peer: SocketAddr,
+        project: Option<&str>,
+    ) {
+        let record = String::from_utf8_lossy(record);
+
+        if record.trim().is_empty() {
+            return;
+        }
+
+        match self.receive_syslog(&record, peer, project).await {
+            Ok(()) =>
+                trace!(target: "codectrl_server - syslog listener", "Log received from {peer}"),
+            Err(status) =>
+                error!(target: "codectrl_server - syslog listener", "Could not store record from {peer}: {}", status.message()),
+        }
+    }
+
+    /// Reads the records of a TCP connection until it closes, see
+    /// [`read_record`].
+    async fn read_syslog_stream(
+        &self,
+        stream: TcpStream,
+        peer: SocketAddr,
+        project: Option<&str>,
+    ) -> io::Result<()> {
+        let mut reader = BufReader::new(stream);
+
+        while let Some(record) = read_record(&mut reader).await? {
+            self.receive_syslog_bytes(&record, peer, project).await;
+        }
+
+        Ok(())
+    }
+
+    /// Starts listening for syslog records on `host`, storing each of them
+    /// as a log.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the UDP or TCP port could not be bound to, or if the
+    /// server requires authentication and `config` doesn't allow
+    /// unauthenticated records, as syslog has no way of sending a token.
+    pub async fn start_syslog_listener(
+        &self,
+        host: &str,
+        config: SyslogConfig,
+    ) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            !self.requires_authentication || config.allow_unauthenticated,
+            "The syslog listener can't authenticate senders, set \
+             syslog.allow_unauthenticated to start it while authentication is required"
+        );
+
+        let addr: SocketAddr = format!("{host}:{}", config.port).parse()?;
+
+        if config.udp {
+            let socket = UdpSocket::bind(addr).await?;
+            let service = self.clone();
+            let project = config.project.clone();
+
+            info!(target: "codectrl_server - syslog listener", "Listening for syslog over UDP on {addr}");
+
+            tokio::spawn(async move {
+                let mut buffer = vec![0; MAX_RECORD_SIZE];
+
+                loop {
+                    match socket.recv_from(&mut buffer).await {
+                        Ok((length, peer)) =>
+                            service
+                                .receive_syslog_bytes(
+                                    &buffer[..length],
+                                    peer,
+                                    project.as_deref(),
+                                )
+                                .await,
+                        Err(error) =>
+                            error!(target: "codectrl_server - syslog listener", "Error occurred while receiving over UDP: {error}"),
+                    }
+                }
+            });
+        }
+
+        if config.tcp {
+            let listener = TcpListener::bind(addr).await?;
+            let service = self.clone();
+            let project = config.project;
+
+            info!(target: "codectrl_server - syslog listener", "Listening for syslog over TCP on {addr}");
+
+            tokio::spawn(async move {
+                loop {
+                    let (stream, peer) = match listener.accept().await {
+                        Ok(connection) => connection,
+                        Err(error) => {
+                            error!(target: "codectrl_server - syslog listener", "Error occurred while accepting a TCP connection: {error}");
+                            continue;
+                        },
+                    };
+
+                    let service = service.clone();
+                    let project = project.clone();
+
+                    tokio::spawn(async move {
+                        if let Err(error) = service
+                            .read_syslog_stream(stream, peer, project.as_deref())
+                            .await
+                        {
+                            warn!(target: "codectrl_server - syslog listener", "Closed TCP connection from {peer}: {error}");
+                        }
+                    });
+                }
+            });
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,179 @@
+// region: imports
+
+use anyhow::{anyhow, Context};
+use log::warn;
+use rustls_pemfile::Item;
+use serde::Deserialize;
+use std::{env, fs, io::BufReader, path::PathBuf};
+use tokio_rustls::rustls::{
+    server::AllowAnyAuthenticatedClient, Certificate as RustlsCertificate, PrivateKey,
+    RootCertStore, ServerConfig,
+};
+use tonic::{
+    transport::{Certificate, Identity, ServerTlsConfig},
+    Request,
+};
+use x509_parser::parse_x509_certificate;
+
+// endregion
+
+/// The PEM files that the `gRPC` server and the HTTP API use for TLS, either
+/// from the `[tls]` table of the config file or from the environment, see
+/// [`TlsConfig::from_env`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct TlsConfig {
+    /// The server's certificate chain.
+    pub cert: PathBuf,
+    /// The private key of `cert`.
+    pub key: PathBuf,
+    /// The CA that client certificates have to be signed by. Clients have to
+    /// present a certificate (mutual TLS) if this is set.
+    #[serde(default)]
+    pub client_ca: Option<PathBuf>,
+}
+
+impl TlsConfig {
+    /// Reads the config from the `TLS_CERT`, `TLS_KEY` and `TLS_CLIENT_CA`
+    /// environment variables. Returns `None` if `TLS_CERT` or `TLS_KEY` isn't
+    /// set, as TLS is disabled by default.
+    #[must_use]
+    pub fn from_env() -> Option<Self> {
+        let path = |name: &str| {
+            env::var_os(name)
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from)
+        };
+
+        match (path("TLS_CERT"), path("TLS_KEY")) {
+            (Some(cert), Some(key)) => Some(Self {
+                cert,
+                key,
+                client_ca: path("TLS_CLIENT_CA"),
+            }),
+            (None, None) => None,
+            _ => {
+                warn!("Only one of TLS_CERT and TLS_KEY was found, not using TLS!");
+                None
+            },
+        }
+    }
+
+    /// Reads the PEM files into the config that the `gRPC` server is built
+    /// with.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the files can't be read.
+    pub fn server_tls_config(&self) -> anyhow::Result<ServerTlsConfig> {
+        let read = |path: &PathBuf| {
+            fs::read(path).with_context(|| {
+                format!("Could not read TLS file {}", path.to_string_lossy())
+            })
+        };
+
+        let mut tls_config = ServerTlsConfig::new()
+            .identity(Identity::from_pem(read(&self.cert)?, read(&self.key)?));
+
+        if let Some(client_ca) = &self.client_ca {
+            tls_config =
+                tls_config.client_ca_root(Certificate::from_pem(read(client_ca)?));
+        }
+
+        Ok(tls_config)
+    }
+
+    /// Reads the PEM files into the config that the HTTP API accepts TLS
+    /// connections with, requiring a client certificate like
+    /// [`TlsConfig::server_tls_config`] does if `client_ca` is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the files can't be read or contain no usable
+    /// certificate or key.
+    pub fn server_rustls_config(&self) -> anyhow::Result<ServerConfig> {
+        let read = |path: &PathBuf| {
+            fs::read(path)
+                .and_then(|pem| rustls_pemfile::read_all(&mut BufReader::new(&pem[..])))
+                .with_context(|| {
+                    format!("Could not read TLS file {}", path.to_string_lossy())
+                })
+        };
+        let certs = |path: &PathBuf| {
+            let certs = read(path)?
+                .into_iter()
+                .filter_map(|item| match item {
+                    Item::X509Certificate(cert) => Some(RustlsCertificate(cert)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+
+            if certs.is_empty() {
+                return Err(anyhow!(
+                    "{} contains no certificates",
+                    path.to_string_lossy()
+                ));
+            }
+
+            Ok(certs)
+        };
+
+        let key = read(&self.key)?
+            .into_iter()
+            .find_map(|item| match item {
+                Item::RSAKey(key) | Item::PKCS8Key(key) | Item::ECKey(key) =>
+                    Some(PrivateKey(key)),
+                _ => None,
+            })
+            .ok_or_else(|| {
+                anyhow!("{} contains no private key", self.key.to_string_lossy())
+            })?;
+
+        let builder = ServerConfig::builder().with_safe_defaults();
+        let builder = if let Some(client_ca) = &self.client_ca {
+            let mut roots = RootCertStore::empty();
+
+            for cert in certs(client_ca)? {
+                roots.add(&cert).with_context(|| {
+                    format!("Invalid client CA in {}", client_ca.to_string_lossy())
+                })?;
+            }
+
+            builder.with_client_cert_verifier(AllowAnyAuthenticatedClient::new(roots))
+        } else {
+            builder.with_no_client_auth()
+        };
+
+        let mut config = builder
+            .with_single_cert(certs(&self.cert)?, key)
+            .context("Invalid TLS certificate or key")?;
+        config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+        Ok(config)
+    }
+}
+
+/// The subject of a DER encoded certificate, i.e. `CN=build-agent-1,
+/// O=Example`.
+#[must_use]
+pub fn certificate_subject(cert: &[u8]) -> Option<String> {
+    match parse_x509_certificate(cert) {
+        Ok((_, cert)) => Some(cert.subject().to_string()),
+        Err(error) => {
+            warn!("Could not parse the client certificate: {error}");
+            None
+        },
+    }
+}
+
+/// The subject of the certificate that the client verified itself with, see
+/// [`certificate_subject`]. Returns `None` if mutual TLS isn't enabled or the
+/// request didn't come in over `gRPC`.
+#[must_use]
+pub fn client_subject<T>(request: &Request<T>) -> Option<String> {
+    let certs = request.peer_certs()?;
+    // The first certificate is the client's own, the rest are the chain that
+    // signed it.
+    let cert = certs.first()?;
+
+    certificate_subject(cert.get_ref())
+}
@@ -0,0 +1,80 @@
+// region: imports
+
+#[cfg(unix)]
+use anyhow::{bail, Context};
+#[cfg(unix)]
+use std::{
+    fs::{self, Permissions},
+    os::unix::{
+        fs::{FileTypeExt, PermissionsExt},
+        net::UnixStream,
+    },
+    path::Path,
+};
+#[cfg(unix)]
+use tokio::net::UnixListener;
+#[cfg(unix)]
+use tokio_stream::wrappers::UnixListenerStream;
+
+// endregion
+
+/// Parses a file mode in octal, with or without the `0o` prefix, i.e. `660` or
+/// `0o660`.
+#[must_use]
+pub fn parse_mode(mode: &str) -> Option<u32> {
+    let mode = mode.trim();
+    let mode = mode.strip_prefix("0o").unwrap_or(mode);
+
+    u32::from_str_radix(mode, 8)
+        .ok()
+        .filter(|mode| *mode <= 0o777)
+}
+
+/// Binds the Unix socket at `path` and sets its permissions to `mode`, if
+/// given. A socket that was left behind by a server that didn't shut down
+/// cleanly is replaced.
+///
+/// # Errors
+///
+/// Returns an error if `path` is something other than a socket, if another
+/// server is still listening on it, or if it can't be bound.
+#[cfg(unix)]
+pub fn bind(path: &Path, mode: Option<u32>) -> anyhow::Result<UnixListenerStream> {
+    if let Ok(metadata) = fs::symlink_metadata(path) {
+        if !metadata.file_type().is_socket() {
+            bail!(
+                "{} already exists and is not a Unix socket",
+                path.to_string_lossy()
+            );
+        }
+
+        if UnixStream::connect(path).is_ok() {
+            bail!(
+                "Another server is already listening on {}",
+                path.to_string_lossy()
+            );
+        }
+
+        fs::remove_file(path).with_context(|| {
+            format!("Could not remove the old socket {}", path.to_string_lossy())
+        })?;
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let listener = UnixListener::bind(path)
+        .with_context(|| format!("Could not bind {}", path.to_string_lossy()))?;
+
+    if let Some(mode) = mode {
+        fs::set_permissions(path, Permissions::from_mode(mode)).with_context(|| {
+            format!(
+                "Could not set the permissions of {}",
+                path.to_string_lossy()
+            )
+        })?;
+    }
+
+    Ok(UnixListenerStream::new(listener))
+}
@@ -0,0 +1,40 @@
+use codectrl_server::{admin, open_database};
+use std::{env, fs};
+
+#[tokio::test]
+async fn test_empty_database() {
+    let data_dir =
+        env::temp_dir().join(format!("codectrl-admin-test-{}", std::process::id()));
+
+    let db_connection = open_database(&data_dir).await.unwrap();
+
+    // Opening the database again has nothing left to migrate.
+    open_database(&data_dir).await.unwrap();
+
+    assert!(
+        admin::list_connections(&db_connection)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(
+        admin::prune_connections(&db_connection, true)
+            .await
+            .unwrap(),
+        0
+    );
+
+    let mut exported = vec![];
+
+    assert_eq!(
+        admin::export_logs(&db_connection, None, &mut exported)
+            .await
+            .unwrap(),
+        0
+    );
+    assert!(exported.is_empty());
+
+    admin::vacuum(&db_connection).await.unwrap();
+
+    fs::remove_dir_all(data_dir).ok();
+}
@@ -0,0 +1,151 @@
+use codectrl_server::auth::{AuthInterceptor, Identity, TokenClaims, TokenSecret};
+use dashmap::DashSet;
+use std::{sync::Arc, time::Duration};
+use tonic::{service::Interceptor, Request};
+
+fn interceptor(rejects_anonymous: bool) -> AuthInterceptor {
+    AuthInterceptor {
+        requires_authentication: true,
+        rejects_anonymous,
+        token_secret: TokenSecret::new("test secret".into()),
+        revoked_tokens: Arc::new(DashSet::new()),
+    }
+}
+
+fn request_with_token(token: &str) -> Request<()> {
+    let mut request = Request::new(());
+    request
+        .metadata_mut()
+        .insert("authorization", format!("Bearer {token}").parse().unwrap());
+
+    request
+}
+
+#[test]
+fn test_sign_and_verify() {
+    let secret = TokenSecret::new("test secret".into());
+    let claims = TokenClaims::new("tester", Duration::from_secs(60));
+    let token = secret.sign(&claims).unwrap();
+
+    let verified = secret.verify(&token).unwrap();
+    assert_eq!(verified.subject, "tester");
+    assert_eq!(verified.id, claims.id);
+
+    assert!(
+        TokenSecret::new("other secret".into())
+            .verify(&token)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_interceptor_rejects_anonymous() {
+    let mut interceptor = interceptor(true);
+
+    assert!(interceptor.call(Request::new(())).is_err());
+    assert!(interceptor.call(request_with_token("not a token")).is_err());
+}
+
+#[test]
+fn test_interceptor_attaches_identity() {
+    let mut interceptor = interceptor(false);
+    let claims = TokenClaims::new("tester", Duration::from_secs(60));
+    let token = interceptor.token_secret.sign(&claims).unwrap();
+
+    let request = interceptor.call(request_with_token(&token)).unwrap();
+    let claims = Identity::require(&request, true).unwrap().unwrap();
+    assert_eq!(claims.subject, "tester");
+
+    let request = interceptor.call(Request::new(())).unwrap();
+    assert!(Identity::require(&request, true).is_err());
+    assert!(Identity::require(&request, false).unwrap().is_none());
+}
+
+#[test]
+fn test_interceptor_rejects_revoked() {
+    let mut interceptor = interceptor(true);
+    let claims = TokenClaims::new("tester", Duration::from_secs(60));
+    let token = interceptor.token_secret.sign(&claims).unwrap();
+
+    assert!(interceptor.call(request_with_token(&token)).is_ok());
+
+    interceptor.revoked_tokens.insert(claims.id);
+    assert!(interceptor.call(request_with_token(&token)).is_err());
+}
+
+#[test]
+fn test_child_token_keeps_subject() {
+    let secret = TokenSecret::new("test secret".into());
+    let parent = TokenClaims::new("tester", Duration::from_secs(60));
+
+    // The name asked for is only a label, the token still belongs to whoever
+    // generated it.
+    let child = parent.child(Duration::from_secs(60)).with_label("admin");
+    let token = secret.sign(&child).unwrap();
+
+    let verified = secret.verify(&token).unwrap();
+    assert_eq!(verified.subject, "tester");
+    assert_eq!(verified.label.as_deref(), Some("admin"));
+    assert_eq!(verified.parent.as_deref(), Some(parent.session.as_str()));
+    assert_ne!(verified.id, parent.id);
+
+    let unlabelled = parent.child(Duration::from_secs(60)).with_label("  ");
+    assert_eq!(unlabelled.label, None);
+}
+
+#[test]
+fn test_interceptor_rejects_children_of_revoked() {
+    let mut interceptor = interceptor(true);
+    let parent = TokenClaims::new("tester", Duration::from_secs(60));
+    let child = parent.child(Duration::from_secs(60));
+    let grandchild = child.child(Duration::from_secs(60));
+    let sibling = TokenClaims::new("tester", Duration::from_secs(60));
+
+    let tokens = [&child, &grandchild, &sibling]
+        .map(|claims| interceptor.token_secret.sign(claims).unwrap());
+
+    for token in &tokens {
+        assert!(interceptor.call(request_with_token(token)).is_ok());
+    }
+
+    interceptor.revoked_tokens.insert(parent.session);
+
+    assert!(interceptor.call(request_with_token(&tokens[0])).is_err());
+    assert!(interceptor.call(request_with_token(&tokens[1])).is_err());
+    assert!(interceptor.call(request_with_token(&tokens[2])).is_ok());
+}
+
+#[test]
+fn test_refreshed_token_keeps_claims() {
+    let mut interceptor = interceptor(true);
+    let parent = TokenClaims::new("tester", Duration::from_secs(60));
+    let child = parent
+        .child(Duration::from_secs(60))
+        .with_label("logger")
+        .with_projects(vec!["project".into()]);
+    let refreshed = child.refreshed(Duration::from_secs(120));
+
+    assert_ne!(refreshed.id, child.id);
+    assert_eq!(refreshed.session, child.session);
+    assert_eq!(refreshed.label.as_deref(), Some("logger"));
+    assert_eq!(refreshed.projects, ["project"]);
+    assert_eq!(refreshed.parent, child.parent);
+    assert!(refreshed.expire > child.expire);
+
+    // Refreshing only revokes the old token, tokens generated with it stay
+    // valid.
+    let grandchild = child.child(Duration::from_secs(60));
+    let tokens = [&refreshed, &grandchild]
+        .map(|claims| interceptor.token_secret.sign(claims).unwrap());
+
+    interceptor.revoked_tokens.insert(child.id.clone());
+
+    assert!(interceptor.call(request_with_token(&tokens[0])).is_ok());
+    assert!(interceptor.call(request_with_token(&tokens[1])).is_ok());
+
+    // Revoking the session revokes the refreshed token too.
+    interceptor.revoked_tokens.insert(child.session);
+
+    assert!(interceptor.call(request_with_token(&tokens[0])).is_err());
+    assert!(interceptor.call(request_with_token(&tokens[1])).is_err());
+}
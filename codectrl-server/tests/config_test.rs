@@ -0,0 +1,387 @@
+use codectrl_server::{
+    config::{ConfigError, ServerConfig},
+    oauth::ProviderKind,
+    retention::RetentionPolicy,
+    syslog::SyslogConfig,
+    tls::TlsConfig,
+    unix_socket,
+};
+use std::{env, path::PathBuf, time::Duration};
+
+fn problem_keys(result: Result<impl std::fmt::Debug, ConfigError>) -> Vec<String> {
+    match result {
+        Err(ConfigError::Invalid(_, problems)) => {
+            let mut keys: Vec<_> =
+                problems.into_iter().map(|problem| problem.key).collect();
+            keys.sort_unstable();
+            keys
+        },
+        result => panic!("Expected an invalid config, got {result:?}"),
+    }
+}
+
+#[test]
+fn test_empty_config_is_default() {
+    let config = ServerConfig::from_toml("").unwrap();
+
+    assert_eq!(config, ServerConfig::default());
+    assert_eq!(config.listen.host, "127.0.0.1");
+    assert_eq!(config.listen.port, 3002);
+    assert!(config.listen.http_api);
+    assert_eq!(config.listen.http_api_port, 3003);
+    assert_eq!(config.auth.redirect_port, 8080);
+    assert!(config.censor_usernames);
+    assert!(!config.auth.required);
+    assert!(config.syslog.is_none());
+    assert!(config.tls.is_none());
+    assert!(config.oauth.is_none());
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn test_full_config() {
+    let config = ServerConfig::from_toml(
+        r#"
+        data_dir = "/var/lib/codectrl"
+        censor_usernames = false
+
+        [listen]
+        host = "0.0.0.0"
+        port = 4002
+        http_api = false
+        http_api_port = 4003
+
+        [auth]
+        required = true
+        redirect_port = 9090
+        token_secret = "secret"
+
+        [retention]
+        max_logs = 1000
+        max_age = 60
+
+        [syslog]
+        port = 5140
+        tcp = false
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(config.data_dir(), PathBuf::from("/var/lib/codectrl"));
+    assert!(!config.censor_usernames);
+    assert_eq!(config.listen.host, "0.0.0.0");
+    assert_eq!(config.listen.port, 4002);
+    assert!(!config.listen.http_api);
+    assert_eq!(config.listen.http_api_port, 4003);
+    assert!(config.auth.required);
+    assert_eq!(config.auth.redirect_port, 9090);
+    assert_eq!(config.auth.token_secret.as_deref(), Some("secret"));
+    assert_eq!(
+        RetentionPolicy::from(config.retention),
+        RetentionPolicy {
+            max_count: Some(1000),
+            max_age: Some(Duration::from_secs(60)),
+            max_bytes: None,
+        }
+    );
+    assert_eq!(
+        config.syslog,
+        Some(SyslogConfig {
+            port: 5140,
+            udp: true,
+            tcp: false,
+            project: None,
+            allow_unauthenticated: false,
+        })
+    );
+}
+
+#[test]
+fn test_every_bad_key_is_listed() {
+    let error = ServerConfig::from_toml(
+        r#"
+        censor_usernames = "yes"
+        colour = true
+
+        [listen]
+        host = "localhost:3002"
+        port = 70000
+
+        [auth]
+        token_secret = ""
+
+        [retention]
+        max_age = -1
+
+        [syslog]
+        udp = false
+        tcp = false
+        "#,
+    )
+    .unwrap_err();
+
+    let problems = match &error {
+        ConfigError::Invalid(_, problems) => problems,
+        error => panic!("Expected an invalid config, got {error}"),
+    };
+
+    let mut keys: Vec<_> = problems
+        .iter()
+        .map(|problem| problem.key.as_str())
+        .collect();
+    keys.sort_unstable();
+
+    assert_eq!(
+        keys,
+        [
+            "auth.token_secret",
+            "censor_usernames",
+            "colour",
+            "listen.host",
+            "listen.port",
+            "retention.max_age",
+            "syslog.port",
+        ]
+    );
+
+    let message = error.to_string();
+
+    assert!(message.contains("colour: unknown key"));
+    assert!(message.contains("listen.port: 70000 is not a port"));
+}
+
+#[test]
+fn test_tls_config() {
+    let config = ServerConfig::from_toml(
+        r#"
+        [tls]
+        cert = "server.pem"
+        key = "server.key"
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        config.tls,
+        Some(TlsConfig {
+            cert: "server.pem".into(),
+            key: "server.key".into(),
+            client_ca: None,
+        })
+    );
+
+    match ServerConfig::from_toml("[tls]\nclient_ca = \"ca.pem\"") {
+        Err(ConfigError::Invalid(_, problems)) => {
+            let keys: Vec<_> = problems
+                .iter()
+                .map(|problem| problem.key.as_str())
+                .collect();
+
+            assert_eq!(keys, ["tls.cert", "tls.key"]);
+        },
+        result => panic!("Expected an invalid config, got {result:?}"),
+    }
+}
+
+#[test]
+fn test_unix_socket_config() {
+    let config = ServerConfig::from_toml(
+        r#"
+        [listen]
+        tcp = false
+        unix_socket = "/run/codectrl/codectrl.sock"
+        unix_socket_mode = 0o660
+        "#,
+    )
+    .unwrap();
+
+    assert!(!config.listen.tcp);
+    assert_eq!(
+        config.listen.unix_socket,
+        Some(PathBuf::from("/run/codectrl/codectrl.sock"))
+    );
+    assert_eq!(config.listen.unix_socket_mode, Some(0o660));
+
+    assert_eq!(
+        problem_keys(ServerConfig::from_toml(
+            "[listen]\ntcp = false\nunix_socket_mode = 0o1777"
+        )),
+        ["listen.unix_socket_mode"]
+    );
+}
+
+#[test]
+fn test_validate() {
+    let mut config = ServerConfig::from_toml(
+        r#"
+        [listen]
+        tcp = false
+
+        [syslog]
+        port = 5140
+        udp = false
+        tcp = false
+
+        [oauth.custom]
+        client_id = "codectrl"
+        auth_url = "https://auth.example.com/authorize"
+
+        [oauth.keycloak]
+        kind = "oidc"
+        client_id = "codectrl"
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        problem_keys(config.validate()),
+        [
+            "listen.tcp",
+            "oauth.custom.token_url",
+            "oauth.custom.user_url",
+            "oauth.keycloak.base_url",
+            "syslog",
+        ]
+    );
+
+    // What the config file is missing can be filled in by the overrides.
+    config.listen.unix_socket = Some("/run/codectrl/codectrl.sock".into());
+    config.syslog = None;
+    config.oauth = None;
+
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn test_env_is_validated() {
+    let mut config = ServerConfig::from_toml("[listen]\ntcp = false").unwrap();
+
+    assert!(config.validate().is_err());
+
+    env::set_var("UNIX_SOCKET", "/run/codectrl/codectrl.sock");
+    env::set_var("PROJECT_MEMBERS", "team-a=github:octocat");
+    env::set_var("OAUTH_PROVIDERS", "gitlab");
+    env::set_var("GITLAB_CLIENT_ID", "mock-client-id");
+
+    config.apply_env();
+
+    assert!(config.validate().is_ok());
+    assert_eq!(
+        config.listen.unix_socket,
+        Some(PathBuf::from("/run/codectrl/codectrl.sock"))
+    );
+    assert_eq!(
+        config.projects.projects_of("github:octocat"),
+        Some(vec!["team-a".into()])
+    );
+
+    let providers = config.oauth_providers();
+
+    assert_eq!(providers.len(), 1);
+    assert_eq!(providers[0].name, "gitlab");
+    assert_eq!(providers[0].kind, ProviderKind::GitLab);
+}
+
+#[test]
+fn test_oauth_and_projects_config() {
+    let config = ServerConfig::from_toml(
+        r#"
+        [oauth.github]
+        client_id = "github-client-id"
+        client_secret = "github-client-secret"
+
+        [oauth.keycloak]
+        client_id = "codectrl"
+        base_url = "https://keycloak.example.com/realms/main"
+        scopes = ["openid", "profile"]
+        subject_field = "preferred_username"
+
+        [projects]
+        project-a = ["github:alice", "gitlab:bob"]
+        "*" = ["github:admin"]
+        "#,
+    )
+    .unwrap();
+
+    assert!(config.validate().is_ok());
+
+    let providers = config.oauth_providers();
+
+    assert_eq!(providers.len(), 2);
+    assert_eq!(providers[0].name, "github");
+    assert_eq!(providers[0].kind, ProviderKind::GitHub);
+    assert_eq!(
+        providers[0].client_secret.as_deref(),
+        Some("github-client-secret")
+    );
+    assert_eq!(providers[1].name, "keycloak");
+    assert_eq!(providers[1].kind, ProviderKind::Oidc);
+    assert_eq!(
+        providers[1].scopes,
+        Some(vec!["openid".into(), "profile".into()])
+    );
+
+    assert_eq!(
+        config.projects.projects_of("gitlab:bob"),
+        Some(vec!["project-a".into()])
+    );
+    assert_eq!(config.projects.projects_of("github:admin"), Some(vec![]));
+    assert_eq!(config.projects.projects_of("github:nobody"), None);
+}
+
+#[test]
+fn test_bad_oauth_and_projects_keys() {
+    assert_eq!(
+        problem_keys(ServerConfig::from_toml(
+            r#"
+            [oauth.custom]
+            kind = "saml"
+            scopes = "openid"
+            token = "secret"
+
+            [oauth.other]
+            client_secret = "secret"
+
+            [projects]
+            team-a = "github:alice"
+            team-b = ["github:bob", 42]
+            "#,
+        )),
+        [
+            "oauth.custom.client_id",
+            "oauth.custom.kind",
+            "oauth.custom.scopes",
+            "oauth.custom.token",
+            "oauth.other.client_id",
+            "projects.team-a",
+            "projects.team-b",
+        ]
+    );
+}
+
+#[test]
+fn test_parse_unix_socket_mode() {
+    assert_eq!(unix_socket::parse_mode("660"), Some(0o660));
+    assert_eq!(unix_socket::parse_mode("0o600"), Some(0o600));
+    assert_eq!(unix_socket::parse_mode("888"), None);
+    assert_eq!(unix_socket::parse_mode("1777"), None);
+}
+
+#[test]
+fn test_wrong_table_type() {
+    match ServerConfig::from_toml("listen = 3002") {
+        Err(ConfigError::Invalid(_, problems)) => {
+            assert_eq!(problems.len(), 1);
+            assert_eq!(problems[0].key, "listen");
+        },
+        result => panic!("Expected an invalid config, got {result:?}"),
+    }
+}
+
+#[test]
+fn test_syntax_error() {
+    assert!(matches!(
+        ServerConfig::from_toml("[listen\nport = 3002"),
+        Err(ConfigError::Syntax(..))
+    ));
+}
@@ -0,0 +1,58 @@
+use codectrl_protobuf_bindings::data::Log;
+use codectrl_server::fields::{self, FieldFilter, FIELDS_HEADER};
+use serde_json::json;
+use tonic::{metadata::MetadataMap, Code};
+
+#[test]
+fn test_from_message() {
+    let fields = fields::from_message(r#"{"user": {"id": 42}, "ok": true}"#).unwrap();
+    assert_eq!(fields["user"]["id"], json!(42));
+
+    // Debug formatted strings are quoted.
+    let fields = fields::from_message(r#""{\"user\": \"sam\"}""#).unwrap();
+    assert_eq!(fields["user"], json!("sam"));
+
+    assert_eq!(fields::from_message("[1, 2, 3]"), None);
+    assert_eq!(fields::from_message("Hello, world!"), None);
+}
+
+#[test]
+fn test_of() {
+    let log = Log {
+        message: r#"{"user": "sam", "action": "login"}"#.into(),
+        ..Default::default()
+    };
+    let mut metadata = MetadataMap::new();
+    metadata.insert(
+        FIELDS_HEADER,
+        r#"{"user": "alex", "request_id": 7}"#.parse().unwrap(),
+    );
+
+    // The header wins over the message.
+    let fields = fields::of(&log, &metadata).unwrap();
+    assert_eq!(fields["user"], json!("alex"));
+    assert_eq!(fields["action"], json!("login"));
+    assert_eq!(fields["request_id"], json!(7));
+
+    metadata.insert(FIELDS_HEADER, "[]".parse().unwrap());
+    assert_eq!(
+        fields::of(&log, &metadata).unwrap_err().code(),
+        Code::InvalidArgument
+    );
+}
+
+#[test]
+fn test_field_filter() {
+    let fields = fields::from_message(
+        r#"{"user": {"id": 42, "name": "sam"}, "tags": ["a", "b"]}"#,
+    )
+    .unwrap();
+
+    assert!(FieldFilter::parse("user.id=42").matches(&fields));
+    assert!(FieldFilter::parse("user.name = sam").matches(&fields));
+    assert!(FieldFilter::parse("tags.1=b").matches(&fields));
+    assert!(FieldFilter::parse("user").matches(&fields));
+    assert!(!FieldFilter::parse("user.id=41").matches(&fields));
+    assert!(!FieldFilter::parse("user.email").matches(&fields));
+    assert!(!FieldFilter::parse("tags.x=a").matches(&fields));
+}
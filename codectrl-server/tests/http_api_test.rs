@@ -0,0 +1,62 @@
+use codectrl_server::http_api::{status_code, JsonLogs};
+use tonic::Status;
+use warp::http::StatusCode;
+
+#[test]
+fn test_single_log() {
+    let logs: JsonLogs = serde_json::from_str(
+        r#"{
+            "message": "Hello from a shell script",
+            "message_type": "String",
+            "file_name": "deploy.sh",
+            "line_number": 12,
+            "stack": [{ "file_path": "deploy.sh", "line_number": 12 }],
+            "code_snippet": { "12": "codectrl_log \"Hello\"" }
+        }"#,
+    )
+    .unwrap();
+
+    let logs = logs.into_logs();
+    assert_eq!(logs.len(), 1);
+
+    let log = &logs[0];
+    assert_eq!(log.message, "Hello from a shell script");
+    assert_eq!(log.line_number, 12);
+    assert_eq!(log.stack[0].file_path, "deploy.sh");
+    assert_eq!(log.stack[0].column_number, 0);
+    assert_eq!(log.code_snippet[&12], "codectrl_log \"Hello\"");
+    assert!(log.uuid.is_empty());
+}
+
+#[test]
+fn test_many_logs() {
+    let logs: JsonLogs =
+        serde_json::from_str(r#"[{ "message": "one" }, { "message": "two" }, {}]"#)
+            .unwrap();
+
+    let logs = logs.into_logs();
+    assert_eq!(logs.len(), 3);
+    assert_eq!(logs[1].message, "two");
+    assert!(logs[2].message.is_empty());
+}
+
+#[test]
+fn test_invalid_log() {
+    assert!(serde_json::from_str::<JsonLogs>(r#"{ "line_number": "twelve" }"#).is_err());
+}
+
+#[test]
+fn test_status_code() {
+    assert_eq!(
+        status_code(&Status::unauthenticated("")),
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        status_code(&Status::permission_denied("")),
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        status_code(&Status::aborted("")),
+        StatusCode::INTERNAL_SERVER_ERROR
+    );
+}
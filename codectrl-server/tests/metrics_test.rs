@@ -0,0 +1,22 @@
+use codectrl_server::metrics::{LogSource, LogWarning, METRICS};
+use std::time::Duration;
+
+#[test]
+fn test_render_metrics() {
+    METRICS.log_received(LogSource::SendLog);
+    METRICS.log_received(LogSource::SendLogs);
+    METRICS.log_warning(LogWarning::EmptyStack);
+    METRICS.bytes_ingested(128);
+    METRICS.backup_written(Duration::from_millis(5), true);
+
+    let metrics = METRICS.render(3, 7);
+
+    assert!(metrics.contains("codectrl_logs_received_total{source=\"send_log\"}"));
+    assert!(metrics.contains("codectrl_logs_received_total{source=\"send_logs\"}"));
+    assert!(metrics.contains("codectrl_log_warnings_total{kind=\"empty_stack\"}"));
+    assert!(metrics.contains("codectrl_ingested_bytes_total"));
+    assert!(metrics.contains("codectrl_backup_write_duration_seconds_count"));
+    assert!(metrics.contains("codectrl_backup_write_errors_total"));
+    assert!(metrics.contains("codectrl_connections 3"));
+    assert!(metrics.contains("codectrl_log_queue_depth 7"));
+}
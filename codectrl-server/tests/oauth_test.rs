@@ -0,0 +1,99 @@
+use codectrl_server::oauth::{ProviderConfig, ProviderKind};
+use serde_json::json;
+use std::{collections::HashMap, env};
+use warp::Filter;
+
+fn config(name: &str, kind: ProviderKind) -> ProviderConfig {
+    ProviderConfig {
+        name: name.into(),
+        kind,
+        client_id: "mock-client-id".into(),
+        client_secret: Some("mock-client-secret".into()),
+        base_url: None,
+        auth_url: None,
+        token_url: None,
+        user_url: None,
+        scopes: None,
+        subject_field: None,
+    }
+}
+
+#[test]
+fn test_kind_from_env() {
+    env::set_var("GITLAB_CLIENT_ID", "mock-client-id");
+    env::set_var("KEYCLOAK_CLIENT_ID", "mock-client-id");
+    env::set_var("KEYCLOAK_BASE_URL", "http://127.0.0.1/realms/codectrl");
+    env::set_var("CUSTOM_CLIENT_ID", "mock-client-id");
+
+    assert_eq!(
+        ProviderConfig::from_env("gitlab").unwrap().kind,
+        ProviderKind::GitLab
+    );
+    assert_eq!(
+        ProviderConfig::from_env("keycloak").unwrap().kind,
+        ProviderKind::Oidc
+    );
+    assert_eq!(
+        ProviderConfig::from_env("custom").unwrap().kind,
+        ProviderKind::OAuth2
+    );
+    assert!(ProviderConfig::from_env("unconfigured").is_err());
+}
+
+#[tokio::test]
+async fn test_gitlab_base_url() {
+    let config = ProviderConfig {
+        base_url: Some("https://gitlab.example.com/".into()),
+        ..config("gitlab", ProviderKind::GitLab)
+    };
+
+    let provider = config.build(8080).await.unwrap();
+    let (url, _) = provider.authorize_url();
+
+    assert_eq!(provider.name(), "gitlab");
+    assert_eq!(url.host_str(), Some("gitlab.example.com"));
+    assert_eq!(url.path(), "/oauth/authorize");
+}
+
+#[tokio::test]
+async fn test_oauth2_requires_endpoints() {
+    assert!(
+        config("custom", ProviderKind::OAuth2)
+            .build(8080)
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn test_oidc_discovery() {
+    let discovery = warp::path!(
+        "realms" / "codectrl" / ".well-known" / "openid-configuration"
+    )
+    .map(|| {
+        warp::reply::json(&json!({
+            "issuer": "http://127.0.0.1:8093/realms/codectrl",
+            "authorization_endpoint": "http://127.0.0.1:8093/realms/codectrl/auth",
+            "token_endpoint": "http://127.0.0.1:8093/realms/codectrl/token",
+            "userinfo_endpoint": "http://127.0.0.1:8093/realms/codectrl/userinfo",
+        }))
+    });
+
+    tokio::spawn(warp::serve(discovery).run(([127, 0, 0, 1], 8093)));
+
+    let config = ProviderConfig {
+        base_url: Some("http://127.0.0.1:8093/realms/codectrl".into()),
+        ..config("keycloak", ProviderKind::Oidc)
+    };
+
+    let provider = config.build(8080).await.unwrap();
+    let (url, _) = provider.authorize_url();
+    let query = url.query_pairs().into_owned().collect::<HashMap<_, _>>();
+
+    assert_eq!(url.path(), "/realms/codectrl/auth");
+    assert_eq!(query["scope"], "openid profile email");
+    assert_eq!(
+        query["redirect_uri"],
+        "http://localhost:8080/oauth/register/keycloak"
+    );
+}
@@ -0,0 +1,141 @@
+use codectrl_server::{
+    otlp::{
+        parse_stacktrace, record_to_log, severity_of_number, CODE_FILEPATH,
+        CODE_FUNCTION, CODE_LINENO, EXCEPTION_MESSAGE, EXCEPTION_STACKTRACE,
+        EXCEPTION_TYPE, SDK_LANGUAGE,
+    },
+    proto::opentelemetry::proto::{
+        common::v1::{any_value::Value, AnyValue, KeyValue},
+        logs::v1::{LogRecord, SeverityNumber},
+    },
+    severity::Severity,
+};
+
+fn attribute(key: &str, value: Value) -> KeyValue {
+    KeyValue {
+        key: key.into(),
+        value: Some(AnyValue { value: Some(value) }),
+    }
+}
+
+#[test]
+fn test_code_attributes() {
+    let record = LogRecord {
+        body: Some(AnyValue {
+            value: Some(Value::StringValue("User logged in".into())),
+        }),
+        attributes: vec![
+            attribute(CODE_FILEPATH, Value::StringValue("app/views.py".into())),
+            attribute(CODE_LINENO, Value::IntValue(42)),
+            attribute(CODE_FUNCTION, Value::StringValue("login".into())),
+        ],
+        ..Default::default()
+    };
+    let resource = vec![attribute(SDK_LANGUAGE, Value::StringValue("python".into()))];
+
+    let log = record_to_log(&record, &resource);
+
+    assert_eq!(log.message, "User logged in");
+    assert_eq!(log.message_type, "String");
+    assert_eq!(log.file_name, "app/views.py");
+    assert_eq!(log.line_number, 42);
+    assert_eq!(log.language, "Python");
+    assert_eq!(log.stack.len(), 1);
+    assert_eq!(log.stack[0].file_path, "app/views.py");
+    assert_eq!(log.stack[0].code, "login");
+}
+
+#[test]
+fn test_exception_attributes() {
+    let record = LogRecord {
+        attributes: vec![
+            attribute(EXCEPTION_TYPE, Value::StringValue("ValueError".into())),
+            attribute(EXCEPTION_MESSAGE, Value::StringValue("bad value".into())),
+            attribute(
+                EXCEPTION_STACKTRACE,
+                Value::StringValue(
+                    "Traceback (most recent call last):\n  File \"main.py\", line 3, in \
+                     <module>\n    run()\n  File \"app.py\", line 10, in run\n    raise \
+                     ValueError(\"bad value\")\nValueError: bad value"
+                        .into(),
+                ),
+            ),
+        ],
+        ..Default::default()
+    };
+
+    let log = record_to_log(&record, &[]);
+
+    assert_eq!(log.message, "ValueError: bad value");
+    assert!(log.file_name.is_empty());
+    assert_eq!(log.stack.len(), 2);
+    assert_eq!(log.stack[0].file_path, "main.py");
+    assert_eq!(log.stack[0].code, "run()");
+    assert_eq!(log.stack[1].file_path, "app.py");
+    assert_eq!(log.stack[1].line_number, 10);
+    assert_eq!(log.stack[1].code, "raise ValueError(\"bad value\")");
+}
+
+#[test]
+fn test_java_stacktrace() {
+    let stack = parse_stacktrace(
+        "java.lang.IllegalStateException: oops\n\tat \
+         com.example.Service.handle(Service.java:27)\n\tat \
+         com.example.Main.main(Main.java:8)\n\tat java.base/Foo.bar(Native Method)",
+    );
+
+    // Outermost call first.
+    assert_eq!(stack.len(), 2);
+    assert_eq!(stack[0].file_path, "Main.java");
+    assert_eq!(stack[0].line_number, 8);
+    assert_eq!(stack[0].code, "com.example.Main.main");
+    assert_eq!(stack[1].file_path, "Service.java");
+}
+
+#[test]
+fn test_javascript_stacktrace() {
+    let stack = parse_stacktrace(
+        "Error: oops\n    at handler (/app/src/server.js:12:9)\n    at \
+         /app/src/index.js:3:1",
+    );
+
+    assert_eq!(stack.len(), 2);
+    assert_eq!(stack[0].file_path, "/app/src/index.js");
+    assert_eq!(stack[0].column_number, 1);
+    assert_eq!(stack[1].file_path, "/app/src/server.js");
+    assert_eq!(stack[1].line_number, 12);
+    assert_eq!(stack[1].column_number, 9);
+    assert_eq!(stack[1].code, "handler");
+}
+
+#[test]
+fn test_csharp_stacktrace() {
+    let stack =
+        parse_stacktrace("   at App.Program.Main() in C:\\src\\App\\Program.cs:line 14");
+
+    assert_eq!(stack.len(), 1);
+    assert_eq!(stack[0].file_path, "C:\\src\\App\\Program.cs");
+    assert_eq!(stack[0].line_number, 14);
+    assert_eq!(stack[0].code, "App.Program.Main()");
+}
+
+#[test]
+fn test_severity() {
+    let record = LogRecord {
+        severity_number: SeverityNumber::Warn2 as i32,
+        body: Some(AnyValue {
+            value: Some(Value::StringValue("Disk almost full".into())),
+        }),
+        ..Default::default()
+    };
+    assert_eq!(record_to_log(&record, &[]).message_type, "warn");
+
+    let record = LogRecord {
+        severity_text: "CRITICAL".into(),
+        ..record
+    };
+    assert_eq!(record_to_log(&record, &[]).message_type, "CRITICAL");
+
+    assert_eq!(severity_of_number(0), None);
+    assert_eq!(severity_of_number(24), Some(Severity::Fatal));
+}
@@ -0,0 +1,71 @@
+use codectrl_server::{
+    auth::TokenClaims,
+    project::{ProjectMembers, ProjectScope, PROJECT_HEADER},
+};
+use std::time::Duration;
+use tonic::metadata::MetadataMap;
+
+fn claims(projects: &[&str]) -> TokenClaims {
+    TokenClaims::new("tester", Duration::from_secs(60))
+        .with_projects(projects.iter().map(ToString::to_string).collect())
+}
+
+fn metadata(project: Option<&str>) -> MetadataMap {
+    let mut metadata = MetadataMap::new();
+
+    if let Some(project) = project {
+        metadata.insert(PROJECT_HEADER, project.parse().unwrap());
+    }
+
+    metadata
+}
+
+#[test]
+fn test_unlimited_scope() {
+    assert_eq!(
+        ProjectScope::of(None, &metadata(None)).unwrap(),
+        ProjectScope::All
+    );
+    assert_eq!(
+        ProjectScope::of(Some(&claims(&[])), &metadata(Some("team-a"))).unwrap(),
+        ProjectScope::Projects(vec!["team-a".into()])
+    );
+}
+
+#[test]
+fn test_limited_scope() {
+    let claims = claims(&["team-a", "team-b"]);
+
+    let scope = ProjectScope::of(Some(&claims), &metadata(None)).unwrap();
+    assert!(scope.allows(Some("team-b")));
+    assert!(!scope.allows(Some("team-c")));
+    assert!(!scope.allows(None));
+    assert!(scope.log_project().is_err());
+
+    let scope = ProjectScope::of(Some(&claims), &metadata(Some("team-a"))).unwrap();
+    assert_eq!(scope.log_project().unwrap().as_deref(), Some("team-a"));
+
+    assert!(ProjectScope::of(Some(&claims), &metadata(Some("team-c"))).is_err());
+}
+
+#[test]
+fn test_project_members() {
+    let members = ProjectMembers::parse(
+        "team-a=github:alice, gitlab:bob;team-b=gitlab:bob;*=github:admin",
+    );
+
+    assert_eq!(members.projects_of("github:alice").unwrap(), ["team-a"]);
+    assert_eq!(
+        members.projects_of("gitlab:bob").unwrap(),
+        ["team-a", "team-b"]
+    );
+    assert!(members.projects_of("github:admin").unwrap().is_empty());
+    assert!(members.projects_of("github:mallory").is_none());
+
+    assert!(
+        ProjectMembers::default()
+            .projects_of("github:mallory")
+            .unwrap()
+            .is_empty()
+    );
+}
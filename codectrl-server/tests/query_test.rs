@@ -0,0 +1,82 @@
+use codectrl_protobuf_bindings::data::Log;
+use codectrl_server::{
+    proto::log_search::LogQuery,
+    query::{page_size, LogMatcher, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE},
+};
+
+fn log() -> Log {
+    Log {
+        message: "Connection to Database failed".into(),
+        message_type: "String".into(),
+        file_name: "src/db.rs".into(),
+        address: "192.168.1.20:50000".into(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_substring_matching() {
+    let query = LogQuery {
+        message: Some("database".into()),
+        file_name: Some("db.rs".into()),
+        ..Default::default()
+    };
+    assert!(LogMatcher::new(&query).unwrap().matches(&log()));
+
+    let query = LogQuery {
+        case_sensitive: true,
+        ..query
+    };
+    assert!(!LogMatcher::new(&query).unwrap().matches(&log()));
+}
+
+#[test]
+fn test_regex_matching() {
+    let query = LogQuery {
+        message: Some("^connection .* failed$".into()),
+        regex: true,
+        ..Default::default()
+    };
+    assert!(LogMatcher::new(&query).unwrap().matches(&log()));
+
+    let query = LogQuery {
+        message: Some("(unclosed".into()),
+        regex: true,
+        ..Default::default()
+    };
+    assert!(LogMatcher::new(&query).is_err());
+}
+
+#[test]
+fn test_address_glob() {
+    let query = LogQuery {
+        address: Some("192.168.*.20".into()),
+        ..Default::default()
+    };
+    assert!(LogMatcher::new(&query).unwrap().matches(&log()));
+
+    let query = LogQuery {
+        address: Some("10.0.*".into()),
+        ..Default::default()
+    };
+    assert!(!LogMatcher::new(&query).unwrap().matches(&log()));
+}
+
+#[test]
+fn test_page_size() {
+    assert_eq!(page_size(&LogQuery::default()), DEFAULT_PAGE_SIZE);
+    assert_eq!(
+        page_size(&LogQuery {
+            page_size: 5,
+            ..Default::default()
+        }),
+        5
+    );
+    assert_eq!(
+        page_size(&LogQuery {
+            page_size: 100_000,
+            ..Default::default()
+        }),
+        MAX_PAGE_SIZE
+    );
+}
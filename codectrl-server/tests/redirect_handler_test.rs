@@ -1,23 +1,41 @@
-use codectrl_server::redirect_handler::RedirectHandler;
+use codectrl_server::{
+    auth::TokenSecret, oauth::ProviderConfig, project::ProjectMembers,
+    redirect_handler::RedirectHandler,
+};
 use log::info;
+use reqwest::StatusCode;
+use serde_json::json;
+use std::env;
 use tokio::{
     runtime::Handle,
     time::{sleep, Duration},
 };
+use tonic::Code;
+use warp::Filter;
 
 #[tokio::test]
 async fn test_flow() {
     dotenv::from_filename(".env-tests").ok();
     env_logger::init();
 
-    let redirect_handler = RedirectHandler::new(8080);
+    let redirect_handler = RedirectHandler::new(
+        8080,
+        TokenSecret::new("secret".into()),
+        ProjectMembers::default(),
+    );
 
-    redirect_handler.start(Handle::current());
+    redirect_handler.start(Handle::current()).await;
 
     sleep(Duration::new(2, 0)).await;
 
     assert!(redirect_handler.is_started());
 
+    // Starting it again while it's running must not stop it from being seen as
+    // closed later on.
+    redirect_handler.start(Handle::current()).await;
+
+    assert!(redirect_handler.is_started());
+
     redirect_handler.register();
     redirect_handler.register();
     redirect_handler.register();
@@ -35,3 +53,98 @@ async fn test_flow() {
 
     assert!(!redirect_handler.is_started());
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_oauth_login_with_mock_provider() {
+    dotenv::from_filename(".env-tests").ok();
+
+    let access_token = warp::post().and(warp::path("token")).map(|| {
+        warp::reply::json(&json!({
+            "access_token": "mock-access-token",
+            "token_type": "bearer",
+        }))
+    });
+    let user = warp::get()
+        .and(warp::path("user"))
+        .and(warp::header::<String>("authorization"))
+        .map(|authorization: String| {
+            assert_eq!(authorization, "Bearer mock-access-token");
+            warp::reply::json(&json!({ "login": "octocat" }))
+        });
+
+    tokio::spawn(warp::serve(access_token.or(user)).run(([127, 0, 0, 1], 8091)));
+
+    env::set_var("GITHUB_CLIENT_ID", "mock-client-id");
+    env::set_var("GITHUB_CLIENT_SECRET", "mock-client-secret");
+    env::set_var("GITHUB_AUTH_URL", "http://127.0.0.1:8091/authorize");
+    env::set_var("GITHUB_TOKEN_URL", "http://127.0.0.1:8091/token");
+    env::set_var("GITHUB_USER_URL", "http://127.0.0.1:8091/user");
+
+    let token_secret = TokenSecret::new("secret".into());
+    let redirect_handler = RedirectHandler::new(
+        8092,
+        token_secret.clone(),
+        ProjectMembers::parse("team-a=github:octocat"),
+    );
+    let provider = ProviderConfig::from_env("github")
+        .unwrap()
+        .build(redirect_handler.port())
+        .await
+        .unwrap();
+
+    let (_, csrf_state) = provider.authorize_url();
+    let state = csrf_state.secret().clone();
+
+    redirect_handler.add_pending_login(state.clone(), provider);
+    redirect_handler.start(Handle::current()).await;
+
+    sleep(Duration::new(1, 0)).await;
+
+    let client = reqwest::Client::new();
+
+    assert_eq!(redirect_handler.collect_token(&state).unwrap(), None);
+    assert_eq!(
+        redirect_handler
+            .collect_token("wrong-state")
+            .unwrap_err()
+            .code(),
+        Code::NotFound
+    );
+
+    let response = client
+        .get("http://127.0.0.1:8092/oauth/register/github")
+        .query(&[("code", "mock-code"), ("state", "wrong-state")])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = client
+        .get("http://127.0.0.1:8092/oauth/register/github")
+        .query(&[("code", "mock-code"), ("state", state.as_str())])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // The token is now only served over gRPC, not by the redirect handler.
+    let response = client
+        .get(format!("http://127.0.0.1:8092/oauth/token/{state}"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let token = redirect_handler.collect_token(&state).unwrap().unwrap();
+    let claims = token_secret.verify(&token).unwrap();
+    assert_eq!(claims.subject, "github:octocat");
+    assert_eq!(claims.projects, ["team-a"]);
+
+    // A token can only be collected once.
+    assert!(redirect_handler.collect_token(&state).is_err());
+
+    info!(target: "redirect_handler test", "Waiting 10 seconds for the handler to close...");
+    sleep(Duration::new(10, 0)).await;
+
+    assert!(!redirect_handler.is_started());
+}
@@ -0,0 +1,98 @@
+use chrono::{Duration as ChronoDuration, Utc};
+use codectrl_server::retention::{RetentionPolicy, RetentionRow, RetentionTotals};
+use std::time::Duration;
+
+fn rows(ages_in_secs: &[i64], size: i64) -> Vec<RetentionRow> {
+    let now = Utc::now();
+
+    ages_in_secs
+        .iter()
+        .enumerate()
+        .map(|(id, age)| RetentionRow {
+            id: id as i64,
+            uuid: format!("log-{id}"),
+            received_at: now - ChronoDuration::seconds(*age),
+            size,
+        })
+        .collect()
+}
+
+fn totals(rows: &[RetentionRow]) -> RetentionTotals {
+    RetentionTotals {
+        count: rows.len() as i64,
+        bytes: rows.iter().map(|row| row.size).sum(),
+    }
+}
+
+/// Evicts from every stored log, rather than just the oldest page of them.
+fn evict_count(policy: &RetentionPolicy, rows: &[RetentionRow]) -> usize {
+    policy.evict_count(rows, totals(rows), Utc::now())
+}
+
+#[test]
+fn test_unbounded_keeps_everything() {
+    let policy = RetentionPolicy::default();
+
+    assert!(policy.is_unbounded());
+    assert_eq!(evict_count(&policy, &rows(&[300, 200, 100], 10)), 0);
+}
+
+#[test]
+fn test_max_count() {
+    let policy = RetentionPolicy {
+        max_count: Some(2),
+        ..RetentionPolicy::default()
+    };
+
+    assert_eq!(evict_count(&policy, &rows(&[300, 200, 100, 0], 10)), 2);
+}
+
+#[test]
+fn test_max_age() {
+    let policy = RetentionPolicy {
+        max_age: Some(Duration::from_secs(150)),
+        ..RetentionPolicy::default()
+    };
+
+    assert_eq!(evict_count(&policy, &rows(&[300, 200, 100, 0], 10)), 2);
+}
+
+#[test]
+fn test_max_bytes() {
+    let policy = RetentionPolicy {
+        max_bytes: Some(25),
+        ..RetentionPolicy::default()
+    };
+
+    assert_eq!(evict_count(&policy, &rows(&[300, 200, 100, 0], 10)), 2);
+}
+
+#[test]
+fn test_strictest_limit_wins() {
+    let policy = RetentionPolicy {
+        max_count: Some(3),
+        max_age: Some(Duration::from_secs(50)),
+        max_bytes: Some(1000),
+    };
+
+    assert_eq!(evict_count(&policy, &rows(&[300, 200, 100, 0], 10)), 3);
+}
+
+#[test]
+fn test_oldest_page() {
+    let policy = RetentionPolicy {
+        max_count: Some(2),
+        ..RetentionPolicy::default()
+    };
+    let rows = rows(&[500, 400, 300, 200, 100, 0], 10);
+
+    // Every row of the oldest page is evicted, as the totals of all stored logs
+    // are still over the limit.
+    assert_eq!(policy.evict_count(&rows[..3], totals(&rows), Utc::now()), 3);
+
+    let mut remaining = totals(&rows);
+    remaining.evict(&rows[..3]);
+
+    assert_eq!(remaining, totals(&rows[3..]));
+    assert_eq!(policy.evict_count(&rows[3..], remaining, Utc::now()), 1);
+}
@@ -0,0 +1,71 @@
+use codectrl_protobuf_bindings::data::Log;
+use codectrl_server::severity::{self, Severity, SEVERITY_HEADER};
+use tonic::{metadata::MetadataMap, Code};
+
+#[test]
+fn test_from_message_type() {
+    assert_eq!(Severity::from_message_type("WARNING"), Some(Severity::Warn));
+    assert_eq!(Severity::from_message_type("crit"), Some(Severity::Fatal));
+    assert_eq!(Severity::from_message_type("Notice"), Some(Severity::Info));
+    assert_eq!(Severity::from_message_type("INFO2"), Some(Severity::Info));
+    assert_eq!(Severity::from_message_type("FINEST"), Some(Severity::Trace));
+    assert_eq!(
+        Severity::from_message_type("ValueError"),
+        Some(Severity::Error)
+    );
+    assert_eq!(
+        Severity::from_message_type("std::io::Error"),
+        Some(Severity::Error)
+    );
+    assert_eq!(
+        Severity::from_message_type("java.lang.IllegalStateException"),
+        Some(Severity::Error)
+    );
+    assert_eq!(Severity::from_message_type("String"), None);
+    assert_eq!(Severity::from_message_type("i32"), None);
+}
+
+#[test]
+fn test_of() {
+    let log = Log {
+        message_type: "Warning".into(),
+        ..Default::default()
+    };
+    let mut metadata = MetadataMap::new();
+
+    assert_eq!(severity::of(&log, &metadata).unwrap(), Severity::Warn);
+    assert_eq!(
+        severity::of(&Log::default(), &metadata).unwrap(),
+        Severity::Info
+    );
+
+    // The header wins over the message type.
+    metadata.insert(SEVERITY_HEADER, "fatal".parse().unwrap());
+    assert_eq!(severity::of(&log, &metadata).unwrap(), Severity::Fatal);
+
+    metadata.insert(SEVERITY_HEADER, "very bad".parse().unwrap());
+    assert_eq!(
+        severity::of(&log, &metadata).unwrap_err().code(),
+        Code::InvalidArgument
+    );
+}
+
+#[test]
+fn test_ordering() {
+    assert!(Severity::Trace < Severity::Debug);
+    assert!(Severity::Error < Severity::Fatal);
+    assert_eq!(
+        Severity::Warn.and_above(),
+        vec![Severity::Warn, Severity::Error, Severity::Fatal]
+    );
+    assert_eq!(Severity::Trace.and_above(), Severity::ALL.to_vec());
+}
+
+#[test]
+fn test_display_round_trip() {
+    for severity in Severity::ALL {
+        assert_eq!(severity.to_string().parse::<Severity>(), Ok(severity));
+    }
+
+    assert!("nothing".parse::<Severity>().is_err());
+}
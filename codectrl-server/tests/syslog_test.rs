@@ -0,0 +1,188 @@
+use codectrl_server::syslog::{read_record, SyslogRecord, MAX_RECORD_SIZE};
+use serde_json::json;
+use std::io::ErrorKind;
+use tokio::io::BufReader;
+
+#[test]
+fn test_rfc5424() {
+    let record = SyslogRecord::parse(
+        "<165>1 2003-10-11T22:14:15.003Z mymachine.example.com evntslog 1234 ID47 \
+         [exampleSDID@32473 iut=\"3\" eventSource=\"Application\"] An application event \
+         log entry...\n",
+    );
+
+    assert_eq!(record.facility, 20);
+    assert_eq!(record.severity, 5);
+    assert_eq!(record.severity_name(), "Notice");
+    assert_eq!(record.facility_name(), "local4");
+    assert_eq!(
+        record.timestamp.as_deref(),
+        Some("2003-10-11T22:14:15.003Z")
+    );
+    assert_eq!(record.hostname.as_deref(), Some("mymachine.example.com"));
+    assert_eq!(record.app_name.as_deref(), Some("evntslog"));
+    assert_eq!(record.proc_id.as_deref(), Some("1234"));
+    assert_eq!(record.msg_id.as_deref(), Some("ID47"));
+    assert_eq!(
+        record.structured_data.as_deref(),
+        Some("[exampleSDID@32473 iut=\"3\" eventSource=\"Application\"]")
+    );
+    assert_eq!(record.message, "An application event log entry...");
+}
+
+#[test]
+fn test_rfc5424_nil_values() {
+    let record = SyslogRecord::parse(
+        "<34>1 - - su - - [a@1 x=\"]\"][b@1] \u{feff}'su root' failed",
+    );
+
+    assert_eq!(record.severity_name(), "Critical");
+    assert_eq!(record.hostname, None);
+    assert_eq!(record.app_name.as_deref(), Some("su"));
+    assert_eq!(record.proc_id, None);
+    assert_eq!(
+        record.structured_data.as_deref(),
+        Some("[a@1 x=\"]\"][b@1]")
+    );
+    assert_eq!(record.message, "'su root' failed");
+
+    let record = SyslogRecord::parse("<14>1 - host app - - -");
+    assert_eq!(record.structured_data, None);
+    assert!(record.message.is_empty());
+}
+
+#[test]
+fn test_rfc3164() {
+    let record =
+        SyslogRecord::parse("<34>Oct 11 22:14:15 mymachine su[230]: 'su root' failed");
+
+    assert_eq!(record.facility_name(), "auth");
+    assert_eq!(record.severity_name(), "Critical");
+    assert_eq!(record.timestamp.as_deref(), Some("Oct 11 22:14:15"));
+    assert_eq!(record.hostname.as_deref(), Some("mymachine"));
+    assert_eq!(record.app_name.as_deref(), Some("su"));
+    assert_eq!(record.proc_id.as_deref(), Some("230"));
+    assert_eq!(record.message, "'su root' failed");
+
+    let record = SyslogRecord::parse("<13>Oct  5 01:02:03 cron: job started");
+    assert_eq!(record.hostname, None);
+    assert_eq!(record.app_name.as_deref(), Some("cron"));
+    assert_eq!(record.message, "job started");
+}
+
+#[test]
+fn test_unstructured() {
+    let record = SyslogRecord::parse("just some text");
+
+    assert_eq!(record.facility_name(), "user");
+    assert_eq!(record.severity_name(), "Notice");
+    assert_eq!(record.timestamp, None);
+    assert_eq!(record.app_name, None);
+    assert_eq!(record.message, "just some text");
+
+    // Priorities above 191 are invalid, so the whole record is the message.
+    let record = SyslogRecord::parse("<200>hello");
+    assert_eq!(record.message, "<200>hello");
+}
+
+#[test]
+fn test_into_log() {
+    let log =
+        SyslogRecord::parse("<11>1 - web01 nginx 42 - - upstream timed out").into_log();
+
+    assert_eq!(log.message, "upstream timed out");
+    assert_eq!(log.message_type, "Error");
+    assert_eq!(log.file_name, "nginx");
+    assert!(log.address.is_empty());
+    assert!(log.warnings.is_empty());
+}
+
+#[test]
+fn test_fields() {
+    let record = SyslogRecord::parse(
+        "<165>1 2003-10-11T22:14:15.003Z mymachine.example.com evntslog 1234 ID47 \
+         [exampleSDID@32473 iut=\"3\" eventSource=\"App\\\"lication\"][empty@1] An \
+         application event log entry...",
+    );
+
+    assert_eq!(
+        serde_json::Value::Object(record.fields()),
+        json!({
+            "syslog": {
+                "facility": "local4",
+                "timestamp": "2003-10-11T22:14:15.003Z",
+                "proc_id": "1234",
+                "msg_id": "ID47",
+                "structured_data": {
+                    "exampleSDID@32473": {
+                        "iut": "3",
+                        "eventSource": "App\"lication",
+                    },
+                    "empty@1": {},
+                },
+            },
+        })
+    );
+
+    let record = SyslogRecord::parse("<13>Oct  5 01:02:03 cron: job started");
+
+    assert_eq!(
+        serde_json::Value::Object(record.fields()),
+        json!({ "syslog": { "facility": "user", "timestamp": "Oct  5 01:02:03" } })
+    );
+}
+
+#[tokio::test]
+async fn test_read_records() {
+    let stream = b"<13>first\n10 <13>second<13>third";
+    let mut reader = BufReader::new(&stream[..]);
+
+    assert_eq!(
+        read_record(&mut reader).await.unwrap().as_deref(),
+        Some(&b"<13>first\n"[..])
+    );
+    assert_eq!(
+        read_record(&mut reader).await.unwrap().as_deref(),
+        Some(&b"<13>second"[..])
+    );
+    assert_eq!(
+        read_record(&mut reader).await.unwrap().as_deref(),
+        Some(&b"<13>third"[..])
+    );
+    assert_eq!(read_record(&mut reader).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_oversized_records_are_rejected() {
+    let mut stream = vec![b'<'];
+    stream.resize(MAX_RECORD_SIZE, b'a');
+    stream.extend_from_slice(b"rest of the record\n<13>next\n");
+    let mut reader = BufReader::new(&stream[..]);
+
+    assert_eq!(
+        read_record(&mut reader).await.unwrap_err().kind(),
+        ErrorKind::InvalidData
+    );
+
+    // A record that fits exactly, newline included, is still accepted.
+    let mut stream = vec![b'<'];
+    stream.resize(MAX_RECORD_SIZE - 1, b'a');
+    stream.push(b'\n');
+    let mut reader = BufReader::new(&stream[..]);
+
+    assert_eq!(
+        read_record(&mut reader)
+            .await
+            .unwrap()
+            .map(|record| record.len()),
+        Some(MAX_RECORD_SIZE)
+    );
+
+    let stream = format!("{} <13>", MAX_RECORD_SIZE + 1);
+    let mut reader = BufReader::new(stream.as_bytes());
+
+    assert_eq!(
+        read_record(&mut reader).await.unwrap_err().kind(),
+        ErrorKind::InvalidData
+    );
+}